@@ -0,0 +1,197 @@
+use crate::config::AfkConfig;
+use crate::logger::ServerLog;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use steel_core::server::Server;
+use steel_utils::locks::SyncMutex;
+use tokio::select;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+use uuid::Uuid;
+
+/// How often the players are checked for movement
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Smallest timeout the auto kick can be set to
+const MIN_KICK_AFTER: Duration = Duration::from_secs(60);
+
+/// Where a player was and where they looked, which changes whenever they do something
+type Sample = ([f64; 3], [f32; 2]);
+
+/// When a player last moved or looked around
+struct Activity {
+    name: String,
+    sample: Sample,
+    since: Instant,
+}
+
+/// The players of one server and how its auto kick is set, by the log of the server
+struct Tracker {
+    log: ServerLog,
+    config: AfkConfig,
+    players: HashMap<Uuid, Activity>,
+}
+
+static TRACKERS: LazyLock<SyncMutex<Vec<Tracker>>> = LazyLock::new(|| SyncMutex::new(Vec::new()));
+
+/// A player that has not moved for a while
+#[derive(Debug, Clone)]
+pub struct IdlePlayer {
+    pub name: String,
+    pub uuid: Uuid,
+    pub idle: Duration,
+}
+
+/// Runs `f` on the tracker of the server logging into `log`
+fn with_tracker<R>(log: ServerLog, f: impl FnOnce(&mut Tracker) -> R) -> Option<R> {
+    TRACKERS
+        .lock()
+        .iter_mut()
+        .find(|tracker| tracker.log == log)
+        .map(f)
+}
+
+/// Tracks when the players of `server` were last active until `server_token` is cancelled, kicking idle ones if
+/// the auto kick is enabled
+pub fn spawn(server: Arc<Server>, config: AfkConfig, server_token: CancellationToken) {
+    let log = ServerLog::current();
+    {
+        let mut trackers = TRACKERS.lock();
+        trackers.retain(|tracker| tracker.log != log);
+        trackers.push(Tracker {
+            log,
+            config,
+            players: HashMap::new(),
+        });
+    }
+    let mut interval = time::interval(SAMPLE_INTERVAL);
+
+    tokio::spawn(async move {
+        loop {
+            select! {
+                () = server_token.cancelled() => return,
+                _ = interval.tick() => (),
+            }
+            let kick = with_tracker(log, |tracker| sample(&server, tracker)).unwrap_or_default();
+            for (uuid, message) in kick {
+                kick_player(&server, uuid, &message);
+            }
+        }
+    });
+}
+
+/// Records which players of `server` moved, returning the ones to kick with the message to kick them with
+fn sample(server: &Server, tracker: &mut Tracker) -> Vec<(Uuid, String)> {
+    let now = Instant::now();
+    let mut online = HashMap::new();
+    for world in server.worlds.values() {
+        world.players.iter_players(|_, player| {
+            let position = player.position();
+            let (yaw, pitch) = player.rotation();
+            online.insert(
+                player.gameprofile.id,
+                (
+                    player.gameprofile.name.clone(),
+                    ([position.x, position.y, position.z], [yaw, pitch]),
+                ),
+            );
+            true
+        });
+    }
+
+    tracker.players.retain(|uuid, _| online.contains_key(uuid));
+    for (uuid, (name, sample)) in online {
+        let activity = tracker.players.entry(uuid).or_insert(Activity {
+            name,
+            sample,
+            since: now,
+        });
+        if activity.sample != sample {
+            activity.sample = sample;
+            activity.since = now;
+        }
+    }
+
+    if !tracker.config.auto_kick {
+        return Vec::new();
+    }
+    let kick_after = Duration::from_secs(tracker.config.kick_after_secs);
+    tracker
+        .players
+        .iter()
+        .filter(|(_, activity)| now.duration_since(activity.since) >= kick_after)
+        .map(|(uuid, _)| (*uuid, tracker.config.kick_message.clone()))
+        .collect()
+}
+
+fn kick_player(server: &Server, uuid: Uuid, message: &str) {
+    for world in server.worlds.values() {
+        world.players.iter_players(|_, player| {
+            if player.gameprofile.id != uuid {
+                return true;
+            }
+            info!("Kicked {} for being idle", player.gameprofile.name);
+            player.kick(message);
+            false
+        });
+    }
+}
+
+/// The players of the server logging into `log` that have been idle for longer than its threshold, longest first
+pub fn idle_players(log: ServerLog) -> Vec<IdlePlayer> {
+    let mut players = with_tracker(log, |tracker| {
+        let threshold = Duration::from_secs(tracker.config.idle_secs);
+        tracker
+            .players
+            .iter()
+            .map(|(uuid, activity)| IdlePlayer {
+                name: activity.name.clone(),
+                uuid: *uuid,
+                idle: activity.since.elapsed(),
+            })
+            .filter(|player| player.idle >= threshold)
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+    players.sort_by_key(|player| Reverse(player.idle));
+    players
+}
+
+/// Whether idle players of the server logging into `log` are kicked, and after how long
+pub fn auto_kick(log: ServerLog) -> Option<(bool, Duration)> {
+    with_tracker(log, |tracker| {
+        (
+            tracker.config.auto_kick,
+            Duration::from_secs(tracker.config.kick_after_secs),
+        )
+    })
+}
+
+/// Turns the auto kick of the server logging into `log` on or off until the next restart
+pub fn toggle_auto_kick(log: ServerLog) {
+    with_tracker(log, |tracker| {
+        tracker.config.auto_kick = !tracker.config.auto_kick;
+        let state = if tracker.config.auto_kick {
+            "on"
+        } else {
+            "off"
+        };
+        log.scope(|| info!("Turned the auto kick of idle players {state}"));
+    });
+}
+
+/// Changes how long players of the server logging into `log` may be idle before they are kicked
+pub fn adjust_kick_after(log: ServerLog, step: Duration, longer: bool) {
+    with_tracker(log, |tracker| {
+        let current = Duration::from_secs(tracker.config.kick_after_secs);
+        let next = if longer {
+            current.saturating_add(step)
+        } else {
+            current.saturating_sub(step).max(MIN_KICK_AFTER)
+        };
+        tracker.config.kick_after_secs = next.as_secs();
+    });
+}
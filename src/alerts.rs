@@ -0,0 +1,119 @@
+use crate::logger::ServerLog;
+use crate::notifications::{self, NotificationKind, Severity};
+use crate::status;
+use std::backtrace::Backtrace;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use std::{fs, panic, thread};
+use steel_core::server::Server;
+use tokio::select;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+static PANICKED: AtomicBool = AtomicBool::new(false);
+
+/// Where a panic is written when it aborts the process, with the name of its server on the first line
+const CRASH_FILE: &str = "crash-report.txt";
+
+/// Reports every thread that panics, installed once for every server
+///
+/// A panic that aborts the process ends it before a notification could be delivered, so it is written into
+/// `crash-report.txt` instead and reported by `report_previous_crash` on the next start.
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        PANICKED.store(true, Ordering::Relaxed);
+        if cfg!(panic = "abort") {
+            let server = notifications::server_name(ServerLog::current()).unwrap_or_default();
+            let thread = thread::current();
+            let report = format!(
+                "{server}\nThread {} {info}\n\n{}",
+                thread.name().unwrap_or("<unnamed>"),
+                Backtrace::force_capture()
+            );
+            let _ = fs::write(CRASH_FILE, report);
+        } else {
+            notifications::send(NotificationKind::Crash, Severity::Error, info.to_string());
+        }
+        previous(info);
+    }));
+}
+
+/// Sends the crash notification of the server called `name` if the process aborted on a panic of it last time
+pub fn report_previous_crash(name: &str) {
+    let Ok(content) = fs::read_to_string(CRASH_FILE) else {
+        return;
+    };
+    let Some((server, report)) = content.split_once('\n') else {
+        return;
+    };
+    if server != name {
+        return;
+    }
+    let _ = fs::remove_file(CRASH_FILE);
+    let panic = report.lines().next().unwrap_or_default();
+    notifications::send(
+        NotificationKind::Crash,
+        Severity::Error,
+        format!("The server crashed before this start: {panic}"),
+    );
+}
+
+/// Whether any thread panicked since the server started, including tasks that were isolated by tokio
+#[must_use]
+pub fn panicked() -> bool {
+    PANICKED.load(Ordering::Relaxed)
+}
+
+/// Watches the server for joining and leaving players and a tick rate below `low_tps`,
+/// sending a notification for each until `server_token` is cancelled
+pub fn spawn(server: Arc<Server>, low_tps: f32, server_token: CancellationToken) {
+    let mut interval = time::interval(Duration::from_secs(1));
+    tokio::spawn(async move {
+        let mut online = HashMap::new();
+        let mut lagging = false;
+
+        loop {
+            select! {
+                () = server_token.cancelled() => return,
+                _ = interval.tick() => (),
+            }
+
+            let players: HashMap<_, _> = status::players(&server)
+                .into_iter()
+                .map(|player| (player.uuid, player.name))
+                .collect();
+            for (uuid, name) in &players {
+                if !online.contains_key(uuid) {
+                    notifications::send(
+                        NotificationKind::PlayerJoined,
+                        Severity::Info,
+                        format!("{name} joined the server"),
+                    );
+                }
+            }
+            for (uuid, name) in &online {
+                if !players.contains_key(uuid) {
+                    notifications::send(
+                        NotificationKind::PlayerLeft,
+                        Severity::Info,
+                        format!("{name} left the server"),
+                    );
+                }
+            }
+            online = players;
+
+            let tps = status::ticks(&server).tps;
+            if !lagging && tps < low_tps {
+                notifications::send(
+                    NotificationKind::LowTps,
+                    Severity::Warning,
+                    format!("The server is running at {tps:.1} TPS"),
+                );
+            }
+            lagging = tps < low_tps;
+        }
+    });
+}
@@ -0,0 +1,195 @@
+use crate::command::{self, CommandOrigin};
+use crate::config::ApiConfig;
+use crate::metrics;
+use crate::status::{self, MemoryInfo, PlayerInfo, TickInfo};
+use axum::extract::{ConnectInfo, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use steel_core::server::Server;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+#[derive(Clone)]
+struct ApiState {
+    server: Arc<Server>,
+    token: Arc<str>,
+    server_token: CancellationToken,
+}
+
+impl ApiState {
+    fn authorize(&self, headers: &HeaderMap) -> Result<(), StatusCode> {
+        let provided = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        if provided == Some(&*self.token) {
+            Ok(())
+        } else {
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+
+    /// Runs `command` for the client at `client` once its token is checked
+    fn dispatch(
+        &self,
+        headers: &HeaderMap,
+        client: SocketAddr,
+        command: String,
+    ) -> Result<Json<CommandResponse>, StatusCode> {
+        self.authorize(headers)?;
+        if self.server_token.is_cancelled() {
+            return Err(StatusCode::SERVICE_UNAVAILABLE);
+        }
+
+        let client = client.to_string();
+        let output = command::dispatch(&self.server, CommandOrigin::Api, Some(&client), command);
+        Ok(Json(CommandResponse { output }))
+    }
+
+    /// Runs `action` on the player named in `request` once its token is checked
+    ///
+    /// An empty name or one with whitespace is a bad request, the rest of it would end up in the reason. A player that
+    /// isn't online is not found.
+    fn moderate(
+        &self,
+        headers: &HeaderMap,
+        client: SocketAddr,
+        action: &str,
+        request: &ModerationRequest,
+    ) -> Result<Json<CommandResponse>, StatusCode> {
+        self.authorize(headers)?;
+        let name = request.player.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        let player = status::players(&self.server)
+            .into_iter()
+            .find(|player| player.name.eq_ignore_ascii_case(name))
+            .ok_or(StatusCode::NOT_FOUND)?;
+
+        let command = format!("{action} {} {}", player.name, request.reason.trim());
+        self.dispatch(headers, client, command.trim_end().to_owned())
+    }
+}
+
+#[derive(Deserialize)]
+struct CommandRequest {
+    command: String,
+}
+
+#[derive(Deserialize)]
+struct ModerationRequest {
+    player: String,
+    #[serde(default)]
+    reason: String,
+}
+
+#[derive(Serialize)]
+struct CommandResponse {
+    output: Vec<String>,
+}
+
+/// Starts the admin api if it is enabled, it stops once `server_token` is cancelled
+pub fn spawn(server: Arc<Server>, config: ApiConfig, server_token: CancellationToken) {
+    if !config.enabled {
+        return;
+    }
+    if config.token.is_empty() {
+        warn!("The admin api is enabled but has no token set, refusing to start");
+        return;
+    }
+
+    let state = ApiState {
+        server,
+        token: config.token.into(),
+        server_token: server_token.clone(),
+    };
+    let router = Router::new()
+        .route("/players", get(players))
+        .route("/tps", get(tps))
+        .route("/memory", get(memory))
+        .route("/plugins", get(plugins))
+        .route("/metrics", get(prometheus))
+        .route("/command", post(run_command))
+        .route("/kick", post(kick))
+        .route("/ban", post(ban))
+        .with_state(state);
+
+    tokio::spawn(async move {
+        let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, config.port));
+        let listener = match TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                warn!("Failed to bind the admin api to {addr}: {err}");
+                return;
+            }
+        };
+        info!("Admin api running on {addr}");
+
+        // the address of the client is recorded in the audit log along with its commands
+        let service = router.into_make_service_with_connect_info::<SocketAddr>();
+        if let Err(err) = axum::serve(listener, service)
+            .with_graceful_shutdown(server_token.cancelled_owned())
+            .await
+        {
+            warn!("Admin api stopped: {err}");
+        }
+    });
+}
+
+async fn players(State(state): State<ApiState>) -> Json<Vec<PlayerInfo>> {
+    Json(status::players(&state.server))
+}
+
+async fn tps(State(state): State<ApiState>) -> Json<TickInfo> {
+    Json(status::ticks(&state.server))
+}
+
+async fn memory() -> Json<MemoryInfo> {
+    Json(status::memory())
+}
+
+async fn plugins() -> Json<Vec<String>> {
+    Json(status::plugins())
+}
+
+async fn prometheus(State(state): State<ApiState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics::render(&state.server),
+    )
+}
+
+async fn run_command(
+    State(state): State<ApiState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(request): Json<CommandRequest>,
+) -> Result<Json<CommandResponse>, StatusCode> {
+    state.dispatch(&headers, client, request.command)
+}
+
+async fn kick(
+    State(state): State<ApiState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(request): Json<ModerationRequest>,
+) -> Result<Json<CommandResponse>, StatusCode> {
+    state.moderate(&headers, client, "kick", &request)
+}
+
+async fn ban(
+    State(state): State<ApiState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(request): Json<ModerationRequest>,
+) -> Result<Json<CommandResponse>, StatusCode> {
+    state.moderate(&headers, client, "ban", &request)
+}
@@ -0,0 +1,53 @@
+use crate::command::CommandOrigin;
+use crate::logger::ServerLog;
+use anyhow::Context;
+use chrono::Local;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::LazyLock;
+use steel_utils::locks::SyncMutex;
+
+/// The source executed commands are echoed into the log with, which the audit filter shows
+pub const SOURCE: &str = "audit log";
+
+/// The audit file of every server, by the log of the server
+static FILES: LazyLock<SyncMutex<Vec<(ServerLog, File)>>> =
+    LazyLock::new(|| SyncMutex::new(Vec::new()));
+
+/// Appends every command executed on the server the current thread logs into to the audit file at `path`
+pub fn open(path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("failed to create the audit log directory")?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    let log = ServerLog::current();
+    let mut files = FILES.lock();
+    files.retain(|(file_log, _)| *file_log != log);
+    files.push((log, file));
+    Ok(())
+}
+
+/// Records `command` executed from `origin` by `client` in the audit file of the current server
+///
+/// The client is whoever sent the command, like the SSH user and address or the address of an RCON or api client,
+/// written as `-` when the command came from the local console or the server itself.
+pub fn record(origin: CommandOrigin, client: Option<&str>, command: &str) {
+    let log = ServerLog::current();
+    let mut files = FILES.lock();
+    let Some((_, file)) = files.iter_mut().find(|(file_log, _)| *file_log == log) else {
+        return;
+    };
+    let time = Local::now().format("%Y-%m-%d %H:%M:%S");
+    // a full disk must not keep commands from running
+    let _ = writeln!(
+        file,
+        "{time} {} {} {command}",
+        origin.name(),
+        client.unwrap_or("-")
+    );
+}
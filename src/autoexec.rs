@@ -0,0 +1,51 @@
+use crate::command::{self, CommandOrigin};
+use crate::logger::ServerLog;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use steel_core::server::Server;
+use tokio::task;
+use tracing::{error, info, warn};
+
+/// Runs the commands in the file at `path` on the blocking pool, so the server future polled alongside isn't held up
+pub async fn run_blocking(server: Arc<Server>, path: PathBuf) {
+    let log = ServerLog::current();
+    let ran = task::spawn_blocking(move || log.scope(|| run(&server, &path)));
+    if ran.await.is_err() {
+        error!("An autoexec command panicked");
+    }
+}
+
+/// Runs every command listed in the file at `path`, one per line.
+///
+/// Blank lines and lines starting with `#` are skipped, a missing file runs nothing.
+fn run(server: &Arc<Server>, path: &Path) {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return,
+        Err(err) => {
+            warn!("Failed to read {}: {err}", path.display());
+            return;
+        }
+    };
+
+    let commands: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    if commands.is_empty() {
+        return;
+    }
+
+    info!(
+        "Running {} commands from {}",
+        commands.len(),
+        path.display()
+    );
+    for command in commands {
+        let command = command.strip_prefix('/').unwrap_or(command);
+        command::dispatch(server, CommandOrigin::Autoexec, None, command.to_owned());
+    }
+}
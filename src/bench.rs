@@ -0,0 +1,46 @@
+use std::time::{Duration, Instant};
+
+/// Times the phases of starting the server, reported as a table once it is done
+pub struct StartupBench {
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl StartupBench {
+    pub const fn new() -> Self {
+        Self { phases: Vec::new() }
+    }
+
+    /// Runs `f` as the phase `name`
+    pub fn phase<R>(&mut self, name: &'static str, f: impl FnOnce() -> R) -> R {
+        let started = Instant::now();
+        let result = f();
+        self.phases.push((name, started.elapsed()));
+        result
+    }
+
+    /// Awaits `future` as the phase `name`
+    pub async fn phase_async<R>(
+        &mut self,
+        name: &'static str,
+        future: impl Future<Output = R>,
+    ) -> R {
+        let started = Instant::now();
+        let result = future.await;
+        self.phases.push((name, started.elapsed()));
+        result
+    }
+
+    /// Every phase with how long it took and its share of the whole startup, followed by the total
+    pub fn table(&self) -> Vec<String> {
+        let total: Duration = self.phases.iter().map(|(_, took)| *took).sum();
+        let mut lines = vec![format!("{:<14}{:>12}{:>8}", "Phase", "Time", "Share")];
+        for (name, took) in &self.phases {
+            let time = format!("{took:.1?}");
+            let share = took.as_secs_f64() / total.as_secs_f64().max(f64::EPSILON) * 100.0;
+            lines.push(format!("{name:<14}{time:>12}{share:>7.1}%"));
+        }
+        let total = format!("{total:.1?}");
+        lines.push(format!("{:<14}{total:>12}", "Total"));
+        lines
+    }
+}
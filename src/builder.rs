@@ -0,0 +1,308 @@
+use crate::config::UiConfig;
+use crate::confirmation::Session;
+use crate::keymap::Keymap;
+use crate::logger::{self, ServerLog};
+use crate::panels::CustomPanel;
+use crate::panels::afk::AfkView;
+use crate::panels::console::Console;
+use crate::panels::datapacks::DatapackList;
+use crate::panels::gamerules::GameruleView;
+use crate::panels::jobs::JobList;
+use crate::panels::performance::PerformanceView;
+use crate::panels::permissions::PermissionsView;
+use crate::panels::player_data::PlayerDataView;
+use crate::panels::players::PlayerList;
+#[cfg(feature = "plugin")]
+use crate::panels::plugin_events::EventMonitorPanel;
+#[cfg(feature = "plugin")]
+use crate::panels::plugin_repl::PluginReplPanel;
+#[cfg(feature = "plugin")]
+use crate::panels::plugins::PluginsPanel;
+use crate::panels::registry::RegistryView;
+use crate::panels::security::SecurityView;
+use crate::panels::stopped::StoppedMenu;
+use crate::panels::worlds::WorldList;
+use crate::status::ServerStatus;
+use crate::{AppEvent, ManagedServer, Restart, SteelApp, notifications, reload, save, terminal};
+use anyhow::bail;
+use futures::future::BoxFuture;
+use ratatui::layout::Position;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use steel_core::server::Server;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tui_input::Input;
+
+/// Configures and creates a `SteelApp`
+pub struct SteelAppBuilder {
+    server: Option<Arc<Server>>,
+    status: Arc<dyn ServerStatus>,
+    /// Where the commands typed into the console go instead of the server
+    commands: Option<mpsc::UnboundedSender<String>>,
+    name: String,
+    servers: Vec<ManagedServer>,
+    ui: UiConfig,
+    token: CancellationToken,
+    server_token: Option<CancellationToken>,
+    tick_rate: Duration,
+    mouse_capture: bool,
+    panels: Vec<Box<dyn CustomPanel>>,
+    events: Option<mpsc::Receiver<AppEvent>>,
+    restarts: HashMap<ServerLog, Restart>,
+    remote: Option<Arc<str>>,
+}
+
+impl SteelAppBuilder {
+    /// Creates a builder for an app controlling `server` with the default settings
+    #[must_use]
+    pub fn new(server: Arc<Server>) -> Self {
+        let mut builder = Self::without_server(server.clone());
+        builder.server = Some(server);
+        builder
+    }
+
+    /// Creates a builder for an app showing a made up server, like the demo, through `status`
+    ///
+    /// The tabs working on the server itself are hidden and commands go nowhere unless `commands` is set.
+    #[must_use]
+    pub(crate) fn without_server(status: Arc<dyn ServerStatus>) -> Self {
+        Self {
+            server: None,
+            status,
+            commands: None,
+            name: String::from("main"),
+            servers: Vec::new(),
+            ui: UiConfig::default(),
+            token: CancellationToken::new(),
+            server_token: None,
+            tick_rate: Duration::from_secs(1),
+            mouse_capture: true,
+            panels: Vec::new(),
+            events: None,
+            restarts: HashMap::new(),
+            remote: None,
+        }
+    }
+
+    /// Uses the settings of the `[ui]` section of the tui config
+    #[must_use]
+    pub fn ui(mut self, ui: UiConfig) -> Self {
+        self.ui = ui;
+        self
+    }
+
+    /// Sets the number of log lines kept in the scrollback
+    #[must_use]
+    pub const fn scrollback(mut self, lines: usize) -> Self {
+        self.ui.scrollback = lines;
+        self
+    }
+
+    /// Sets how often panels showing live server state are redrawn
+    #[must_use]
+    pub const fn tick_rate(mut self, tick_rate: Duration) -> Self {
+        self.tick_rate = tick_rate;
+        self
+    }
+
+    /// Sets whether the app captures the mouse for scrolling, which disables text selection
+    /// in most terminals
+    #[must_use]
+    pub const fn mouse_capture(mut self, mouse_capture: bool) -> Self {
+        self.mouse_capture = mouse_capture;
+        self
+    }
+
+    /// Sets the token that exits the app once cancelled
+    #[must_use]
+    pub fn token(mut self, token: CancellationToken) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// Sets the token the app cancels to stop the server, a child of the app token by default
+    #[must_use]
+    pub fn server_token(mut self, server_token: CancellationToken) -> Self {
+        self.server_token = Some(server_token);
+        self
+    }
+
+    /// Makes the app a remote session of `client`, like one over SSH, that Ctrl+C closes without stopping the server
+    ///
+    /// A remote session stops the server only with the `stop` command, always asking to confirm it first. Its commands
+    /// are recorded in the audit log as sent by `client`, like the user and address of the SSH connection.
+    #[must_use]
+    pub fn remote(mut self, client: impl Into<Arc<str>>) -> Self {
+        self.remote = Some(client.into());
+        self
+    }
+
+    /// Sends the commands typed into the console of the made up server to `commands`
+    #[must_use]
+    pub(crate) fn commands(mut self, commands: mpsc::UnboundedSender<String>) -> Self {
+        self.commands = Some(commands);
+        self
+    }
+
+    /// Sets the name of the server passed to `new` shown in the server switcher
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Adds another server to switch to, stopped with `server_token` and showing `log`
+    #[must_use]
+    pub fn server(
+        mut self,
+        name: impl Into<String>,
+        server: Arc<Server>,
+        server_token: CancellationToken,
+        log: ServerLog,
+    ) -> Self {
+        let status = server.clone();
+        self.servers.push(managed(
+            name.into(),
+            Some(server),
+            status,
+            server_token,
+            log,
+        ));
+        self
+    }
+
+    /// Adds a panel after the built in ones
+    #[must_use]
+    pub fn panel(mut self, panel: impl CustomPanel + 'static) -> Self {
+        self.panels.push(Box::new(panel));
+        self
+    }
+
+    /// Reads input from `events` instead of the terminal
+    #[must_use]
+    pub fn events(mut self, events: mpsc::Receiver<AppEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Lets the menu of the stopped server showing `log` start it again through `restart`
+    ///
+    /// `restart` returns the handle of the new server and the token that stops it.
+    #[must_use]
+    pub fn restart(
+        mut self,
+        log: ServerLog,
+        restart: impl Fn() -> BoxFuture<'static, anyhow::Result<(Arc<Server>, CancellationToken)>>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        self.restarts.insert(log, Arc::new(restart));
+        self
+    }
+
+    /// Creates the app, spawning a task reading terminal events unless `events` was set
+    ///
+    /// Fails if the tick rate is zero, the panels showing live state would be redrawn without pause.
+    ///
+    /// # Panics
+    /// Panics if `events` was not set and this is called outside of a tokio runtime
+    pub fn build(self) -> anyhow::Result<SteelApp> {
+        if self.tick_rate.is_zero() {
+            bail!("the tick rate must be longer than zero");
+        }
+        logger::set_max_lines(self.ui.scrollback);
+
+        let server_token = self
+            .server_token
+            .unwrap_or_else(|| self.token.child_token());
+        let event_rx = self.events.unwrap_or_else(|| terminal::events(&self.token));
+        #[allow(unused_mut)]
+        let mut custom_panels = self.panels;
+        #[cfg(feature = "plugin")]
+        custom_panels.push(Box::new(PluginsPanel::default()));
+        #[cfg(feature = "plugin")]
+        custom_panels.push(Box::new(EventMonitorPanel::default()));
+        #[cfg(feature = "plugin")]
+        custom_panels.push(Box::new(PluginReplPanel::default()));
+
+        let primary = managed(
+            self.name,
+            self.server,
+            self.status,
+            server_token,
+            ServerLog::PRIMARY,
+        );
+        if let Some(commands) = self.commands {
+            let _ = primary.commands.set(commands);
+        }
+        let mut restarts = self.restarts;
+        let mut servers: Vec<ManagedServer> = [primary].into_iter().chain(self.servers).collect();
+        for server in &mut servers {
+            server.restart = restarts.remove(&server.console.log());
+        }
+
+        Ok(SteelApp {
+            servers,
+            selected: 0,
+            event_rx,
+            keymap: Keymap::new(&self.ui.keybinds),
+            panel: self.ui.default_panel,
+            custom_panels,
+            ui: self.ui,
+            tick_rate: self.tick_rate,
+            mouse_capture: self.mouse_capture,
+            suspendable: false,
+            remote: self.remote,
+            session: Session::unique(),
+            suspend_requested: false,
+            confirm: None,
+            inspector: None,
+            notifications: notifications::subscribe(),
+            reloads: reload::subscribe(),
+            restarts: mpsc::unbounded_channel(),
+            toasts: VecDeque::new(),
+            input: Input::new(String::new()),
+            cursor_position: Position::default(),
+            token: self.token,
+            redraw: true,
+            last_frame: None,
+            frame_pending: false,
+            last_wheel: None,
+        })
+    }
+}
+
+/// The state of a server shown in the app, before anything was done with it
+fn managed(
+    name: String,
+    server: Option<Arc<Server>>,
+    status: Arc<dyn ServerStatus>,
+    server_token: CancellationToken,
+    log: ServerLog,
+) -> ManagedServer {
+    ManagedServer {
+        name,
+        server,
+        status,
+        server_token,
+        console: Console::new(log),
+        saves: save::saves(log),
+        players: PlayerList::default(),
+        performance: PerformanceView::default(),
+        player_data: PlayerDataView::default(),
+        worlds: WorldList::default(),
+        afk: AfkView::default(),
+        jobs: JobList::default(),
+        gamerules: GameruleView::default(),
+        datapacks: DatapackList::default(),
+        registry: RegistryView::default(),
+        permissions: PermissionsView::default(),
+        security: SecurityView::default(),
+        stopped_menu: StoppedMenu::default(),
+        commands: OnceLock::new(),
+        restart: None,
+    }
+}
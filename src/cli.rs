@@ -0,0 +1,67 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use tracing::Level;
+
+/// A terminal ui for the Steel Minecraft server
+#[derive(Debug, Clone, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Path of the server config, the tui config is read from `tui.toml` next to it
+    #[arg(long, default_value = "config/config.toml")]
+    pub config: PathBuf,
+    /// Directory of the world to load
+    #[arg(long)]
+    pub world: Option<PathBuf>,
+    /// Port the server listens on, overriding the config
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Print the log to stdout instead of starting the terminal ui
+    #[arg(long)]
+    pub no_tui: bool,
+    /// Most verbose level that gets logged, overriding `RUST_LOG`
+    #[arg(long)]
+    pub log_level: Option<Level>,
+    /// Directory plugins are loaded from, overriding the tui config
+    #[arg(long)]
+    pub plugins_dir: Option<PathBuf>,
+    /// Record the log and executed commands into a replay file
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+    /// Time each phase of starting the server, print them as a table and exit without running it
+    #[arg(long)]
+    pub bench_startup: bool,
+    /// Run the terminal ui against a made up server instead of starting one, for working on the ui
+    #[arg(long)]
+    pub demo: bool,
+    /// Run as a Windows service, passed by the service control manager
+    #[cfg(windows)]
+    #[arg(long, hide = true)]
+    pub service: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// Path of the tui config, next to the server config
+    #[must_use]
+    pub fn tui_config(&self) -> PathBuf {
+        self.config.with_file_name("tui.toml")
+    }
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Check that the config files can be loaded and exit, without creating any that are missing
+    ValidateConfig,
+    /// Play back a session recorded with `--record`
+    Replay {
+        /// The recording to play back
+        file: PathBuf,
+        /// How many times faster than the original session to play
+        #[arg(long, default_value_t = 1.0)]
+        speed: f32,
+    },
+    /// Register this executable as a Windows service
+    #[cfg(windows)]
+    InstallService,
+}
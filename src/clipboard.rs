@@ -0,0 +1,12 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::io::{self, Write, stdout};
+
+/// Copies `text` into the clipboard of the terminal with an OSC 52 escape sequence
+///
+/// This works over SSH and inside tmux with `set-clipboard on`, terminals that don't support it ignore the sequence.
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut out = stdout();
+    write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    out.flush()
+}
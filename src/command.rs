@@ -0,0 +1,241 @@
+use crate::REDRAW;
+use crate::confirmation::Session;
+use crate::logger::{self, ServerLog};
+#[cfg(feature = "plugin")]
+use crate::plugin;
+#[cfg(all(feature = "profiling", unix))]
+use crate::profiler;
+use crate::{
+    audit, datapacks, distance, dump, inspect, maintenance, ops, ping, pregen, recording, reload,
+    save, security, server_list, worlds,
+};
+use chrono::Local;
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Span};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+use steel_core::command::sender::CommandSender;
+use steel_core::server::Server;
+use tokio::sync::mpsc;
+use tokio::task;
+use tracing::{error, info};
+
+thread_local! {
+    static CAPTURE: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+    /// Where the command being dispatched on this thread was issued from
+    static ORIGIN: Cell<Option<CommandOrigin>> = const { Cell::new(None) };
+}
+
+/// The interface a console command was issued from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandOrigin {
+    /// The input line of the terminal ui
+    Console,
+    /// A remote RCON client
+    Rcon,
+    /// A request to the HTTP admin api
+    Api,
+    /// The startup autoexec script
+    Autoexec,
+    /// A log trigger that matched a logged message
+    Trigger,
+}
+
+impl CommandOrigin {
+    /// The name of the interface, as written into the audit log
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Console => "console",
+            Self::Rcon => "rcon",
+            Self::Api => "api",
+            Self::Autoexec => "autoexec",
+            Self::Trigger => "trigger",
+        }
+    }
+}
+
+impl fmt::Display for CommandOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Console => Ok(()),
+            Self::Rcon => f.write_str("[RCON] "),
+            Self::Api => f.write_str("[API] "),
+            Self::Autoexec => f.write_str("[AUTOEXEC] "),
+            Self::Trigger => f.write_str("[TRIGGER] "),
+        }
+    }
+}
+
+/// Echoes `command` into the log and runs it as the console sender.
+///
+/// `client` is who sent it from `origin` for the audit log, like the address of an RCON client.
+/// Returns every console message the command produced while it was running.
+/// The output is logged grouped under the echoed command, even when other lines were logged in the meantime.
+/// Once the command returns, the echo is marked with whether it succeeded and how long it took.
+pub fn dispatch(
+    server: &Arc<Server>,
+    origin: CommandOrigin,
+    client: Option<&str>,
+    command: String,
+) -> Vec<String> {
+    let line = Line::from(vec![
+        Span::raw(Local::now().format("%H:%M:%S ").to_string()).dark_gray(),
+        Span::raw(format!("{origin}> {command}")),
+    ]);
+    let echo = ServerLog::current()
+        .history()
+        .lock()
+        .push_line_from(line, Some(Arc::from(audit::SOURCE)));
+    REDRAW.notify_one();
+    recording::command(origin, &command);
+    audit::record(origin, client, &command);
+
+    let started = Instant::now();
+    let previous = ORIGIN.replace(Some(origin));
+    let (output, succeeded) = logger::grouped_under(echo, || run(server, origin, command));
+    ORIGIN.set(previous);
+    let took = format!(" {} ms", started.elapsed().as_millis());
+    let status = if succeeded {
+        Span::raw(format!(" ✓{took}")).green()
+    } else {
+        Span::raw(format!(" ✗{took}")).red()
+    };
+    if let Some(line) = ServerLog::current().history().lock().line_mut(echo) {
+        line.spans.push(status);
+    }
+    REDRAW.notify_one();
+    output
+}
+
+/// Starts a task running the commands sent into the returned queue one after another as the console of `server`
+///
+/// The commands run on the blocking pool, so a slow one doesn't hold up the task sending them. Their output is logged
+/// into `log` and redrawn as it comes in, their questions are asked in `session`. `client` is who typed them, if the
+/// console is remote.
+pub fn runner(
+    server: Arc<Server>,
+    log: ServerLog,
+    session: Session,
+    client: Option<Arc<str>>,
+) -> mpsc::UnboundedSender<String> {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(command) = receiver.recv().await {
+            let server = server.clone();
+            let client = client.clone();
+            let dispatched = task::spawn_blocking(move || {
+                log.scope(|| {
+                    session.scope(|| {
+                        dispatch(&server, CommandOrigin::Console, client.as_deref(), command)
+                    })
+                })
+            });
+            if dispatched.await.is_err() {
+                log.scope(|| error!("A console command panicked"));
+            }
+        }
+    });
+    sender
+}
+
+/// Runs `command` as the console sender, logging and returning its output and whether it succeeded
+fn run(server: &Arc<Server>, origin: CommandOrigin, command: String) -> (Vec<String>, bool) {
+    if let Some(result) = builtin(server, origin, command.trim()) {
+        let succeeded = result.is_ok();
+        let output = result.unwrap_or_else(|err| vec![format!("{err:#}")]);
+        for line in &output {
+            info!("{line}");
+        }
+        return (output, succeeded);
+    }
+
+    #[cfg(feature = "plugin")]
+    if let Some(output) = plugin::command(&command) {
+        for line in &output {
+            info!("{line}");
+        }
+        return (output, true);
+    }
+
+    #[cfg(feature = "plugin")]
+    let help = command.trim() == "help";
+    CAPTURE.with_borrow_mut(|capture| *capture = Some(Vec::new()));
+    let result =
+        server
+            .command_dispatcher
+            .read()
+            .handle_command(CommandSender::Console, command, server);
+    #[allow(unused_mut)]
+    let mut output = CAPTURE.with_borrow_mut(Option::take).unwrap_or_default();
+
+    // the dispatcher only knows the names of plugin commands, not their hints
+    #[cfg(feature = "plugin")]
+    if help {
+        for line in plugin::commands::help() {
+            info!("{line}");
+            output.push(line);
+        }
+    }
+    (output, result.is_ok())
+}
+
+/// Runs `command` if it is one of the commands of the terminal ui itself, returning its output or why it failed
+fn builtin(
+    server: &Arc<Server>,
+    origin: CommandOrigin,
+    command: &str,
+) -> Option<anyhow::Result<Vec<String>>> {
+    let (name, args) = command.split_once(' ').unwrap_or((command, ""));
+    let result = match (name, args.trim()) {
+        // saves in the background with progress instead of blocking the console until it is done
+        ("save-all", "") => {
+            save::start(server.clone(), ServerLog::current());
+            return Some(Ok(Vec::new()));
+        }
+        ("reload", "config") => reload::reload_server_config(server),
+        ("dump", "") => Ok(dump::command(server)),
+        ("status", args) => server_list::command(server, args),
+        ("ping", args) => ping::command(args),
+        ("distance", args) => distance::command(server, args),
+        ("world", args) => worlds::command(server, args),
+        ("maintenance", args) => maintenance::command(server, args),
+        ("pregen", args) => pregen::command(server, args),
+        // hides the `datapack` command of the server, whose pack ids the terminal ui doesn't know
+        ("datapack", args) => datapacks::command(args),
+        ("perm", args) => ops::command(server, args),
+        (name @ ("block" | "unblock"), args) => security::command(name, args),
+        // only the terminal ui that ran it can show the tree, other interfaces get it as indented lines
+        ("inspect", args) => inspect::command(server, args).map(|(title, nodes)| {
+            match Session::current().filter(|_| origin == CommandOrigin::Console) {
+                Some(session) => {
+                    let output = vec![format!("Inspecting {title}")];
+                    inspect::open(session, title, nodes);
+                    output
+                }
+                None => nodes.iter().flat_map(|node| node.lines(0)).collect(),
+            }
+        }),
+        #[cfg(all(feature = "profiling", unix))]
+        ("profile", args) => profiler::command(args),
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Where the command being dispatched on this thread was issued from
+pub fn origin() -> Option<CommandOrigin> {
+    ORIGIN.get()
+}
+
+/// Records a console message for the command currently being dispatched on this thread
+pub fn capture(message: &str) {
+    CAPTURE.with_borrow_mut(|capture| {
+        if let Some(capture) = capture {
+            capture.push(message.to_owned());
+        }
+    });
+}
@@ -0,0 +1,70 @@
+use crate::plugin::commands;
+
+/// A command known to the input line, with hints for its arguments
+struct Candidate {
+    name: String,
+    args: Vec<String>,
+}
+
+/// The commands handled by the terminal ui and its plugins
+fn candidates() -> Vec<Candidate> {
+    let mut candidates = vec![Candidate {
+        name: "plugins".to_owned(),
+        args: vec![
+            "[list | info | enable | disable | reload | config | profile]".to_owned(),
+            "[name]".to_owned(),
+        ],
+    }];
+    candidates.extend(commands::all().into_iter().map(|command| Candidate {
+        name: command.name,
+        args: command.args,
+    }));
+    candidates
+}
+
+/// Completes the command name typed in `input`, as far as every matching command agrees
+pub fn complete(input: &str) -> Option<String> {
+    if input.contains(' ') {
+        return None;
+    }
+    let matching: Vec<String> = candidates()
+        .into_iter()
+        .map(|candidate| candidate.name)
+        .filter(|name| name.starts_with(input))
+        .collect();
+
+    match matching.as_slice() {
+        [] => None,
+        [name] => Some(format!("{name} ")),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.len(), |common, name| {
+                first
+                    .bytes()
+                    .zip(name.bytes())
+                    .take(common)
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            });
+            first
+                .get(..common)
+                .filter(|common| common.len() > input.len())
+                .map(str::to_owned)
+        }
+    }
+}
+
+/// Hints for the arguments of the command in `input` that are not typed yet
+pub fn hint(input: &str) -> Option<String> {
+    let (name, args) = input.split_once(' ')?;
+    // no hint while an argument is being typed, it would run into it
+    if !args.is_empty() && !args.ends_with(' ') {
+        return None;
+    }
+    let typed = args.split_whitespace().count();
+
+    let candidate = candidates()
+        .into_iter()
+        .find(|candidate| candidate.name == name)?;
+    let remaining: Vec<_> = candidate.args.into_iter().skip(typed).collect();
+    (!remaining.is_empty()).then(|| remaining.join(" "))
+}
@@ -0,0 +1,540 @@
+use crate::keymap::{Action, KeyChord};
+use crate::notifications::NotificationKind;
+use crate::panels::Panel;
+use anyhow::Context;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::num::NonZero;
+use std::path::{Path, PathBuf};
+
+/// Configuration of the terminal ui and the services it hosts, stored next to the server config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TuiConfig {
+    /// Name of the server shown in the server switcher
+    pub name: String,
+    /// Directory plugins are loaded from
+    pub plugins_dir: PathBuf,
+    /// Whether a plugin is reloaded when its file in `plugins_dir` changes
+    pub plugins_hot_reload: bool,
+    /// File of console commands run once the server has started, one per line
+    pub autoexec: PathBuf,
+    /// File every executed command is appended to with its time and origin, nothing is recorded if unset
+    pub audit_log: Option<PathBuf>,
+    /// Resources every plugin may use, only used with the `plugin` feature
+    pub plugin_limits: PluginLimits,
+    /// Settings for saving the server periodically
+    pub autosave: AutosaveConfig,
+    /// Settings for the terminal ui itself
+    pub ui: UiConfig,
+    /// Settings for the RCON server
+    pub rcon: RconConfig,
+    /// Settings for the query protocol answered to server lists and monitoring
+    pub query: QueryConfig,
+    /// Settings for the HTTP admin api
+    pub api: ApiConfig,
+    /// Settings for the remote console served over SSH
+    pub ssh: SshConfig,
+    /// Settings for the notifications sent on server events
+    pub notifications: NotificationsConfig,
+    /// Settings for the watchdog alerting about slow ticks
+    pub watchdog: WatchdogConfig,
+    /// Settings for maintenance mode
+    pub maintenance: MaintenanceConfig,
+    /// Settings for tracking idle players
+    pub afk: AfkConfig,
+    /// Threads of the tokio runtimes the server runs on
+    pub runtime: RuntimeConfig,
+    /// Rules that fire actions when a logged message matches them
+    pub triggers: Vec<TriggerConfig>,
+    /// Other servers managed from the same terminal ui
+    pub servers: Vec<ServerConfig>,
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self {
+            name: String::from("main"),
+            plugins_dir: PathBuf::from("plugins"),
+            plugins_hot_reload: true,
+            autoexec: PathBuf::from("config/autoexec.txt"),
+            audit_log: Some(PathBuf::from("logs/audit.log")),
+            plugin_limits: PluginLimits::default(),
+            autosave: AutosaveConfig::default(),
+            ui: UiConfig::default(),
+            rcon: RconConfig::default(),
+            query: QueryConfig::default(),
+            api: ApiConfig::default(),
+            ssh: SshConfig::default(),
+            notifications: NotificationsConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            afk: AfkConfig::default(),
+            runtime: RuntimeConfig::default(),
+            triggers: Vec::new(),
+            servers: Vec::new(),
+        }
+    }
+}
+
+impl TuiConfig {
+    /// Loads the config from `path`, writing the defaults to it first if it does not exist
+    pub fn load_or_create(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            let config = Self::default();
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).context("failed to create config directory")?;
+            }
+            let content =
+                toml::to_string_pretty(&config).context("failed to serialize tui config")?;
+            fs::write(path, content).context("failed to write tui config")?;
+            return Ok(config);
+        }
+        Self::load(path)
+    }
+
+    /// Loads the tui config at `path` without creating it if it is missing
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path).context("failed to read tui config")?;
+        toml::from_str(&content).context("failed to parse tui config")
+    }
+}
+
+/// Another server managed from the same terminal ui, like a game server next to a lobby
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// Name of the server shown in the server switcher
+    pub name: String,
+    /// Path of its server config, its own `tui.toml` is read from next to it
+    pub config: PathBuf,
+}
+
+/// Settings for the terminal ui itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Number of log lines kept in the scrollback
+    pub scrollback: usize,
+    /// The panel shown on startup
+    pub default_panel: Panel,
+    /// Whether stopping the server with Ctrl+C has to be confirmed
+    pub confirm_stop: bool,
+    /// Lines the console scrolls by per mouse wheel step
+    pub scroll_lines: u16,
+    /// Whether fast flicks of the mouse wheel scroll further, up to four times `scroll_lines` per step
+    pub scroll_acceleration: bool,
+    /// Whether the ui draws on the alternate screen, otherwise its last frame stays in the scrollback after it exits
+    pub alternate_screen: bool,
+    /// Most frames drawn per second, output arriving faster is drawn together in the next frame
+    pub max_fps: u32,
+    /// Colors used by the terminal ui
+    pub theme: Theme,
+    /// Key bindings overriding the defaults, like `"ctrl+q" = "stop_server"`
+    pub keybinds: HashMap<KeyChord, Action>,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            scrollback: 1000,
+            default_panel: Panel::Console,
+            confirm_stop: true,
+            scroll_lines: 3,
+            scroll_acceleration: true,
+            alternate_screen: true,
+            max_fps: 30,
+            theme: Theme::default(),
+            keybinds: HashMap::new(),
+        }
+    }
+}
+
+/// Colors used by the terminal ui
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Color of the prompt in front of the input line
+    pub prompt: Color,
+    /// Color of the selected tab, headers and popup borders
+    pub accent: Color,
+    /// Color of secondary text like unselected tabs
+    pub muted: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            prompt: Color::White,
+            accent: Color::Cyan,
+            muted: Color::DarkGray,
+        }
+    }
+}
+
+/// Settings for saving dirty chunks and player data while the server runs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutosaveConfig {
+    /// Whether the server is saved periodically, otherwise only when it stops
+    pub enabled: bool,
+    /// Seconds between two saves
+    pub interval_secs: u64,
+}
+
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 300,
+        }
+    }
+}
+
+/// Settings for the Minecraft RCON protocol server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RconConfig {
+    /// Whether the RCON server should be started
+    pub enabled: bool,
+    /// The port the RCON server listens on
+    pub port: u16,
+    /// The password clients have to authenticate with, RCON refuses to start if it is empty
+    pub password: String,
+}
+
+impl Default for RconConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 25575,
+            password: String::new(),
+        }
+    }
+}
+
+/// Settings for the query protocol, which server lists and monitoring read the players and plugins with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryConfig {
+    /// Whether query should be answered
+    pub enabled: bool,
+    /// The UDP port query listens on, like in vanilla the game port works as well
+    pub port: u16,
+}
+
+impl Default for QueryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 25565,
+        }
+    }
+}
+
+/// Settings for the HTTP admin api
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Whether the admin api should be started
+    pub enabled: bool,
+    /// The port the admin api listens on
+    pub port: u16,
+    /// The bearer token required by endpoints that change server state, the api refuses to
+    /// start if it is empty
+    pub token: String,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8080,
+            token: String::new(),
+        }
+    }
+}
+
+/// Settings for the remote console served over SSH, only used with the `ssh` feature
+///
+/// Ctrl+C closes an SSH session, the server is stopped from one with the `stop` command after confirming it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SshConfig {
+    /// Whether the SSH server should be started
+    pub enabled: bool,
+    /// The port the SSH server listens on
+    pub port: u16,
+    /// The private host key, generated on first start if it does not exist
+    pub host_key: PathBuf,
+    /// A file of OpenSSH public keys that are allowed to log in, one per line
+    pub authorized_keys: PathBuf,
+}
+
+impl Default for SshConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 2222,
+            host_key: PathBuf::from("config/ssh_host_key"),
+            authorized_keys: PathBuf::from("config/ssh_authorized_keys"),
+        }
+    }
+}
+
+/// Resources every plugin may use, so one misbehaving plugin can't stall the tick
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginLimits {
+    /// Fuel, roughly wasm instructions, a plugin may burn per event callback, unlimited if unset
+    pub fuel: Option<u64>,
+    /// Memory a plugin may grow to in MiB, unlimited if unset
+    pub memory_mib: Option<u64>,
+    /// Milliseconds an event callback may run before it is interrupted, unlimited if unset
+    pub timeout_ms: Option<u64>,
+    /// Whether a plugin whose callback was interrupted is disabled like a crashed one
+    pub disable_on_timeout: bool,
+}
+
+impl Default for PluginLimits {
+    fn default() -> Self {
+        Self {
+            fuel: None,
+            memory_mib: Some(256),
+            timeout_ms: Some(50),
+            disable_on_timeout: true,
+        }
+    }
+}
+
+/// Settings for the notifications sent on server events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Ticks per second below which a notification is sent
+    pub low_tps: f32,
+    /// Webhooks every notification is posted to
+    pub webhooks: Vec<WebhookConfig>,
+    /// Notifications shown on the desktop the server runs on
+    pub desktop: DesktopConfig,
+    /// Emails sent about fatal events
+    pub email: EmailConfig,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            low_tps: 15.0,
+            webhooks: Vec::new(),
+            desktop: DesktopConfig::default(),
+            email: EmailConfig::default(),
+        }
+    }
+}
+
+/// Settings for desktop notifications, shown through the notification daemon when the server runs locally
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DesktopConfig {
+    /// Whether desktop notifications are shown at all
+    pub enabled: bool,
+    /// Kinds of notifications to show, every kind is shown if this is empty
+    pub events: Vec<NotificationKind>,
+    /// Whether every error is shown too, whatever its kind
+    pub errors: bool,
+}
+
+impl Default for DesktopConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            events: vec![
+                NotificationKind::PlayerJoined,
+                NotificationKind::Crash,
+                NotificationKind::SaveFinished,
+            ],
+            errors: true,
+        }
+    }
+}
+
+/// Thresholds of the watchdog, which alerts with escalating severity while ticks take too long
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    /// Whether the tick times are watched
+    pub enabled: bool,
+    /// Milliseconds per tick above which ticks are slow
+    pub slow_mspt: f32,
+    /// Milliseconds per tick above which ticks are critically slow
+    pub critical_mspt: f32,
+    /// Seconds without a completed tick before the server counts as stalled
+    pub stall_secs: u64,
+    /// Whether a diagnostic dump is written once the server stalls
+    pub dump_on_stall: bool,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            slow_mspt: 50.0,
+            critical_mspt: 200.0,
+            stall_secs: 10,
+            dump_on_stall: false,
+        }
+    }
+}
+
+/// Threads of the tokio runtimes the server runs on, half the cores each unless set
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    /// Worker threads of the runtime running the server and its network
+    pub main_workers: Option<NonZero<usize>>,
+    /// Worker threads of the runtime generating and loading chunks
+    pub chunk_workers: Option<NonZero<usize>>,
+    /// Most threads the chunk runtime blocks on while saving and loading chunks, 512 if unset
+    pub io_threads: Option<NonZero<usize>>,
+}
+
+/// Settings for maintenance mode, which only lets whitelisted players on the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    /// Message players that are not whitelisted are kicked with
+    pub kick_message: String,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            kick_message: String::from("The server is under maintenance, please come back later"),
+        }
+    }
+}
+
+/// Settings for tracking idle players, which count as idle while they neither move nor look around
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AfkConfig {
+    /// Seconds after which a player is listed as idle
+    pub idle_secs: u64,
+    /// Whether idle players are kicked
+    pub auto_kick: bool,
+    /// Seconds a player may be idle before they are kicked
+    pub kick_after_secs: u64,
+    /// Message idle players are kicked with
+    pub kick_message: String,
+}
+
+impl Default for AfkConfig {
+    fn default() -> Self {
+        Self {
+            idle_secs: 300,
+            auto_kick: false,
+            kick_after_secs: 1800,
+            kick_message: String::from("You were kicked for being idle"),
+        }
+    }
+}
+
+/// Settings for the emails sent through an SMTP server when something fatal happens
+///
+/// Only errors are emailed, like a crash, a failed save or a plugin crashing again and again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailConfig {
+    /// Whether emails are sent at all
+    pub enabled: bool,
+    /// Host name of the SMTP server
+    pub host: String,
+    /// Port of the SMTP server, the default of `security` is used if unset
+    pub port: Option<u16>,
+    /// How the connection to the SMTP server is secured
+    pub security: SmtpSecurity,
+    /// User to log in to the SMTP server as, no login is attempted if this is empty
+    pub username: String,
+    /// Password to log in to the SMTP server with
+    pub password: String,
+    /// Address the emails are sent from, like `Steel <steel@example.com>`
+    pub from: String,
+    /// Addresses the emails are sent to
+    pub to: Vec<String>,
+    /// Kinds of errors to email, every kind is emailed if this is empty
+    pub events: Vec<NotificationKind>,
+    /// Plugin crashes within an hour it takes before an email is sent about them
+    pub plugin_failures: u32,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: None,
+            security: SmtpSecurity::default(),
+            username: String::new(),
+            password: String::new(),
+            from: String::new(),
+            to: Vec::new(),
+            events: vec![
+                NotificationKind::Crash,
+                NotificationKind::PluginCrashed,
+                NotificationKind::SaveFinished,
+            ],
+            plugin_failures: 3,
+        }
+    }
+}
+
+/// How the connection to an SMTP server is secured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Upgrades a plain connection with STARTTLS, on port 587 by default
+    #[default]
+    Starttls,
+    /// Connects with TLS right away, on port 465 by default
+    Tls,
+    /// Never encrypts the connection, only meant for relays on the same machine
+    None,
+}
+
+/// A Discord or Slack webhook notifications are posted to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Url of the webhook
+    pub url: String,
+    /// Which service the webhook belongs to
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// Kinds of notifications to post, every kind is posted if this is empty
+    #[serde(default)]
+    pub events: Vec<NotificationKind>,
+}
+
+/// The payload format a webhook expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// A Discord channel webhook
+    #[default]
+    Discord,
+    /// A Slack incoming webhook
+    Slack,
+}
+
+/// A rule that fires its actions when a logged message matches `pattern`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerConfig {
+    /// Regular expression matched against every logged message
+    pub pattern: String,
+    /// Console command to run when the pattern matches
+    pub command: Option<String>,
+    /// Notification to send when the pattern matches
+    pub notify: Option<String>,
+    /// Background color of matching lines in the console
+    pub highlight: Option<Color>,
+}
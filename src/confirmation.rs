@@ -0,0 +1,110 @@
+use crate::REDRAW;
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::time::{Duration, Instant};
+use steel_utils::locks::SyncMutex;
+
+/// How long a command waits for an answer before it is declined, nobody may be watching the ui
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Questions waiting for an answer, by the running ui session asked, the first one of a session is shown
+static PROMPTS: LazyLock<SyncMutex<HashMap<Session, VecDeque<Prompt>>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+static NEXT_SESSION: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// The ui session that ran the command being dispatched on this thread
+    static SESSION: Cell<Option<Session>> = const { Cell::new(None) };
+}
+
+/// A terminal ui that commands it runs ask their questions, like the local one or one over SSH
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Session(u64);
+
+impl Session {
+    /// A session no other one was given before
+    pub fn unique() -> Self {
+        Self(NEXT_SESSION.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Runs `f`, asking this session to confirm what the commands dispatched meanwhile on this thread do
+    pub fn scope<T>(self, f: impl FnOnce() -> T) -> T {
+        let previous = SESSION.replace(Some(self));
+        let result = f();
+        SESSION.set(previous);
+        result
+    }
+
+    /// The session that ran the command being dispatched on this thread, if a terminal ui ran it
+    pub fn current() -> Option<Self> {
+        SESSION.get()
+    }
+
+    /// Starts showing the questions asked, once the session is drawn and answers them
+    pub fn open(self) {
+        PROMPTS.lock().entry(self).or_default();
+    }
+
+    /// Stops showing the questions asked, declining the ones still waiting
+    pub fn close(self) {
+        // dropping the senders declines them
+        PROMPTS.lock().remove(&self);
+        REDRAW.notify_one();
+    }
+
+    /// The question waiting for an answer
+    pub fn pending(self) -> Option<String> {
+        // questions that timed out meanwhile are no longer shown
+        let mut prompts = PROMPTS.lock();
+        let prompts = prompts.get_mut(&self)?;
+        let now = Instant::now();
+        prompts.retain(|prompt| prompt.expires > now);
+        prompts.front().map(|prompt| prompt.message.clone())
+    }
+
+    /// Answers the question shown by `pending`
+    pub fn answer(self, confirmed: bool) {
+        if let Some(prompt) = PROMPTS.lock().get_mut(&self).and_then(VecDeque::pop_front) {
+            let _ = prompt.reply.send(confirmed);
+        }
+        REDRAW.notify_one();
+    }
+}
+
+struct Prompt {
+    message: String,
+    expires: Instant,
+    reply: Sender<bool>,
+}
+
+/// Asks whoever ran the command being dispatched on this thread to confirm `message`, blocking until they answer
+///
+/// Commands about to do something destructive call this, like `This will kick 3 players, continue?`. The terminal ui
+/// session that ran the command shows the question in a modal answered with y or n, other sessions don't see it.
+/// Senders that can't be asked, like RCON, the admin api or the stdin console, are taken as confirming, since they
+/// can't answer and asked for the command explicitly.
+pub fn confirm(message: impl Into<String>) -> bool {
+    let Some(session) = SESSION.get() else {
+        return true;
+    };
+
+    let (reply, answer) = mpsc::channel();
+    {
+        let mut prompts = PROMPTS.lock();
+        // a session that isn't drawn can't answer
+        let Some(prompts) = prompts.get_mut(&session) else {
+            return true;
+        };
+        prompts.push_back(Prompt {
+            message: message.into(),
+            expires: Instant::now() + PROMPT_TIMEOUT,
+            reply,
+        });
+    }
+    REDRAW.notify_one();
+    answer.recv_timeout(PROMPT_TIMEOUT).unwrap_or(false)
+}
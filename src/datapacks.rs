@@ -0,0 +1,134 @@
+use crate::reload;
+use anyhow::{Context, bail};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "usage: datapack [enable <name> | disable <name>]";
+
+/// Appended to the file name of a datapack to keep the server from loading it
+const DISABLED_SUFFIX: &str = ".disabled";
+
+/// A datapack installed into the world of a server
+pub struct Datapack {
+    pub name: String,
+    pub enabled: bool,
+    pub description: String,
+    /// Why the server will fail to load the datapack, if it will
+    pub error: Option<String>,
+}
+
+/// The directory the server loads the datapacks of its world from
+fn directory() -> anyhow::Result<PathBuf> {
+    Ok(reload::server_config()?.world_path.join("datapacks"))
+}
+
+/// Every datapack installed for the server the current thread logs into, by name
+pub fn list() -> anyhow::Result<Vec<Datapack>> {
+    let directory = directory()?;
+    let Ok(entries) = fs::read_dir(&directory) else {
+        return Ok(Vec::new());
+    };
+    let mut packs: Vec<Datapack> = entries
+        .flatten()
+        .map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let (name, enabled) = match file_name.strip_suffix(DISABLED_SUFFIX) {
+                Some(name) => (name.to_owned(), false),
+                None => (file_name, true),
+            };
+            let (description, error) = match validate(&entry.path()) {
+                Ok(description) => (description, None),
+                Err(err) => (String::new(), Some(format!("{err:#}"))),
+            };
+            Datapack {
+                name,
+                enabled,
+                description,
+                error,
+            }
+        })
+        .collect();
+    packs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(packs)
+}
+
+/// Checks the `pack.mcmeta` of the datapack at `path`, returning its description
+fn validate(path: &Path) -> anyhow::Result<String> {
+    if path.is_file() {
+        // zipped datapacks can't be looked into without unpacking them
+        return Ok("zip archive".to_owned());
+    }
+    let content = fs::read_to_string(path.join("pack.mcmeta")).context("pack.mcmeta is missing")?;
+    let meta: Value = serde_json::from_str(&content).context("pack.mcmeta is not valid JSON")?;
+    let pack = meta
+        .get("pack")
+        .context("pack.mcmeta has no `pack` section")?;
+    if !pack.get("pack_format").is_some_and(Value::is_u64) {
+        bail!("pack.mcmeta has no `pack_format` number");
+    }
+    Ok(match pack.get("description") {
+        Some(Value::String(description)) => description.clone(),
+        Some(description) => description.to_string(),
+        None => String::new(),
+    })
+}
+
+/// Enables or disables the datapack `name`, which takes effect once the server reloads its datapacks
+///
+/// Only a datapack that [`list`] returns is renamed, so a name can't point outside the datapacks directory.
+fn set_enabled(name: &str, enabled: bool) -> anyhow::Result<()> {
+    let Some(pack) = list()?.into_iter().find(|pack| pack.name == name) else {
+        bail!("there is no datapack called {name}");
+    };
+    if pack.enabled == enabled {
+        bail!(
+            "{name} is already {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    }
+    let directory = directory()?;
+    let enabled_path = directory.join(name);
+    let disabled_path = directory.join(format!("{name}{DISABLED_SUFFIX}"));
+    let (from, to) = if enabled {
+        (disabled_path, enabled_path)
+    } else {
+        (enabled_path, disabled_path)
+    };
+    if to.exists() {
+        bail!("both {} and {} exist", from.display(), to.display());
+    }
+    fs::rename(&from, &to).with_context(|| format!("failed to rename {}", from.display()))
+}
+
+/// Runs the `datapack` console command, returning its output
+///
+/// `datapack` lists the datapacks of the world, `datapack enable <name>` and `datapack disable <name>` switch one on
+/// or off for the next `reload`. It takes the place of the `datapack` command of the server, which can't be reached
+/// from the terminal ui.
+pub fn command(args: &str) -> anyhow::Result<Vec<String>> {
+    match args.split_once(' ') {
+        None if args.is_empty() => Ok(list()?
+            .into_iter()
+            .map(|pack| {
+                let state = if pack.enabled { "enabled" } else { "disabled" };
+                match pack.error {
+                    Some(error) => format!("{} ({state}): {error}", pack.name),
+                    None => format!("{} ({state}): {}", pack.name, pack.description),
+                }
+            })
+            .collect()),
+        Some(("enable", name)) => {
+            set_enabled(name.trim(), true)?;
+            Ok(vec![format!("Enabled {}, `reload` loads it", name.trim())])
+        }
+        Some(("disable", name)) => {
+            set_enabled(name.trim(), false)?;
+            Ok(vec![format!(
+                "Disabled {}, `reload` unloads it",
+                name.trim()
+            )])
+        }
+        _ => bail!(USAGE),
+    }
+}
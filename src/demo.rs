@@ -0,0 +1,278 @@
+use crate::config::UiConfig;
+use crate::logger::LOGGER;
+use crate::status::{Client, PlayerInfo, ServerStatus, TickInfo, WorldInfo};
+use crate::{REDRAW, SteelAppBuilder};
+use ratatui::prelude::*;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use steel_utils::locks::SyncMutex;
+use tokio::select;
+use tokio::sync::mpsc;
+use tokio::time::{self, Instant};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// How often the fake server ticks its log, players and TPS
+const STEP: Duration = Duration::from_millis(250);
+
+/// The upper half of the UUIDs of the fake players, which marks them as Java players
+const UUID_PREFIX: u64 = 0x5eed;
+
+/// Names the fake players join with
+const NAMES: [&str; 10] = [
+    "Alex",
+    "Steve",
+    "Notch",
+    "Jeb",
+    "Dinnerbone",
+    "Grumm",
+    "Kai",
+    "Sunny",
+    "Efe",
+    "Noor",
+];
+
+/// Messages the fake server logs while nothing else happens
+const CHATTER: [(Level, &str); 8] = [
+    (Level::Info, "Saving chunks for level 'overworld'"),
+    (Level::Info, "Saved 412 chunks in 38 ms"),
+    (Level::Info, "Generated spawn chunks in 1.2 s"),
+    (
+        Level::Warn,
+        "Can't keep up! Is the server overloaded? Running 2041ms behind",
+    ),
+    (Level::Info, "[Chat: Alex] anyone got spare iron?"),
+    (Level::Info, "[Chat: Steve] check the chest by the portal"),
+    (
+        Level::Error,
+        "Failed to load chunk [12, -7]: unexpected end of file",
+    ),
+    (Level::Info, "Reloaded 3 datapacks"),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+/// A xorshift generator, the demo only needs noise
+struct Noise(u64);
+
+impl Noise {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number from 0 to `below`, excluding `below`
+    fn below(&mut self, below: usize) -> usize {
+        (self.next() % below as u64) as usize
+    }
+
+    /// Whether an event with the chance of one in `odds` happens
+    fn one_in(&mut self, odds: u64) -> bool {
+        self.next().is_multiple_of(odds)
+    }
+}
+
+/// What the made up server is doing, changed by the task stepping it and read by the panels
+struct State {
+    noise: Noise,
+    /// Indices into `NAMES` of the players online
+    players: Vec<usize>,
+    /// The current TPS in hundredths
+    tps: u64,
+}
+
+/// A server that only exists in memory, logging made up lines with players coming and going
+struct DemoServer {
+    started: Instant,
+    state: SyncMutex<State>,
+}
+
+impl ServerStatus for DemoServer {
+    fn players(&self) -> Vec<PlayerInfo> {
+        self.state
+            .lock()
+            .players
+            .iter()
+            .map(|index| PlayerInfo {
+                name: NAMES[*index].to_owned(),
+                uuid: Uuid::from_u64_pair(UUID_PREFIX, *index as u64),
+                client: Client::Java,
+                brand: Some("vanilla".to_owned()),
+            })
+            .collect()
+    }
+
+    fn worlds(&self) -> Vec<WorldInfo> {
+        // every player keeps the chunks around them loaded
+        let players = self.state.lock().players.len();
+        vec![
+            WorldInfo {
+                name: "overworld".to_owned(),
+                loaded_chunks: 441 + players * 289,
+            },
+            WorldInfo {
+                name: "the_nether".to_owned(),
+                loaded_chunks: 0,
+            },
+            WorldInfo {
+                name: "the_end".to_owned(),
+                loaded_chunks: 0,
+            },
+        ]
+    }
+
+    fn ticks(&self) -> TickInfo {
+        let tps = self.state.lock().tps as f32 / 100.0;
+        TickInfo {
+            tps,
+            mspt: 1000.0 / tps.max(1.0),
+        }
+    }
+}
+
+impl DemoServer {
+    /// Logs `message` like the server formats its log
+    fn log(&self, level: Level, message: impl Into<String>) {
+        let level = match level {
+            Level::Info => " INFO".green(),
+            Level::Warn => " WARN".yellow(),
+            Level::Error => "ERROR".red(),
+        };
+        let uptime = format!("{:>10.3}s ", self.started.elapsed().as_secs_f64());
+        LOGGER.lock().push(
+            Line::from(vec![
+                uptime.dark_gray(),
+                level,
+                " steel: ".dark_gray(),
+                message.into().into(),
+            ])
+            .into(),
+        );
+        REDRAW.notify_one();
+    }
+
+    /// Advances the fake server by one step
+    fn step(&self) {
+        let mut state = self.state.lock();
+        // mostly a steady 20 TPS, now and then a lag spike the watchdog would alert about
+        state.tps = if state.noise.one_in(80) {
+            800 + state.noise.below(800) as u64
+        } else {
+            (state.tps + 40).min(2000) - state.noise.below(30) as u64
+        };
+
+        let message = if state.noise.one_in(12) {
+            let index = state.noise.below(NAMES.len());
+            let name = NAMES[index];
+            if let Some(online) = state.players.iter().position(|player| *player == index) {
+                state.players.remove(online);
+                Some((Level::Info, format!("{name} left the game")))
+            } else {
+                state.players.push(index);
+                Some((Level::Info, format!("{name} joined the game")))
+            }
+        } else if state.noise.one_in(6) {
+            let (level, message) = CHATTER[state.noise.below(CHATTER.len())];
+            Some((level, message.to_owned()))
+        } else {
+            None
+        };
+        drop(state);
+        if let Some((level, message)) = message {
+            self.log(level, message);
+        }
+    }
+
+    /// Answers a command typed into the console, the demo only knows `list`
+    fn answer(&self, command: &str) {
+        LOGGER
+            .lock()
+            .push(Text::from(format!("console> {command}")));
+        match command.trim() {
+            "list" => {
+                let mut players: Vec<&str> = self
+                    .state
+                    .lock()
+                    .players
+                    .iter()
+                    .map(|index| NAMES[*index])
+                    .collect();
+                players.sort_unstable();
+                self.log(
+                    Level::Info,
+                    format!(
+                        "There are {} players online: {}",
+                        players.len(),
+                        players.join(", ")
+                    ),
+                );
+            }
+            _ => self.log(
+                Level::Info,
+                format!("Unknown command `{command}`, the demo only knows `list`"),
+            ),
+        }
+    }
+}
+
+/// Steps `server` and answers the `commands` typed into its console until it is stopped through `server_token`
+async fn run_server(
+    server: Arc<DemoServer>,
+    mut commands: mpsc::UnboundedReceiver<String>,
+    server_token: CancellationToken,
+) {
+    let mut interval = time::interval(STEP);
+    loop {
+        select! {
+            () = server_token.cancelled() => break,
+            _ = interval.tick() => server.step(),
+            Some(command) = commands.recv() => server.answer(&command),
+        }
+    }
+    server.log(Level::Info, "Stopped the demo server");
+}
+
+/// Runs the terminal ui against a made up server, which logs lines, lets players come and go and lags now and then
+///
+/// The app is the same one the real server runs in, only the tabs working on the world itself are hidden. Nothing of
+/// the real server is started, so changes to the ui can be tried out without loading a world.
+pub async fn demo(ui: UiConfig) -> anyhow::Result<()> {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64;
+    let server = Arc::new(DemoServer {
+        started: Instant::now(),
+        state: SyncMutex::new(State {
+            // xorshift never leaves zero
+            noise: Noise(seed | 1),
+            players: Vec::new(),
+            tps: 2000,
+        }),
+    });
+    server.log(
+        Level::Info,
+        "Starting the demo server, nothing here is real",
+    );
+
+    let token = CancellationToken::new();
+    let server_token = token.child_token();
+    let (commands, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(run_server(server.clone(), receiver, server_token.clone()));
+
+    let mut app = SteelAppBuilder::without_server(server)
+        .name("demo")
+        .commands(commands)
+        .ui(ui)
+        .token(token)
+        .server_token(server_token)
+        .build()?;
+    app.run().await
+}
@@ -0,0 +1,137 @@
+use crate::config::DesktopConfig;
+use crate::logger::ServerLog;
+use crate::notifications::{self, Notification, NotificationKind, Severity};
+use std::collections::HashMap;
+use std::env;
+use std::io;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use steel_utils::locks::SyncMutex;
+use tokio::task;
+use tracing::{debug, warn};
+
+/// Name the notifications are shown under
+const APP_NAME: &str = "steel-tui";
+
+/// The settings of every running server that shows desktop notifications, by its log
+static SERVERS: LazyLock<SyncMutex<HashMap<ServerLog, DesktopConfig>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+/// Whether the task showing the notifications of every server is running
+static SPAWNED: AtomicBool = AtomicBool::new(false);
+
+impl DesktopConfig {
+    fn wants(&self, notification: &Notification) -> bool {
+        (self.errors && notification.severity == Severity::Error)
+            || notifications::wanted(&self.events, notification.kind)
+    }
+}
+
+/// Whether the server runs on a desktop someone sits in front of, rather than on a remote or headless machine
+fn is_local() -> bool {
+    if env::var_os("SSH_CONNECTION").is_some() {
+        return false;
+    }
+    if cfg!(target_os = "linux") {
+        env::var_os("DISPLAY").is_some() || env::var_os("WAYLAND_DISPLAY").is_some()
+    } else {
+        cfg!(target_os = "macos")
+    }
+}
+
+/// Shows `notification` through `notify-send`, which talks to the notification daemon
+#[cfg(target_os = "linux")]
+fn show(notification: &Notification) -> io::Result<()> {
+    let urgency = match notification.severity {
+        Severity::Info => "low",
+        Severity::Warning => "normal",
+        Severity::Error => "critical",
+    };
+    run(Command::new("notify-send")
+        .arg(format!("--app-name={APP_NAME}"))
+        .arg(format!("--urgency={urgency}"))
+        // a message starting with a dash is not an option
+        .arg("--")
+        .arg(APP_NAME)
+        .arg(notification.text()))
+}
+
+/// Shows `notification` through the notification center
+#[cfg(target_os = "macos")]
+fn show(notification: &Notification) -> io::Result<()> {
+    // the message ends up in an AppleScript string literal
+    let message = notification
+        .text()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    run(Command::new("osascript").arg("-e").arg(format!(
+        "display notification \"{message}\" with title \"{APP_NAME}\""
+    )))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn show(_notification: &Notification) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "desktop notifications are not supported on this platform",
+    ))
+}
+
+/// Runs `command` without letting it write into the terminal ui
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn run(command: &mut Command) -> io::Result<()> {
+    let status = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("exited with {status}")))
+    }
+}
+
+/// Shows the notifications about the server logging into `log` that `config` asks for, until it stops
+pub fn configure(log: ServerLog, config: DesktopConfig) {
+    if config.enabled {
+        SERVERS.lock().insert(log, config);
+    }
+}
+
+/// Shows the notifications every server asks for on the desktop, if the servers run locally
+///
+/// Gives up after the first notification that can't be shown, so a missing daemon only warns once.
+pub fn spawn() {
+    if SPAWNED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    if !is_local() {
+        debug!("Not showing desktop notifications, the server doesn't run on a local desktop");
+        return;
+    }
+
+    let mut bus = notifications::subscribe();
+    tokio::spawn(async move {
+        while let Some(notification) = notifications::next(&mut bus, "Desktop notifications").await
+        {
+            let wanted = SERVERS
+                .lock()
+                .get(&notification.server)
+                .is_some_and(|config| config.wants(&notification));
+            // the next start of the server configures it again
+            if notification.kind == NotificationKind::ServerStopped {
+                SERVERS.lock().remove(&notification.server);
+            }
+            if wanted {
+                let shown = task::spawn_blocking(move || show(&notification)).await;
+                if let Ok(Err(err)) = shown {
+                    warn!("Failed to show a desktop notification, not showing any more: {err}");
+                    return;
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,87 @@
+use anyhow::{Context, bail};
+use steel_core::server::Server;
+
+const USAGE: &str = "usage: distance [view | simulation] [chunks]";
+
+/// Smallest distance in chunks the server can be set to
+pub const MIN_DISTANCE: u8 = 2;
+
+/// Largest distance in chunks the server can be set to
+pub const MAX_DISTANCE: u8 = 32;
+
+/// A distance around players the server works within, adjustable while it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Distance {
+    /// How far chunks are sent to clients
+    #[default]
+    View,
+    /// How far chunks around players are ticked
+    Simulation,
+}
+
+impl Distance {
+    pub const ALL: [Self; 2] = [Self::View, Self::Simulation];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::View => "view",
+            Self::Simulation => "simulation",
+        }
+    }
+
+    /// The distance `server` currently uses
+    pub fn get(self, server: &Server) -> u8 {
+        match self {
+            Self::View => server.view_distance(),
+            Self::Simulation => server.simulation_distance(),
+        }
+    }
+
+    /// Changes the distance of `server`, which sends it to connected clients and resizes the loaded area around them
+    ///
+    /// The config file is left alone, a changed distance only lasts until the server restarts.
+    pub fn set(self, server: &Server, chunks: u8) {
+        let chunks = chunks.clamp(MIN_DISTANCE, MAX_DISTANCE);
+        match self {
+            Self::View => server.set_view_distance(chunks),
+            Self::Simulation => server.set_simulation_distance(chunks),
+        }
+    }
+
+    /// Changes the distance of `server` by `step` chunks
+    pub fn adjust(self, server: &Server, step: i8) {
+        self.set(server, self.get(server).saturating_add_signed(step));
+    }
+}
+
+/// Runs the `distance` console command, returning its output
+///
+/// `distance` shows both distances, `distance <view | simulation> <chunks>` changes one.
+pub fn command(server: &Server, args: &str) -> anyhow::Result<Vec<String>> {
+    let mut words = args.split_whitespace();
+    let Some(name) = words.next() else {
+        return Ok(Distance::ALL
+            .iter()
+            .map(|distance| format!("{} distance: {}", distance.name(), distance.get(server)))
+            .collect());
+    };
+    let distance = Distance::ALL
+        .into_iter()
+        .find(|distance| distance.name() == name)
+        .context(USAGE)?;
+    let Some(chunks) = words.next() else {
+        return Ok(vec![format!("{name} distance: {}", distance.get(server))]);
+    };
+    let chunks: u8 = chunks
+        .parse()
+        .ok()
+        .filter(|chunks| (MIN_DISTANCE..=MAX_DISTANCE).contains(chunks))
+        .with_context(|| {
+            format!("the {name} distance has to be between {MIN_DISTANCE} and {MAX_DISTANCE}")
+        })?;
+    if words.next().is_some() {
+        bail!(USAGE);
+    }
+    distance.set(server, chunks);
+    Ok(vec![format!("Set the {name} distance to {chunks} chunks")])
+}
@@ -0,0 +1,212 @@
+use crate::logger::ServerLog;
+use crate::{io_pool, status};
+use anyhow::Context;
+use std::backtrace::Backtrace;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use steel_core::server::Server;
+use tokio::runtime::Handle;
+use tokio::time;
+use tracing::{error, info};
+
+/// Directory diagnostic dumps are written into
+const DUMP_DIR: &str = "dumps";
+
+/// How long the ticks are watched to tell whether they still complete
+const TICK_SAMPLE: Duration = Duration::from_millis(200);
+
+/// Part of the name of the thread steel runs the tick loop on
+const TICK_THREAD: &str = "tick";
+
+/// How long the tasks get to reach a point where their state can be captured
+#[cfg(all(tokio_unstable, tokio_taskdump))]
+const TASK_DUMP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Runs the `dump` console command, returning its output
+///
+/// The report is written in the background, since capturing the tasks waits for them to yield, its path is logged
+/// once it is written.
+pub fn command(server: &Arc<Server>) -> Vec<String> {
+    let server = server.clone();
+    let log = ServerLog::current();
+    tokio::spawn(async move {
+        let written = write(&server).await;
+        log.scope(|| match written {
+            Ok(path) => info!("Wrote a diagnostic report to {}", path.display()),
+            Err(err) => error!("Failed to write a diagnostic report: {err:#}"),
+        });
+    });
+    vec!["Writing a diagnostic report...".to_owned()]
+}
+
+/// Writes a diagnostic report of `server` into the dumps directory, returning its path
+///
+/// The report holds the tick times and whether ticks still complete, the tokio runtime metrics and the state of its
+/// tasks where the build can capture it, the pending chunk tasks of every world and the threads of the process with
+/// the kernel stack of the tick thread where the platform lists them.
+pub async fn write(server: &Server) -> anyhow::Result<PathBuf> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let report = report(server).await;
+    fs::create_dir_all(DUMP_DIR).context("failed to create the dumps directory")?;
+    let path = PathBuf::from(DUMP_DIR).join(format!("dump-{secs}.txt"));
+    fs::write(&path, report).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// The state of the server and its runtime as text
+async fn report(server: &Server) -> String {
+    let mut report = String::new();
+    let ticks = status::ticks(server);
+    let _ = writeln!(report, "TPS: {:.1}", ticks.tps);
+    let _ = writeln!(report, "MSPT: {:.2}", ticks.mspt);
+    // a tick thread that hangs completes no tick while it is watched, however fast the last ticks were
+    let before = status::tick_count(server);
+    time::sleep(TICK_SAMPLE).await;
+    let after = status::tick_count(server);
+    let _ = writeln!(
+        report,
+        "Ticks: {after} completed, {} within the last {TICK_SAMPLE:?}{}",
+        after - before,
+        if after == before {
+            ", the tick thread is stuck"
+        } else {
+            ""
+        }
+    );
+    if let Some(bytes) = status::memory().resident_bytes {
+        let _ = writeln!(report, "Memory: {} MiB", bytes / 1024 / 1024);
+    }
+    let _ = writeln!(report, "Players: {}", status::players(server).len());
+    for world in status::worlds(server) {
+        let _ = writeln!(
+            report,
+            "World {}: {} chunks",
+            world.name, world.loaded_chunks
+        );
+    }
+
+    if let Ok(runtime) = Handle::try_current() {
+        let metrics = runtime.metrics();
+        let _ = writeln!(report, "\nRuntime");
+        let _ = writeln!(report, "Workers: {}", metrics.num_workers());
+        let _ = writeln!(report, "Alive tasks: {}", metrics.num_alive_tasks());
+        let _ = writeln!(report, "Global queue: {}", metrics.global_queue_depth());
+        for worker in 0..metrics.num_workers() {
+            let _ = write!(
+                report,
+                "Worker {worker}: busy for {:.1?}, parked {} times",
+                metrics.worker_total_busy_duration(worker),
+                metrics.worker_park_count(worker)
+            );
+            // a worker stuck in a task polls nothing else, its local queue keeps growing
+            #[cfg(tokio_unstable)]
+            let _ = write!(
+                report,
+                ", {} polls, {} queued",
+                metrics.worker_poll_count(worker),
+                metrics.worker_local_queue_depth(worker)
+            );
+            let _ = writeln!(report);
+        }
+        tasks(&runtime, &mut report).await;
+    }
+    if let Some(pool) = io_pool::stats() {
+        let _ = writeln!(
+            report,
+            "World IO: {} busy, {} idle, {} max, {} queued",
+            pool.busy(),
+            pool.idle,
+            pool.max,
+            pool.queued
+        );
+    }
+    let _ = writeln!(report, "\nChunk tasks");
+    for (name, world) in &server.worlds {
+        let _ = writeln!(report, "{name}: {}", world.chunk_map.task_tracker.len());
+    }
+
+    threads(&mut report);
+
+    // the backtraces of other threads can't be captured, only the one writing the dump
+    let _ = writeln!(report, "\nBacktrace\n{}", Backtrace::force_capture());
+    report
+}
+
+/// Lists every task of `runtime` with the trace of where it waits
+///
+/// Tokio only captures tasks when built with `--cfg tokio_unstable --cfg tokio_taskdump` on Linux.
+#[cfg(all(tokio_unstable, tokio_taskdump))]
+async fn tasks(runtime: &Handle, report: &mut String) {
+    let _ = writeln!(report, "\nTasks");
+    match time::timeout(TASK_DUMP_TIMEOUT, runtime.dump()).await {
+        Ok(dump) => {
+            for (index, task) in dump.tasks().iter().enumerate() {
+                let _ = writeln!(report, "Task {index}\n{}", task.trace());
+            }
+        }
+        // a task that never yields keeps the dump from completing, which is worth knowing too
+        Err(_) => {
+            let _ = writeln!(
+                report,
+                "Not every task yielded within {TASK_DUMP_TIMEOUT:?}, one may be blocking its worker"
+            );
+        }
+    }
+}
+
+/// Notes that the tasks of this build can't be captured
+#[cfg(not(all(tokio_unstable, tokio_taskdump)))]
+#[allow(clippy::unused_async)]
+async fn tasks(_runtime: &Handle, report: &mut String) {
+    let _ = writeln!(
+        report,
+        "\nTasks are only captured when built with `--cfg tokio_unstable --cfg tokio_taskdump`"
+    );
+}
+
+/// Lists every thread of the process with what it is waiting on in the kernel
+#[cfg(target_os = "linux")]
+fn threads(report: &mut String) {
+    let Ok(tasks) = fs::read_dir("/proc/self/task") else {
+        return;
+    };
+    let _ = writeln!(report, "\nThreads");
+    for task in tasks.flatten() {
+        let path = task.path();
+        let read = |file: &str| {
+            fs::read_to_string(path.join(file))
+                .map(|content| content.trim().to_owned())
+                .unwrap_or_default()
+        };
+        // the state follows the name in parentheses, which may contain spaces itself
+        let stat = read("stat");
+        let state = stat
+            .rsplit_once(')')
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+            .unwrap_or("?")
+            .to_owned();
+        let name = read("comm");
+        let _ = writeln!(
+            report,
+            "{:>8} {name:<20} {state} {}",
+            task.file_name().to_string_lossy(),
+            read("wchan")
+        );
+        // where the tick thread is stuck in the kernel, only readable with the privileges to trace it
+        if name.contains(TICK_THREAD) {
+            let stack = read("stack");
+            if !stack.is_empty() {
+                let _ = writeln!(report, "{stack}");
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+const fn threads(_report: &mut String) {}
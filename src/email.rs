@@ -0,0 +1,214 @@
+use crate::config::{EmailConfig, SmtpSecurity};
+use crate::logger::ServerLog;
+use crate::notifications::{self, Notification, NotificationKind, Severity};
+use anyhow::{Context, bail};
+use chrono::Local;
+use lettre::message::header::ContentType;
+use lettre::message::{Mailbox, Message};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use steel_utils::locks::SyncMutex;
+use tracing::warn;
+
+/// How long plugin crashes count towards `plugin_failures`
+const PLUGIN_FAILURE_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Longest subject an email gets, the full message is in the body
+const SUBJECT_LEN: usize = 80;
+
+/// Where the errors of every running server that emails them go, by its log
+static SERVERS: LazyLock<SyncMutex<HashMap<ServerLog, Alerts>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+/// Whether the task emailing the errors of every server is running
+static SPAWNED: AtomicBool = AtomicBool::new(false);
+
+/// The SMTP connection with the addresses emails are sent from and to
+struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl Mailer {
+    fn new(config: &EmailConfig) -> anyhow::Result<Self> {
+        if config.host.is_empty() {
+            bail!("no SMTP host is set");
+        }
+        if config.to.is_empty() {
+            bail!("no addresses to send to are set");
+        }
+        let from = config
+            .from
+            .parse::<Mailbox>()
+            .with_context(|| format!("invalid from address {:?}", config.from))?;
+        let to = config
+            .to
+            .iter()
+            .map(|to| {
+                to.parse::<Mailbox>()
+                    .with_context(|| format!("invalid address {to:?}"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut builder = match config.security {
+            SmtpSecurity::Starttls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+            }
+        };
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if !config.username.is_empty() {
+            builder = builder.credentials(Credentials::new(
+                config.username.clone(),
+                config.password.clone(),
+            ));
+        }
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            to,
+        })
+    }
+
+    async fn send(&self, notification: &Notification) -> anyhow::Result<()> {
+        let text = notification.text();
+        let first_line = text.lines().next().unwrap_or_default();
+        let subject: String = first_line.chars().take(SUBJECT_LEN).collect();
+        let mut message = Message::builder()
+            .from(self.from.clone())
+            .subject(format!("[steel-tui] {subject}"))
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            message = message.to(to.clone());
+        }
+        let message = message.body(format!(
+            "{text}\n\nSent by steel-tui at {}",
+            Local::now().format("%Y-%m-%d %H:%M:%S")
+        ))?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+/// Counts plugin crashes, so a single one doesn't send an email but a plugin crashing again and again does
+struct PluginFailures {
+    threshold: usize,
+    crashes: VecDeque<Instant>,
+}
+
+impl PluginFailures {
+    /// Counts a crash, returning how many happened within the window if that reached the threshold
+    fn crashed(&mut self) -> Option<usize> {
+        let now = Instant::now();
+        self.crashes.push_back(now);
+        while self
+            .crashes
+            .front()
+            .is_some_and(|crash| now.duration_since(*crash) > PLUGIN_FAILURE_WINDOW)
+        {
+            self.crashes.pop_front();
+        }
+        let count = self.crashes.len();
+        (count >= self.threshold).then(|| {
+            // the next email needs as many crashes again
+            self.crashes.clear();
+            count
+        })
+    }
+}
+
+/// The email alerts of one server
+struct Alerts {
+    mailer: Arc<Mailer>,
+    events: Vec<NotificationKind>,
+    failures: PluginFailures,
+}
+
+impl Alerts {
+    /// Whether `notification` is emailed, counting it if it is about a plugin crash
+    fn wants(&mut self, notification: &mut Notification) -> bool {
+        if notification.severity != Severity::Error
+            || !notifications::wanted(&self.events, notification.kind)
+        {
+            return false;
+        }
+        if notification.kind == NotificationKind::PluginCrashed {
+            let Some(count) = self.failures.crashed() else {
+                return false;
+            };
+            if count > 1 {
+                notification.message = format!(
+                    "{}, {count} plugin crashes within the last hour",
+                    notification.message
+                );
+            }
+        }
+        true
+    }
+}
+
+/// Emails the errors of the server logging into `log` that `config` asks for through its SMTP server, until it stops
+pub fn configure(log: ServerLog, config: EmailConfig) {
+    if !config.enabled {
+        return;
+    }
+    let mailer = match Mailer::new(&config) {
+        Ok(mailer) => mailer,
+        Err(err) => {
+            warn!("Email alerts are enabled but can't be sent: {err:#}");
+            return;
+        }
+    };
+    SERVERS.lock().insert(
+        log,
+        Alerts {
+            mailer: Arc::new(mailer),
+            events: config.events,
+            failures: PluginFailures {
+                threshold: config.plugin_failures.max(1) as usize,
+                crashes: VecDeque::new(),
+            },
+        },
+    );
+}
+
+/// Emails the errors every server asks for, for every server at once
+pub fn spawn() {
+    if SPAWNED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    let mut bus = notifications::subscribe();
+    tokio::spawn(async move {
+        while let Some(mut notification) = notifications::next(&mut bus, "Email alerts").await {
+            let mailer = {
+                let mut servers = SERVERS.lock();
+                let mailer = servers
+                    .get_mut(&notification.server)
+                    .filter(|alerts| alerts.wants(&mut notification))
+                    .map(|alerts| alerts.mailer.clone());
+                // the next start of the server configures it again
+                if notification.kind == NotificationKind::ServerStopped {
+                    servers.remove(&notification.server);
+                }
+                mailer
+            };
+
+            if let Some(mailer) = mailer
+                && let Err(err) = mailer.send(&notification).await
+            {
+                warn!("Failed to send an email alert: {err:#}");
+            }
+        }
+    });
+}
@@ -0,0 +1,91 @@
+use crate::confirmation::Session;
+use crate::nbt;
+use crate::widgets::TreeNode;
+use crate::worlds;
+use anyhow::{Context, bail};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use steel_core::server::Server;
+use steel_core::world::World;
+use steel_utils::BlockPos;
+use steel_utils::locks::SyncMutex;
+
+const USAGE: &str = "usage: inspect <x> <y> <z> [world]";
+
+/// The last inspected block, waiting to be opened by the terminal ui session that ran the command, by that session
+static OPENED: LazyLock<SyncMutex<HashMap<Session, (String, Vec<TreeNode>)>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+/// Runs the `inspect` console command, returning the title and nodes of the block at the given coordinates
+///
+/// Without a world name, the block is looked up in the overworld.
+pub fn command(server: &Server, args: &str) -> anyhow::Result<(String, Vec<TreeNode>)> {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let (coordinates, world_name) = match words.as_slice() {
+        [x, y, z] => ([*x, *y, *z], "overworld"),
+        [x, y, z, world] => ([*x, *y, *z], *world),
+        _ => bail!(USAGE),
+    };
+    let [x, y, z] = coordinates.map(|coordinate| coordinate.parse::<i32>());
+    let (Ok(x), Ok(y), Ok(z)) = (x, y, z) else {
+        bail!(USAGE);
+    };
+    let (name, world) = worlds::find(server, world_name)
+        .with_context(|| format!("there is no world called {world_name}"))?;
+
+    let pos = BlockPos::new(x, y, z);
+    let title = format!("{x} {y} {z} in {name}");
+    Ok((title, inspect(world, pos)?))
+}
+
+/// Opens `nodes` in the terminal ui of `session` the next time it draws
+pub fn open(session: Session, title: String, nodes: Vec<TreeNode>) {
+    OPENED.lock().insert(session, (title, nodes));
+}
+
+/// Takes the inspected block waiting to be opened by `session`
+pub fn take(session: Session) -> Option<(String, Vec<TreeNode>)> {
+    OPENED.lock().remove(&session)
+}
+
+/// Describes the block at `pos` and the chunk it is in
+fn inspect(world: &World, pos: BlockPos) -> anyhow::Result<Vec<TreeNode>> {
+    let state = world
+        .block_state(pos)
+        .context("the chunk of that block is not loaded")?;
+
+    let properties = state
+        .properties()
+        .into_iter()
+        .map(|(name, value)| TreeNode::leaf(format!("{name} = {value}")))
+        .collect();
+    let mut nodes = vec![
+        TreeNode::branch(format!("Block {}", state.block_name()), properties).expanded(),
+        TreeNode::leaf(format!("Biome {}", world.biome_at(pos))),
+        TreeNode::branch(
+            "Light",
+            vec![
+                TreeNode::leaf(format!("Block {}", world.block_light(pos))),
+                TreeNode::leaf(format!("Sky {}", world.sky_light(pos))),
+            ],
+        )
+        .expanded(),
+    ];
+    if let Some(nbt) = world.block_entity_nbt(pos) {
+        nodes.push(nbt::compound("Block entity", &nbt));
+    }
+
+    let chunk = pos.chunk_pos();
+    nodes.push(TreeNode::branch(
+        format!("Chunk {} {}", chunk.x, chunk.z),
+        vec![
+            TreeNode::leaf(format!("Section {}", pos.y >> 4)),
+            TreeNode::leaf(format!("Status {}", world.chunk_map.chunk_status(chunk))),
+            TreeNode::leaf(format!(
+                "Block entities {}",
+                world.chunk_map.block_entity_count(chunk)
+            )),
+        ],
+    ));
+    Ok(nodes)
+}
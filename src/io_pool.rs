@@ -0,0 +1,55 @@
+use std::sync::OnceLock;
+use tokio::runtime::Handle;
+
+/// The runtime chunks are saved and loaded on, with the most threads its blocking pool may grow to
+static POOL: OnceLock<(Handle, usize)> = OnceLock::new();
+
+/// How busy the blocking pool doing the world IO is
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(tokio_unstable), allow(dead_code))]
+pub struct IoPool {
+    /// Threads the pool has started, busy or idle
+    pub threads: usize,
+    pub idle: usize,
+    /// Most threads the pool may grow to
+    pub max: usize,
+    /// Blocking tasks waiting for a thread
+    pub queued: usize,
+}
+
+impl IoPool {
+    pub const fn busy(&self) -> usize {
+        self.threads.saturating_sub(self.idle)
+    }
+
+    /// How much of the pool is busy, from 0 to 1
+    pub fn saturation(&self) -> f64 {
+        self.busy() as f64 / self.max.max(1) as f64
+    }
+}
+
+/// Shows the blocking pool of `runtime`, which may grow to `max_threads`, as the pool doing the world IO
+pub fn track_io_pool(runtime: Handle, max_threads: usize) {
+    let _ = POOL.set((runtime, max_threads));
+}
+
+/// How busy the pool doing the world IO is right now, if it is tracked
+///
+/// Tokio only reports its blocking pool when built with `--cfg tokio_unstable`, without it this is always `None`.
+#[cfg(tokio_unstable)]
+pub fn stats() -> Option<IoPool> {
+    let (runtime, max) = POOL.get()?;
+    let metrics = runtime.metrics();
+    Some(IoPool {
+        threads: metrics.num_blocking_threads(),
+        idle: metrics.num_idle_blocking_threads(),
+        max: *max,
+        queued: metrics.blocking_queue_depth(),
+    })
+}
+
+/// How busy the pool doing the world IO is right now, never known without `--cfg tokio_unstable`
+#[cfg(not(tokio_unstable))]
+pub const fn stats() -> Option<IoPool> {
+    None
+}
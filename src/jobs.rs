@@ -0,0 +1,176 @@
+use crate::REDRAW;
+use crate::logger::ServerLog;
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use steel_utils::locks::SyncMutex;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+/// Every long running command of every server
+static RUNNING: LazyLock<SyncMutex<Vec<Running>>> = LazyLock::new(|| SyncMutex::new(Vec::new()));
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// How often a paused command checks whether it was resumed
+const PAUSE_POLL: Duration = Duration::from_millis(100);
+
+struct Running {
+    id: u64,
+    log: ServerLog,
+    command: String,
+    started: Instant,
+    progress: Option<(u64, u64)>,
+    token: CancellationToken,
+    /// Whether the command waits in `wait_while_paused`, `None` if it can't be paused
+    paused: Option<bool>,
+}
+
+/// A long running command, shown in the running tab until it is dropped
+///
+/// Commands like pregenerating the world register themselves with `Job::start`, report how far they got with
+/// `progress` and check `token` between steps, it is cancelled from the running tab. Commands that can pause call
+/// `wait_while_paused` between steps too.
+pub struct Job {
+    id: u64,
+    token: CancellationToken,
+}
+
+impl Job {
+    /// Registers `command` as running on the server the current thread logs into
+    pub fn start(command: impl Into<String>) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        RUNNING.lock().push(Running {
+            id,
+            log: ServerLog::current(),
+            command: command.into(),
+            started: Instant::now(),
+            progress: None,
+            token: token.clone(),
+            paused: None,
+        });
+        REDRAW.notify_one();
+        Self { id, token }
+    }
+
+    /// Cancelled when the command is cancelled from the running tab
+    pub const fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Whether the command was cancelled from the running tab
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Lets the command be paused and resumed from the running tab
+    #[must_use]
+    pub fn pausable(self) -> Self {
+        if let Some(running) = RUNNING
+            .lock()
+            .iter_mut()
+            .find(|running| running.id == self.id)
+        {
+            running.paused = Some(false);
+        }
+        self
+    }
+
+    /// Whether the command was paused from the running tab
+    pub fn is_paused(&self) -> bool {
+        RUNNING
+            .lock()
+            .iter()
+            .any(|running| running.id == self.id && running.paused == Some(true))
+    }
+
+    /// Waits until the command is resumed or cancelled, if it was paused
+    pub async fn wait_while_paused(&self) {
+        while self.is_paused() && !self.is_cancelled() {
+            time::sleep(PAUSE_POLL).await;
+        }
+    }
+
+    /// Reports that `done` of `total` steps of the command are done
+    pub fn progress(&self, done: u64, total: u64) {
+        if let Some(running) = RUNNING
+            .lock()
+            .iter_mut()
+            .find(|running| running.id == self.id)
+        {
+            running.progress = Some((done, total));
+        }
+        REDRAW.notify_one();
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        RUNNING.lock().retain(|running| running.id != self.id);
+        REDRAW.notify_one();
+    }
+}
+
+/// A long running command as shown in the running tab
+pub struct JobStatus {
+    pub id: u64,
+    pub command: String,
+    pub elapsed: Duration,
+    /// Steps done and the total number of steps, once the command reported them
+    pub progress: Option<(u64, u64)>,
+    pub cancelled: bool,
+    /// Whether the command is paused, `None` if it can't be paused
+    pub paused: Option<bool>,
+}
+
+impl JobStatus {
+    /// Steps done per second on average
+    pub fn rate(&self) -> Option<f64> {
+        let (done, _) = self.progress?;
+        let secs = self.elapsed.as_secs_f64();
+        (secs > 0.0).then(|| done as f64 / secs)
+    }
+
+    /// How long the remaining steps will take at the average rate
+    pub fn eta(&self) -> Option<Duration> {
+        let (done, total) = self.progress?;
+        let rate = self.rate().filter(|rate| *rate > 0.0)?;
+        Some(Duration::from_secs_f64(
+            total.saturating_sub(done) as f64 / rate,
+        ))
+    }
+}
+
+/// The long running commands of the server logging into `log`, oldest first
+pub fn running(log: ServerLog) -> Vec<JobStatus> {
+    RUNNING
+        .lock()
+        .iter()
+        .filter(|running| running.log == log)
+        .map(|running| JobStatus {
+            id: running.id,
+            command: running.command.clone(),
+            elapsed: running.started.elapsed(),
+            progress: running.progress,
+            cancelled: running.token.is_cancelled(),
+            paused: running.paused,
+        })
+        .collect()
+}
+
+/// Cancels the command with the id `id`, it stops at its next step
+pub fn cancel(id: u64) {
+    if let Some(running) = RUNNING.lock().iter().find(|running| running.id == id) {
+        running.token.cancel();
+    }
+    REDRAW.notify_one();
+}
+
+/// Pauses or resumes the command with the id `id`, if it can be paused
+pub fn toggle_pause(id: u64) {
+    if let Some(running) = RUNNING.lock().iter_mut().find(|running| running.id == id) {
+        running.paused = running.paused.map(|paused| !paused);
+    }
+    REDRAW.notify_one();
+}
@@ -0,0 +1,300 @@
+use anyhow::bail;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Something the user can trigger with a key chord
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Runs the command in the input line
+    Submit,
+    /// Scrolls the console up by a line
+    ScrollUp,
+    /// Scrolls the console down by a line
+    ScrollDown,
+    /// Scrolls the console up by a screenful
+    PageUp,
+    /// Scrolls the console down by a screenful
+    PageDown,
+    /// Jumps to the oldest line kept in the console
+    ScrollTop,
+    /// Jumps to the end of the console and follows new output
+    ScrollBottom,
+    /// Selects the next tab
+    NextPanel,
+    /// Selects the previous tab
+    PreviousPanel,
+    /// Selects the console tab
+    ShowConsole,
+    /// Selects the players tab
+    ShowPlayers,
+    /// Selects the performance tab
+    ShowPerformance,
+    /// Cycles the console between the executed commands, the output of each plugin and the full log
+    FilterPlugin,
+    /// Folds messages spanning several lines in the console to their first line, or unfolds them
+    ToggleFolding,
+    /// Pins the line selected in the console above the log, or unpins it
+    PinLine,
+    /// Shows when, where and in which spans the line selected in the console was logged
+    LineMetadata,
+    /// Shows the log with its lines nested under the tracing spans they were logged in
+    SpanTree,
+    /// Switches to the next managed server
+    NextServer,
+    /// Switches to the previous managed server
+    PreviousServer,
+    /// Saves the worlds and players of the server, streaming the progress into the log
+    Save,
+    /// Toggles maintenance mode, which enables the whitelist and kicks everyone not on it
+    ToggleMaintenance,
+    /// Stops the server, or exits once it has stopped
+    StopServer,
+    /// Suspends the terminal ui to the shell, resumed with `fg`
+    Suspend,
+    /// Removes a default binding, the key is typed into the input line instead
+    Unbound,
+}
+
+/// A key together with the modifiers held while pressing it, written like `ctrl+shift+up`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    const fn key(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    const fn ctrl(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::CONTROL)
+    }
+
+    const fn alt(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::ALT)
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers;
+        let code = match event.code {
+            // the case of a character already says whether shift was held
+            KeyCode::Char(c) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            code => code,
+        };
+        Self::new(code, modifiers)
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts = s.split('+').map(str::trim).peekable();
+        let mut key = "";
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                key = part;
+                break;
+            }
+            modifiers |= match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                "super" => KeyModifiers::SUPER,
+                _ => bail!("unknown modifier `{part}` in `{s}`"),
+            };
+        }
+
+        let lower = key.to_ascii_lowercase();
+        let code = match lower.as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            _ => {
+                let mut chars = lower.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    (Some('f'), Some(_)) => match lower[1..].parse() {
+                        Ok(n) => KeyCode::F(n),
+                        Err(_) => bail!("unknown key `{key}` in `{s}`"),
+                    },
+                    _ => bail!("unknown key `{key}` in `{s}`"),
+                }
+            }
+        };
+
+        if let KeyCode::Char(_) = code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl TryFrom<String> for KeyChord {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl"),
+            (KeyModifiers::ALT, "alt"),
+            (KeyModifiers::SHIFT, "shift"),
+            (KeyModifiers::SUPER, "super"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            code => f.write_str(&format!("{code:?}").to_ascii_lowercase()),
+        }
+    }
+}
+
+impl From<KeyChord> for String {
+    fn from(chord: KeyChord) -> Self {
+        chord.to_string()
+    }
+}
+
+/// Maps key chords to the actions they trigger
+#[derive(Debug, Clone)]
+pub struct Keymap(HashMap<KeyChord, Action>);
+
+impl Keymap {
+    /// Creates the default keymap with `overrides` applied on top of it
+    pub fn new(overrides: &HashMap<KeyChord, Action>) -> Self {
+        let mut bindings = HashMap::from([
+            (KeyChord::key(KeyCode::Enter), Action::Submit),
+            (KeyChord::key(KeyCode::Up), Action::ScrollUp),
+            (KeyChord::key(KeyCode::Down), Action::ScrollDown),
+            (KeyChord::ctrl(KeyCode::Down), Action::ScrollBottom),
+            (KeyChord::key(KeyCode::PageUp), Action::PageUp),
+            (KeyChord::key(KeyCode::PageDown), Action::PageDown),
+            (KeyChord::key(KeyCode::Home), Action::ScrollTop),
+            (KeyChord::key(KeyCode::End), Action::ScrollBottom),
+            (KeyChord::key(KeyCode::Tab), Action::NextPanel),
+            (KeyChord::key(KeyCode::BackTab), Action::PreviousPanel),
+            (KeyChord::key(KeyCode::F(1)), Action::ShowConsole),
+            (KeyChord::key(KeyCode::F(2)), Action::ShowPlayers),
+            (KeyChord::key(KeyCode::F(3)), Action::ShowPerformance),
+            (KeyChord::alt(KeyCode::Char('p')), Action::FilterPlugin),
+            (KeyChord::alt(KeyCode::Char('f')), Action::ToggleFolding),
+            (KeyChord::alt(KeyCode::Char('i')), Action::LineMetadata),
+            (KeyChord::alt(KeyCode::Char('k')), Action::PinLine),
+            (KeyChord::alt(KeyCode::Char('t')), Action::SpanTree),
+            (KeyChord::alt(KeyCode::Right), Action::NextServer),
+            (KeyChord::alt(KeyCode::Left), Action::PreviousServer),
+            (KeyChord::ctrl(KeyCode::Char('s')), Action::Save),
+            (KeyChord::alt(KeyCode::Char('m')), Action::ToggleMaintenance),
+            (KeyChord::ctrl(KeyCode::Char('c')), Action::StopServer),
+            (KeyChord::ctrl(KeyCode::Char('z')), Action::Suspend),
+        ]);
+        bindings.extend(overrides);
+        bindings.retain(|_, action| *action != Action::Unbound);
+        Self(bindings)
+    }
+
+    /// Looks up the action bound to the key in `event`
+    pub fn action(&self, event: KeyEvent) -> Option<Action> {
+        self.0.get(&KeyChord::from(event)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, KeyChord, Keymap};
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::collections::HashMap;
+
+    fn chord(s: &str) -> KeyChord {
+        s.parse().expect("the chord is valid")
+    }
+
+    #[test]
+    fn parses_modifiers_and_keys() {
+        assert_eq!(
+            chord("ctrl+shift+up"),
+            KeyChord::new(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        );
+        assert_eq!(chord("Alt + F5"), KeyChord::alt(KeyCode::F(5)));
+        assert_eq!(chord("space"), KeyChord::key(KeyCode::Char(' ')));
+    }
+
+    #[test]
+    fn shift_is_part_of_the_character() {
+        assert_eq!(chord("shift+a"), KeyChord::key(KeyCode::Char('a')));
+        let event = KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT);
+        assert_eq!(KeyChord::from(event), KeyChord::key(KeyCode::Char('a')));
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        assert!("hyper+a".parse::<KeyChord>().is_err());
+        assert!("ctrl+nope".parse::<KeyChord>().is_err());
+        assert!("fx".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn display_parses_back() {
+        for s in ["ctrl+c", "alt+shift+pagedown", "f12", "space", "backtab"] {
+            assert_eq!(chord(s).to_string(), s);
+            assert_eq!(chord(&chord(s).to_string()), chord(s));
+        }
+    }
+
+    #[test]
+    fn overrides_replace_and_unbind_defaults() {
+        let overrides = HashMap::from([
+            (chord("ctrl+q"), Action::StopServer),
+            (chord("ctrl+c"), Action::Unbound),
+        ]);
+        let keymap = Keymap::new(&overrides);
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        assert_eq!(keymap.action(ctrl('q')), Some(Action::StopServer));
+        assert_eq!(keymap.action(ctrl('c')), None);
+        assert_eq!(keymap.action(ctrl('s')), Some(Action::Save));
+    }
+}
@@ -1,294 +1,1006 @@
 //! `SteelTui` application made using ratatui
 
-use crate::logger::LOGGER;
+use crate::config::UiConfig;
+use crate::confirmation::Session;
+use crate::keymap::Keymap;
+use crate::logger::ServerLog;
+use crate::notifications::{Notification, Severity};
+use crate::panels::afk::AfkView;
+use crate::panels::datapacks::DatapackList;
+use crate::panels::gamerules::GameruleView;
+use crate::panels::jobs::JobList;
+use crate::panels::performance::PerformanceView;
+use crate::panels::permissions::PermissionsView;
+use crate::panels::player_data::PlayerDataView;
+use crate::panels::players::PlayerList;
+use crate::panels::registry::RegistryView;
+use crate::panels::security::SecurityView;
+use crate::panels::stopped::{StopChoice, StoppedMenu};
+use crate::panels::worlds::WorldList;
+use crate::panels::{CustomPanel, console::Console};
+#[cfg(feature = "plugin")]
+use crate::plugin::permissions::{self, Answer};
+use crate::save::Saves;
+use crate::status::ServerStatus;
+#[cfg(feature = "plugin")]
+use crate::widgets::Splash;
+use crate::widgets::{Popup, Toast, Tree, TreeNode};
 use anyhow::Context;
-use ratatui::DefaultTerminal;
+use futures::future::BoxFuture;
+use ratatui::backend::Backend;
 use ratatui::crossterm::event::{
-    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event,
-    KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind,
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind,
 };
-use ratatui::crossterm::{ExecutableCommand, event};
 use ratatui::layout::Constraint;
 use ratatui::prelude::*;
-use std::sync::Arc;
-use std::thread;
-use std::time::Duration;
+use ratatui::widgets::{Block, Clear, Paragraph};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{fmt, fs};
 use steel::SteelServer;
 use steel_core::server::Server;
-use tokio::select;
-use tokio::sync::{Notify, mpsc};
+use steel_utils::locks::SyncMutex;
+use tempfile::Builder;
+use tokio::sync::{Notify, broadcast, mpsc};
+use tokio::time::{self, Instant};
+use tokio::{pin, select};
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::TaskTracker;
-use tracing::{error, info};
-use tui_input::Input;
+use tracing::{error, info, warn};
 use tui_input::backend::crossterm::EventHandler;
-use tui_scrollview::{ScrollView, ScrollViewState, ScrollbarVisibility};
+use tui_input::{Input, InputRequest};
 
 static REDRAW: Notify = Notify::const_new();
 
+/// Logs of the servers that finished stopping, with the error they stopped with
+static STOPPED: LazyLock<SyncMutex<HashMap<ServerLog, Option<String>>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+const MAX_TOASTS: usize = 3;
+/// Number of input events queued while the app is busy before their senders have to wait
+const EVENT_BUFFER: usize = 64;
+/// Largest paste put into the input line in bytes, as long as the longest command the server accepts
+const MAX_PASTE: usize = 32_767;
+/// Longest time between mouse wheel steps that still scroll faster
+const WHEEL_STREAK: Duration = Duration::from_millis(50);
+/// Most times `scroll_lines` a single wheel step scrolls by
+const MAX_WHEEL_STREAK: u16 = 4;
+
+mod afk;
+mod alerts;
+mod api;
+mod audit;
+mod autoexec;
+mod builder;
+mod clipboard;
+mod command;
+#[cfg(feature = "plugin")]
+mod completion;
+mod config;
+mod confirmation;
+mod datapacks;
+mod demo;
+mod desktop;
+mod distance;
+mod dump;
+mod email;
+mod inspect;
+mod io_pool;
+mod jobs;
+mod keymap;
 pub(crate) mod logger;
+mod maintenance;
+mod metrics;
+mod nbt;
+mod notifications;
+mod ops;
+mod panels;
+mod ping;
+mod pregen;
+mod query;
+mod rcon;
+mod recording;
+mod registry;
+mod reload;
+mod replay;
+mod save;
+mod security;
+mod server_list;
+mod session;
+mod signals;
+mod status;
+mod stdin;
+mod terminal;
+mod text_preview;
+mod triggers;
+mod watchdog;
+mod webhooks;
+mod widgets;
+mod worlds;
 
 #[cfg(feature = "plugin")]
 mod plugin;
+#[cfg(all(feature = "profiling", unix))]
+mod profiler;
+#[cfg(feature = "ssh")]
+mod ssh;
+#[cfg(target_os = "linux")]
+mod systemd;
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "tokio-console")]
+mod tokio_console;
+
+use crate::command::CommandOrigin;
+pub use alerts::{install_panic_hook, panicked};
+pub use builder::SteelAppBuilder;
+pub use config::{
+    AfkConfig, ApiConfig, AutosaveConfig, DesktopConfig, EmailConfig, MaintenanceConfig,
+    NotificationsConfig, PluginLimits, QueryConfig, RconConfig, RuntimeConfig, ServerConfig,
+    SmtpSecurity, SshConfig, Theme, TriggerConfig, TuiConfig, UiConfig, WatchdogConfig,
+    WebhookConfig, WebhookFormat,
+};
+pub use confirmation::confirm;
+pub use demo::demo;
+pub use io_pool::track_io_pool;
+pub use jobs::Job;
+pub use keymap::{Action, KeyChord};
+pub use logger::{LogCounter, LogMetadata, Logger, ServerLog, TuiLoggerWriter};
+pub use notifications::NotificationKind;
+pub use panels::{CustomPanel, Panel};
+#[cfg(feature = "plugin")]
+pub use plugin::commands::{CommandRegistration, PluginCommand, register_command};
+#[cfg(feature = "plugin")]
+pub use plugin::load_plugins;
+#[cfg(feature = "plugin")]
+pub use plugin::panels::{PanelContent, PanelUpdate, update_panel};
+#[cfg(feature = "plugin")]
+pub use plugin::permissions::{Capability, request_capability};
+#[cfg(feature = "plugin")]
+pub use plugin::plugin_log;
+#[cfg(feature = "plugin")]
+pub use plugin::settings::{plugin_config, plugin_data_dir};
+#[cfg(feature = "plugin")]
+pub use plugin::startup::compile_plugins_on;
+#[cfg(feature = "plugin")]
+pub use plugin::storage::{plugin_store_get, plugin_store_remove, plugin_store_set};
+pub use pregen::pregenerate_on;
+pub use recording::start_recording;
+pub use reload::{on_log_reload, reload_config, track_server_config};
+pub use replay::replay;
+pub use security::{connection_attempt, handshake_rejected};
+pub use session::summary as session_summary;
+pub use signals::{Signal, Signals, reload_configs};
+pub use stdin::read_commands;
+#[cfg(target_os = "linux")]
+pub use systemd::stopping as systemd_stopping;
+#[cfg(feature = "testing")]
+pub use testing::TestHarness;
+#[cfg(feature = "tokio-console")]
+pub use tokio_console::{console_layer, is_console_instrumentation};
+pub use triggers::LogTriggers;
 
-pub use logger::{Logger, TuiLoggerWriter};
-use steel_core::command::sender::CommandSender;
+/// Writes the log of the server called `name` as plain text into the logs directory, returning its path
+fn export_log(name: &str, log: ServerLog) -> anyhow::Result<PathBuf> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    fs::create_dir_all("logs").context("failed to create the logs directory")?;
+    let path = PathBuf::from("logs").join(format!("{name}-{secs}.log"));
+    let text: Vec<String> = log
+        .history()
+        .lock()
+        .text
+        .lines
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    fs::write(&path, text.join("\n"))
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
 
+/// An event the app reacts to
 #[derive(Debug)]
-enum AppEvent {
+pub enum AppEvent {
+    /// Input from the terminal
     UiEvent(Event),
 }
 
+/// An action that waits for the user to confirm it
+#[derive(Debug, Clone)]
+enum Confirm {
+    StopServer,
+    /// A console command picked from a menu
+    Command(String),
+}
+
+impl Confirm {
+    fn message(&self) -> String {
+        match self {
+            Self::StopServer => "Stop the server? [y/N]".to_owned(),
+            Self::Command(command) => format!("Run `{command}`? [y/N]"),
+        }
+    }
+}
+
+/// What a server that couldn't bind its port failed with, so its exit code can tell it apart from a crash
+#[derive(Debug, Clone, Copy)]
+pub struct BindFailed;
+
+impl fmt::Display for BindFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to bind the server port")
+    }
+}
+
+/// Starts a stopped server again, returning its new handle and the token that stops it
+type Restart = Arc<
+    dyn Fn() -> BoxFuture<'static, anyhow::Result<(Arc<Server>, CancellationToken)>> + Send + Sync,
+>;
+
+/// A server started again from the menu of a stopped server, by its log
+type Restarted = (ServerLog, Arc<Server>, CancellationToken);
+
+/// A server controlled by the app, with its own log
+struct ManagedServer {
+    name: String,
+    /// The running server, missing for a made up one like the demo
+    server: Option<Arc<Server>>,
+    /// What the panels show about the server
+    status: Arc<dyn ServerStatus>,
+    server_token: CancellationToken,
+    console: Console,
+    /// Whether the server is saving, with what the status bar shows about it
+    saves: Arc<Saves>,
+    players: PlayerList,
+    performance: PerformanceView,
+    player_data: PlayerDataView,
+    worlds: WorldList,
+    afk: AfkView,
+    jobs: JobList,
+    gamerules: GameruleView,
+    datapacks: DatapackList,
+    registry: RegistryView,
+    permissions: PermissionsView,
+    security: SecurityView,
+    stopped_menu: StoppedMenu,
+    /// Queue of the task running the commands typed into the console, started with the first command
+    commands: OnceLock<mpsc::UnboundedSender<String>>,
+    /// Starts the server again once it stopped, if whoever started it can
+    restart: Option<Restart>,
+}
+
 /// The main application struct
 pub struct SteelApp {
-    server: Arc<Server>,
-    server_token: CancellationToken,
+    servers: Vec<ManagedServer>,
+    selected: usize,
     event_rx: mpsc::Receiver<AppEvent>,
+    ui: UiConfig,
+    keymap: Keymap,
+    panel: Panel,
+    custom_panels: Vec<Box<dyn CustomPanel>>,
+    tick_rate: Duration,
+    mouse_capture: bool,
+    suspendable: bool,
+    suspend_requested: bool,
+    /// Who is connected if this is a remote session, which is closed rather than stopping the server
+    remote: Option<Arc<str>>,
+    /// Answers the questions of the commands run from this app
+    session: Session,
+    confirm: Option<Confirm>,
+    /// The block opened by the `inspect` command
+    inspector: Option<Tree>,
+    notifications: broadcast::Receiver<Notification>,
+    reloads: broadcast::Receiver<Arc<TuiConfig>>,
+    restarts: (
+        mpsc::UnboundedSender<Restarted>,
+        mpsc::UnboundedReceiver<Restarted>,
+    ),
+    toasts: VecDeque<(Notification, Instant)>,
     input: Input,
-    scroll_view_state: ScrollViewState,
-    scroll_bottom: bool,
     cursor_position: Position,
     token: CancellationToken,
     redraw: bool,
+    /// When the last frame was drawn
+    last_frame: Option<Instant>,
+    /// Whether a frame was skipped to stay under `max_fps` and still has to be drawn
+    frame_pending: bool,
+    /// When the mouse wheel last scrolled, with how many steps in quick succession that was
+    last_wheel: Option<(Instant, u16)>,
 }
 
 impl SteelApp {
-    /// Creates a new steel app and spawns an event thread
-    ///
-    /// # Panics
-    /// Panics if the inner thread fails to poll or read events from the terminal
+    /// Creates a builder for an app controlling `server`
     #[must_use]
-    pub fn new(
-        server: Arc<Server>,
-        token: CancellationToken,
-        server_token: CancellationToken,
-    ) -> Self {
-        let (tx, rx) = mpsc::channel(1);
-        let event_token = token.child_token();
-        thread::spawn(move || {
-            while !event_token.is_cancelled() {
-                if event::poll(Duration::from_millis(100)).expect("failed to poll event") {
-                    let event = event::read().expect("failed to read event");
-                    tx.blocking_send(AppEvent::UiEvent(event))
-                        .expect("failed to send");
-                }
-            }
-        });
-
-        Self {
-            server,
-            server_token,
-            event_rx: rx,
-            input: Input::new(String::new()),
-            scroll_view_state: ScrollViewState::new(),
-            scroll_bottom: true,
-            cursor_position: Position::default(),
-            token,
-            redraw: true,
-        }
+    pub fn builder(server: Arc<Server>) -> SteelAppBuilder {
+        SteelAppBuilder::new(server)
     }
 
-    fn draw(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+    fn draw<B>(&mut self, terminal: &mut Terminal<B>) -> anyhow::Result<()>
+    where
+        B: Backend,
+        B::Error: Send + Sync + 'static,
+    {
         if !self.redraw {
             self.redraw = true;
             return Ok(());
         }
+        if let Some(next) = self.next_frame()
+            && Instant::now() < next
+        {
+            self.frame_pending = true;
+            return Ok(());
+        }
 
-        terminal.draw(|frame| {
-            frame.render_widget(&mut *self, frame.area());
-            frame.set_cursor_position(self.cursor_position);
-        })?;
+        self.frame_pending = false;
+        self.last_frame = Some(Instant::now());
+        terminal.draw(|frame| self.render_frame(frame))?;
         Ok(())
     }
 
+    /// The earliest time the next frame may be drawn at
+    fn next_frame(&self) -> Option<Instant> {
+        let interval = Duration::from_secs(1) / self.ui.max_fps.max(1);
+        self.last_frame.map(|last| last + interval)
+    }
+
+    fn render_frame(&mut self, frame: &mut Frame) {
+        frame.render_widget(&mut *self, frame.area());
+        frame.set_cursor_position(self.cursor_position);
+    }
+
+    /// Handles every terminal event except resizes, which need the terminal
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Key(event) => self.handle_key(event),
+            Event::Mouse(event) => self.handle_mouse(event),
+            Event::Paste(paste) if paste.len() > MAX_PASTE => self.stash_paste(&paste),
+            // terminals commit text composed with an input method either as key presses or as a paste
+            Event::Paste(paste) => {
+                for c in paste.chars().filter(|c| !c.is_control()) {
+                    self.input.handle(InputRequest::InsertChar(c));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Writes a paste too large for the input line into a file to review instead, telling where with a toast
+    ///
+    /// The file gets a random name only its owner can read, others on the host can't read or plant it.
+    fn stash_paste(&mut self, paste: &str) {
+        let stashed = Builder::new()
+            .prefix("steel-tui-paste-")
+            .suffix(".txt")
+            .tempfile()
+            .and_then(|mut file| {
+                file.write_all(paste.as_bytes())?;
+                file.keep().map_err(|err| err.error)
+            });
+        let message = match stashed {
+            Ok((_, path)) => format!(
+                "Pasted {} KiB, too much for a command, saved it to {}",
+                paste.len() / 1024,
+                path.display()
+            ),
+            Err(err) => format!("Dropped a paste of {} KiB: {err}", paste.len() / 1024),
+        };
+        // only for this ui, webhooks and other subscribers of the bus don't care about pastes
+        self.toast(Notification {
+            kind: NotificationKind::Ui,
+            severity: Severity::Warning,
+            message,
+            server: ServerLog::current(),
+        });
+    }
+
+    fn toast(&mut self, notification: Notification) {
+        self.toasts
+            .push_back((notification, Instant::now() + TOAST_DURATION));
+        if self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+    }
+
+    /// The server the app currently shows and sends commands to
+    fn current(&self) -> &ManagedServer {
+        &self.servers[self.selected]
+    }
+
+    fn console(&mut self) -> &mut Console {
+        &mut self.servers[self.selected].console
+    }
+
     fn submit_message(&mut self) {
         let command = self.input.value_and_reset();
-        if command.is_empty() || self.server_token.is_cancelled() {
+        // a remote session stops the server only once the user confirmed it
+        if self.remote.is_some() && command.trim() == "stop" {
+            if !self.current().server_token.is_cancelled() {
+                self.confirm = Some(Confirm::StopServer);
+            }
             return;
         }
-        LOGGER.lock().push(Text::raw(format!("> {command}")));
-        self.server.command_dispatcher.read().handle_command(
-            CommandSender::Console,
-            command,
-            &self.server,
-        );
+        self.run_command(command);
+    }
+
+    /// Runs `command` on the current server as the console
+    fn run_command(&self, command: String) {
+        let current = self.current();
+        if command.is_empty() || current.server_token.is_cancelled() {
+            return;
+        }
+        // a slow command must not stall drawing and input, it runs in the background
+        let commands = match (current.commands.get(), &current.server) {
+            (Some(commands), _) => commands,
+            (None, Some(server)) => current.commands.get_or_init(|| {
+                command::runner(
+                    server.clone(),
+                    current.console.log(),
+                    self.session,
+                    self.remote.clone(),
+                )
+            }),
+            (None, None) => return,
+        };
+        let _ = commands.send(command);
     }
 
     fn handle_key(&mut self, event: KeyEvent) {
-        if !event.is_press() {
+        // only terminals speaking the kitty keyboard protocol report releases, held keys still repeat
+        if event.is_release() {
             return;
         }
 
-        if event.code == KeyCode::Char('c') && event.modifiers.contains(KeyModifiers::CONTROL) {
-            if self.server_token.is_cancelled() {
-                self.token.cancel();
-            } else {
-                self.server_token.cancel();
+        let action = self.keymap.action(event);
+
+        if let Some(confirm) = self.confirm.take() {
+            let yes = matches!(event.code, KeyCode::Char('y' | 'Y'));
+            match confirm {
+                Confirm::StopServer
+                    if yes || (self.remote.is_none() && action == Some(Action::StopServer)) =>
+                {
+                    self.current().server_token.cancel();
+                }
+                Confirm::Command(command) if yes => self.run_command(command),
+                _ => (),
             }
+            return;
         }
 
-        match event.code {
-            KeyCode::Enter => self.submit_message(),
-            KeyCode::Up => {
-                self.scroll_up();
+        if let Some(inspector) = &mut self.inspector {
+            if !inspector.handle_key(event) {
+                self.inspector = None;
             }
-            KeyCode::Down if event.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.scroll_bottom = true;
+            return;
+        }
+
+        #[cfg(feature = "plugin")]
+        if permissions::pending().is_some() {
+            match event.code {
+                KeyCode::Char('o' | 'O') => permissions::answer(Answer::AllowOnce),
+                KeyCode::Char('a' | 'A') => permissions::answer(Answer::AllowAlways),
+                KeyCode::Char('d' | 'D') | KeyCode::Esc => permissions::answer(Answer::Deny),
+                _ => (),
+            }
+            return;
+        }
+
+        if self.session.pending().is_some() {
+            match event.code {
+                KeyCode::Char('y' | 'Y') => self.session.answer(true),
+                KeyCode::Char('n' | 'N') | KeyCode::Esc => self.session.answer(false),
+                _ => (),
             }
-            KeyCode::Down => {
-                self.scroll_view_state.scroll_down();
+            return;
+        }
+
+        if self.is_stopped() {
+            let current = &mut self.servers[self.selected];
+            let failed = STOPPED
+                .lock()
+                .get(&current.console.log())
+                .is_some_and(Option::is_some);
+            let (used, choice) = current.stopped_menu.handle_key(event, failed);
+            if let Some(choice) = choice {
+                self.stopped_choice(choice);
+            }
+            if used {
+                return;
+            }
+        }
+
+        if let Panel::Custom(index) = self.panel
+            && self.custom_panels[index].handle_key(event)
+        {
+            return;
+        }
+
+        // built in tabs only take keys while nothing is typed, so commands can still be submitted
+        let typing = !self.input.value().is_empty();
+        let current = &mut self.servers[self.selected];
+        let server = current.server.as_deref();
+        let used = match (self.panel, server) {
+            (Panel::Players, _) if !typing => {
+                current
+                    .players
+                    .handle_key(current.status.as_ref(), server, event)
             }
-            _ => {
+            (Panel::Performance, Some(server)) if !typing => {
+                current.performance.handle_key(server, event)
+            }
+            // typing goes into the search line of the tab
+            (Panel::PlayerData, _) => current.player_data.handle_key(event),
+            (Panel::Registry, _) => current.registry.handle_key(event),
+            // typing goes into the search line of the tab
+            (Panel::Permissions, Some(server)) => current.permissions.handle_key(server, event),
+            (Panel::Worlds, Some(server)) if !typing => current.worlds.handle_key(server, event),
+            (Panel::Afk, _) if !typing => current.afk.handle_key(current.console.log(), event),
+            (Panel::Jobs, _) if !typing => current.jobs.handle_key(current.console.log(), event),
+            (Panel::Gamerules, Some(server)) if !typing => {
+                current.gamerules.handle_key(server, event)
+            }
+            (Panel::Security, _) if !typing => current.security.handle_key(event),
+            (Panel::Datapacks, _) if !typing => {
+                current.datapacks.handle_key(current.console.log(), event)
+            }
+            _ => false,
+        };
+        if used {
+            if let Some(command) = current
+                .players
+                .take_picked()
+                .or_else(|| current.worlds.take_picked())
+                .or_else(|| current.permissions.take_picked())
+            {
+                self.confirm = Some(Confirm::Command(command));
+            } else if let Some(command) = current
+                .gamerules
+                .take_picked()
+                .or_else(|| current.datapacks.take_picked())
+            {
+                self.run_command(command);
+            }
+            return;
+        }
+
+        match action {
+            Some(Action::Submit) => self.submit_message(),
+            Some(Action::ScrollUp) => self.console().scroll_up(),
+            Some(Action::ScrollDown) => self.console().scroll_down(),
+            // Home and End move the cursor while a command is typed
+            Some(Action::ScrollTop | Action::ScrollBottom)
+                if !self.input.value().is_empty()
+                    && matches!(event.code, KeyCode::Home | KeyCode::End) =>
+            {
+                self.input.handle_event(&Event::Key(event));
+            }
+            Some(Action::PageUp) => self.console().page_up(),
+            Some(Action::PageDown) => self.console().page_down(),
+            Some(Action::ScrollTop) => self.console().scroll_top(),
+            Some(Action::ScrollBottom) => self.console().scroll_bottom(),
+            #[cfg(feature = "plugin")]
+            Some(Action::NextPanel) if !self.input.value().is_empty() => self.complete(),
+            Some(Action::NextPanel) => self.select_relative(1),
+            Some(Action::PreviousPanel) => self.select_relative(-1),
+            Some(Action::ShowConsole) => self.panel = Panel::Console,
+            Some(Action::ShowPlayers) => self.panel = Panel::Players,
+            Some(Action::ShowPerformance) => self.panel = Panel::Performance,
+            Some(Action::FilterPlugin) => self.cycle_plugin_filter(),
+            Some(Action::ToggleFolding) => self.console().toggle_folded(),
+            Some(Action::LineMetadata) => self.show_line_metadata(),
+            Some(Action::PinLine) => self.console().toggle_pin(),
+            Some(Action::SpanTree) => {
+                let nodes = logger::spans::tree(&self.current().console.log().history().lock());
+                self.inspector = Some(Tree::new("Log by span", nodes, self.ui.theme));
+            }
+            Some(Action::NextServer) => self.select_server(1),
+            Some(Action::PreviousServer) => self.select_server(-1),
+            Some(Action::Save) if !self.current().server_token.is_cancelled() => {
+                let current = self.current();
+                if let Some(server) = &current.server {
+                    save::start(server.clone(), current.console.log());
+                }
+            }
+            Some(Action::Save) => (),
+            Some(Action::ToggleMaintenance) => self.run_command("maintenance".to_owned()),
+            Some(Action::StopServer) => self.stop_server(),
+            Some(Action::Suspend) => self.suspend_requested = self.suspendable,
+            Some(Action::Unbound) | None => {
                 self.input.handle_event(&Event::Key(event));
             }
         }
     }
 
-    const fn scroll_up(&mut self) {
-        self.scroll_bottom = false;
-        self.scroll_view_state.scroll_up();
+    /// Whether the current server has finished stopping
+    fn is_stopped(&self) -> bool {
+        STOPPED.lock().contains_key(&self.current().console.log())
+    }
+
+    /// Starts the current server again, once it finished stopping
+    fn restart_server(&self) {
+        let current = self.current();
+        let log = current.console.log();
+        let Some(restart) = current.restart.clone() else {
+            log.scope(|| warn!("This server can't be restarted from here"));
+            return;
+        };
+        // the menu is back with the error if the server fails to start
+        STOPPED.lock().remove(&log);
+        let restarted = self.restarts.0.clone();
+        tokio::spawn(async move {
+            log.scope(|| info!("Restarting the server..."));
+            match restart().await {
+                Ok((server, server_token)) => {
+                    let _ = restarted.send((log, server, server_token));
+                }
+                Err(err) => {
+                    log.scope(|| error!("Failed to restart the server: {err:#}"));
+                    STOPPED.lock().insert(log, Some(format!("{err:?}")));
+                }
+            }
+            REDRAW.notify_one();
+        });
     }
 
-    const fn handle_mouse(&mut self, event: MouseEvent) {
+    /// Switches the server showing `log` over to the `server` it was restarted as
+    fn restarted(&mut self, (log, server, server_token): Restarted) {
+        let Some(managed) = self
+            .servers
+            .iter_mut()
+            .find(|managed| managed.console.log() == log)
+        else {
+            return;
+        };
+        managed.status = server.clone();
+        managed.server = Some(server);
+        managed.server_token = server_token;
+        managed.stopped_menu = StoppedMenu::default();
+        // the task running commands belongs to the old server
+        managed.commands = OnceLock::new();
+    }
+
+    /// Does what was picked in the menu of a stopped server
+    fn stopped_choice(&mut self, choice: StopChoice) {
+        match choice {
+            StopChoice::Restart => self.restart_server(),
+            StopChoice::ExportLog => {
+                let current = self.current();
+                let log = current.console.log();
+                log.scope(|| match export_log(&current.name, log) {
+                    Ok(path) => info!("Exported the log to {}", path.display()),
+                    Err(err) => error!("Failed to export the log: {err:#}"),
+                });
+            }
+            StopChoice::Error => {
+                let error = STOPPED
+                    .lock()
+                    .get(&self.current().console.log())
+                    .cloned()
+                    .flatten()
+                    .unwrap_or_else(|| "The server stopped without an error".to_owned());
+                let lines = error.lines().map(TreeNode::leaf).collect();
+                self.inspector = Some(Tree::new("Error", lines, self.ui.theme));
+            }
+            StopChoice::Exit => self.exit(),
+        }
+    }
+
+    /// Stops every server and exits once they have stopped
+    fn exit(&self) {
+        for server in &self.servers {
+            server.server_token.cancel();
+        }
+        self.token.cancel();
+    }
+
+    /// Completes the command typed into the input line
+    #[cfg(feature = "plugin")]
+    fn complete(&mut self) {
+        if let Some(value) = completion::complete(self.input.value()) {
+            replace_with::replace_with(
+                &mut self.input,
+                || Input::new(String::new()),
+                |input| input.with_value(value),
+            );
+        }
+    }
+
+    /// Every panel in tab order, without the ones needing a running server if the current one is made up
+    fn tabs(&self) -> impl Iterator<Item = Panel> {
+        let running = self.current().server.is_some();
+        let tabs = Panel::ALL
+            .into_iter()
+            .chain((0..self.custom_panels.len()).map(Panel::Custom))
+            .filter(move |panel| running || !panel.needs_server());
+        #[cfg(feature = "plugin")]
+        let tabs = tabs.chain((0..plugin::panels::count()).map(Panel::Plugin));
+        tabs
+    }
+
+    fn select_relative(&mut self, step: isize) {
+        let tabs: Vec<Panel> = self.tabs().collect();
+        let current = tabs.iter().position(|panel| *panel == self.panel);
+        let index = current.unwrap_or_default() as isize + step;
+        self.panel = tabs[index.rem_euclid(tabs.len() as isize) as usize];
+    }
+
+    /// Filters the console to the executed commands, then to each enabled plugin, back to the full log after the
+    /// last one
+    fn cycle_plugin_filter(&mut self) {
+        let filters: Vec<String> = [audit::SOURCE.to_owned()]
+            .into_iter()
+            .chain(status::plugins())
+            .collect();
+        let console = self.console();
+        let next = match console.filter() {
+            None => filters.first(),
+            Some(current) => filters
+                .iter()
+                .skip_while(|filter| *filter != current)
+                .nth(1),
+        };
+        console.set_filter(next.cloned());
+    }
+
+    /// Opens where the line selected in the console was logged from, if that was captured
+    fn show_line_metadata(&mut self) {
+        if let Some(metadata) = self.console().selected_metadata() {
+            self.inspector = Some(Tree::new("Log line", metadata.nodes(), self.ui.theme));
+        }
+    }
+
+    fn select_server(&mut self, step: isize) {
+        let index = self.selected as isize + step;
+        self.selected = index.rem_euclid(self.servers.len() as isize) as usize;
+    }
+
+    fn is_live(&self) -> bool {
+        match self.panel {
+            Panel::Console
+            | Panel::PlayerData
+            | Panel::Datapacks
+            | Panel::Registry
+            | Panel::Permissions => false,
+            Panel::Players
+            | Panel::Performance
+            | Panel::Worlds
+            | Panel::Afk
+            | Panel::Jobs
+            | Panel::Gamerules
+            | Panel::Security => true,
+            Panel::Custom(index) => self.custom_panels[index].is_live(),
+            // plugins request a redraw whenever they update their panel
+            #[cfg(feature = "plugin")]
+            Panel::Plugin(_) => false,
+        }
+    }
+
+    /// Applies reloaded ui settings, keeping the selected panel
+    fn apply_ui(&mut self, ui: UiConfig) {
+        logger::set_max_lines(ui.scrollback);
+        self.keymap = Keymap::new(&ui.keybinds);
+        self.ui = ui;
+    }
+
+    /// Stops the selected server, or exits once every server has stopped
+    ///
+    /// A remote session is closed instead, its server keeps running.
+    fn stop_server(&mut self) {
+        if self.remote.is_some() {
+            self.token.cancel();
+            return;
+        }
+        let running = !self.current().server_token.is_cancelled();
+        if running && self.ui.confirm_stop {
+            self.confirm = Some(Confirm::StopServer);
+        } else if running {
+            self.current().server_token.cancel();
+        } else if self
+            .servers
+            .iter()
+            .all(|server| server.server_token.is_cancelled())
+        {
+            self.token.cancel();
+        }
+    }
+
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        if self.panel == Panel::Console && self.console().handle_mouse(event) {
+            return;
+        }
         match event.kind {
             MouseEventKind::ScrollDown if event.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.scroll_bottom = true;
+                self.console().scroll_bottom();
+            }
+            MouseEventKind::ScrollDown => {
+                let lines = self.wheel_lines();
+                self.console().scroll_down_by(lines);
+            }
+            MouseEventKind::ScrollUp => {
+                let lines = self.wheel_lines();
+                self.console().scroll_up_by(lines);
             }
-            MouseEventKind::ScrollDown => self.scroll_view_state.scroll_down(),
-            MouseEventKind::ScrollUp => self.scroll_up(),
             _ => self.redraw = false,
         }
     }
 
-    /// Starts the steel server
-    pub async fn start_server(mut steel_server: SteelServer) -> anyhow::Result<()> {
+    /// Lines to scroll by for a step of the mouse wheel, more while the wheel is flicked quickly
+    fn wheel_lines(&mut self) -> u16 {
+        let now = Instant::now();
+        let streak = match self.last_wheel {
+            Some((at, streak)) if self.ui.scroll_acceleration && now - at < WHEEL_STREAK => {
+                (streak + 1).min(MAX_WHEEL_STREAK)
+            }
+            _ => 1,
+        };
+        self.last_wheel = Some((now, streak));
+        self.ui.scroll_lines.max(1).saturating_mul(streak)
+    }
+
+    /// Starts the steel server along with the services configured in `config`, returning once it stopped
+    pub async fn start_server(
+        steel_server: SteelServer,
+        config: TuiConfig,
+        server_token: CancellationToken,
+    ) -> anyhow::Result<()> {
+        let (log, server) = (ServerLog::current(), steel_server.server.clone());
+        signals::running(log, server.clone());
+        let result = Self::serve(steel_server, config, server_token).await;
+        signals::stopped(log, &server);
+        // the menu of the stopped server offers to show the error
+        STOPPED
+            .lock()
+            .insert(log, result.as_ref().err().map(|err| format!("{err:?}")));
+        REDRAW.notify_one();
+        result
+    }
+
+    async fn serve(
+        mut steel_server: SteelServer,
+        config: TuiConfig,
+        server_token: CancellationToken,
+    ) -> anyhow::Result<()> {
         let server = steel_server.server.clone();
         let task_tracker = TaskTracker::new();
+        security::install(&mut steel_server);
+
+        if let Some(path) = &config.audit_log
+            && let Err(err) = audit::open(path)
+        {
+            warn!("Failed to open the audit log: {err:#}");
+        }
+        rcon::spawn(server.clone(), config.rcon, server_token.clone());
+        query::spawn(server.clone(), config.query, server_token.clone());
+        api::spawn(server.clone(), config.api, server_token.clone());
+        triggers::spawn(server.clone(), server_token.clone());
+        save::spawn(server.clone(), config.autosave, server_token.clone());
+        watchdog::spawn(server.clone(), config.watchdog, server_token.clone());
+        maintenance::configure(config.maintenance);
+        afk::spawn(server.clone(), config.afk, server_token.clone());
+        session::spawn(server.clone(), server_token.clone());
+        notifications::name_server(ServerLog::current(), &config.name);
+        // already running if the terminal ui started them, they serve every server
+        webhooks::spawn();
+        let webhooks = webhooks::configure(ServerLog::current(), config.notifications.webhooks);
+        desktop::spawn();
+        desktop::configure(ServerLog::current(), config.notifications.desktop);
+        email::spawn();
+        email::configure(ServerLog::current(), config.notifications.email);
+        alerts::report_previous_crash(&config.name);
+        alerts::spawn(
+            server.clone(),
+            config.notifications.low_tps,
+            server_token.clone(),
+        );
+        #[cfg(feature = "ssh")]
+        ssh::spawn(server.clone(), config.ssh, config.ui, server_token.clone());
 
         #[cfg(feature = "plugin")]
-        match plugin::init("plugins").await {
-            Ok((host, api)) => {
-                tokio::spawn(async move {
-                    while let Ok(event) = api.recv_async().await {
-                        match event {
-                            steel_core::PluginApi::PlayerJoinEvent(player) => {
-                                use steel_host::interface::objects;
-                                use steel_plugin_sdk::{event::PlayerJoinEvent, objects::Handle};
-
-                                let key = host
-                                    .state
-                                    .register_object_handler(objects::make_player_handler(player))
-                                    .await;
-
-                                let event = PlayerJoinEvent {
-                                    player: Handle::from_raw(key),
-                                };
-
-                                host.state
-                                    .handler_registry
-                                    .read()
-                                    .await
-                                    .dispatch_topic(&event)
-                                    .await
-                                    .context("failed to dispatch topic")?;
-
-                                host.state
-                                    .unregister_object_handler(key)
-                                    .await
-                                    .context("failed to unregister object")?;
-                            }
-                            steel_core::PluginApi::PlayerLeaveEvent(player) => {
-                                use steel_host::interface::objects;
-                                use steel_plugin_sdk::{event::PlayerLeaveEvent, objects::Handle};
-
-                                let key = host
-                                    .state
-                                    .register_object_handler(objects::make_player_handler(player))
-                                    .await;
-
-                                let event = PlayerLeaveEvent {
-                                    player: Handle::from_raw(key),
-                                };
-
-                                host.state
-                                    .handler_registry
-                                    .read()
-                                    .await
-                                    .dispatch_topic(&event)
-                                    .await
-                                    .context("failed to dispatch topic")?;
-
-                                host.state
-                                    .unregister_object_handler(key)
-                                    .await
-                                    .context("failed to unregister object")?;
-                            }
-                        }
-                    }
-                    anyhow::Result::<()>::Ok(())
-                });
+        match plugin::init(config.plugins_dir.clone(), config.plugin_limits).await {
+            Ok((manager, api)) => {
+                if config.plugins_hot_reload
+                    && let Err(err) =
+                        plugin::watch(manager.clone(), &config.plugins_dir, server_token.clone())
+                {
+                    warn!("Failed to watch the plugins directory: {err:#}");
+                }
+                plugin::events::spawn(manager, api);
             }
             Err(err) => {
                 error!("Failed to initialize the plugin system: {err:?}");
             }
         }
 
-        steel_server.start(task_tracker.clone()).await;
+        // the port is only bound now, a port in use fails here once the world is loaded
+        let serving = steel_server.start(task_tracker.clone());
+        pin!(serving);
+        // a server that fails to start is never reported as started and never runs the autoexec script
+        let on_started = async {
+            status::started(&server).await;
+            #[cfg(target_os = "linux")]
+            systemd::ready(server.clone(), server_token.clone());
+            notifications::send(
+                NotificationKind::ServerStarted,
+                Severity::Info,
+                "The server has started",
+            );
+            autoexec::run_blocking(server.clone(), config.autoexec.clone()).await;
+        };
+        let started = select! {
+            started = &mut serving => started,
+            () = on_started => serving.await,
+        };
         info!("Waiting for pending tasks...");
 
         task_tracker.close();
         task_tracker.wait().await;
 
-        for world in server.worlds.values() {
-            world.chunk_map.task_tracker.close();
-            world.chunk_map.task_tracker.wait().await;
-        }
-
-        // Save all dirty chunks before shutdown
-        info!("Saving world data...");
-        let mut total_saved = 0;
-        for world in server.worlds.values() {
-            world.cleanup(&mut total_saved).await;
-        }
-        info!("Saved {total_saved} chunks");
-
-        // Save all player data before shutdown
-        info!("Saving player data...");
-        let mut players_to_save = Vec::new();
-        for world in server.worlds.values() {
-            world.players.iter_players(|_, player| {
-                players_to_save.push(player.clone());
-                true
-            });
-        }
-        match server.player_data_storage.save_all(&players_to_save).await {
-            Ok(count) => info!("Saved {count} players"),
-            Err(e) => error!("Failed to save player data: {e}"),
-        }
+        save::shutdown(&server).await;
 
         info!("Server stopped");
-        LOGGER.lock().push(Text::raw(""));
-        LOGGER
-            .lock()
-            .push("Press Ctrl+C again to exit.".white().bold().into());
-        Ok(())
+        notifications::send(
+            NotificationKind::ServerStopped,
+            Severity::Info,
+            "The server has stopped",
+        );
+        if let Some(webhooks) = webhooks
+            && time::timeout(Duration::from_secs(5), webhooks)
+                .await
+                .is_err()
+        {
+            warn!("Timed out delivering the last webhook notifications");
+        }
+        started.context(BindFailed)
     }
 
     /// Starts the steel tui application
     pub async fn run(&mut self) -> anyhow::Result<()> {
-        let mut terminal = ratatui::try_init()?;
-        terminal
-            .backend_mut()
-            .execute(EnableMouseCapture)
-            .context("failed to enable mouse capture")?;
-        terminal
-            .backend_mut()
-            .execute(EnableBracketedPaste)
-            .context("failed to enable bracketed paste")?;
-
-        while !self.token.is_cancelled() {
-            self.draw(&mut terminal)?;
+        // they serve every server, which configures them as it starts
+        webhooks::spawn();
+        desktop::spawn();
+        email::spawn();
+        let mut terminal =
+            terminal::init(self.mouse_capture, self.ui.alternate_screen, &self.token)?;
+        self.session.open();
+        self.suspendable = cfg!(unix);
+        let result = loop {
+            let result = self.run_on(&mut terminal).await;
+            if result.is_err() || !self.suspend_requested {
+                break result;
+            }
+
+            self.suspend_requested = false;
+            #[cfg(unix)]
+            terminal::suspend(
+                &mut terminal,
+                self.mouse_capture,
+                self.ui.alternate_screen,
+                &self.token,
+            )?;
+        };
+        self.session.close();
+        terminal::restore(&mut terminal, self.mouse_capture)?;
+        result
+    }
+
+    /// Runs the event loop, drawing onto an already initialized `terminal`
+    async fn run_on<B>(&mut self, terminal: &mut Terminal<B>) -> anyhow::Result<()>
+    where
+        B: Backend,
+        B::Error: Send + Sync + 'static,
+    {
+        let mut refresh = time::interval(self.tick_rate);
+
+        while !self.token.is_cancelled() && !self.suspend_requested {
+            self.draw(terminal)?;
 
+            let toast_expiry = self.toasts.front().map(|(_, expiry)| *expiry);
             let event = select! {
                 biased;
                 event = self.event_rx.recv() => {
@@ -300,34 +1012,52 @@ impl SteelApp {
                 () = REDRAW.notified() => {
                     continue;
                 }
+                () = time::sleep_until(self.next_frame().unwrap_or_else(Instant::now)),
+                    if self.frame_pending =>
+                {
+                    continue;
+                }
+                _ = refresh.tick(), if self.is_live() => {
+                    continue;
+                }
+                notification = self.notifications.recv() => {
+                    if let Ok(notification) = notification {
+                        self.toast(notification);
+                    }
+                    continue;
+                }
+                restarted = self.restarts.1.recv() => {
+                    if let Some(restarted) = restarted {
+                        self.restarted(restarted);
+                    }
+                    continue;
+                }
+                config = self.reloads.recv() => {
+                    if let Ok(config) = config {
+                        self.apply_ui(config.ui.clone());
+                    }
+                    continue;
+                }
+                () = time::sleep_until(toast_expiry.unwrap_or_else(Instant::now)),
+                    if toast_expiry.is_some() =>
+                {
+                    self.toasts.pop_front();
+                    continue;
+                }
             };
 
             match event {
-                AppEvent::UiEvent(Event::Key(event)) => self.handle_key(event),
-                AppEvent::UiEvent(Event::Mouse(event)) => self.handle_mouse(event),
-                AppEvent::UiEvent(Event::Paste(paste)) => {
-                    let mut value = self.input.value_and_reset();
-                    value.push_str(&paste);
-
-                    replace_with::replace_with(
-                        &mut self.input,
-                        || Input::new(String::new()),
-                        |input| input.with_value(value),
-                    );
+                AppEvent::UiEvent(Event::Resize(width, height)) => {
+                    terminal.resize(Rect::new(0, 0, width, height))?;
+                    // cells drawn at the old size would otherwise linger until they are drawn over
+                    terminal.clear()?;
+                    for server in &mut self.servers {
+                        server.console.resize();
+                    }
                 }
-                AppEvent::UiEvent(_) => (),
+                AppEvent::UiEvent(event) => self.handle_event(event),
             }
         }
-
-        terminal
-            .backend_mut()
-            .execute(DisableBracketedPaste)
-            .context("failed to disable bracketed paste")?;
-        terminal
-            .backend_mut()
-            .execute(DisableMouseCapture)
-            .context("failed to disable bracketed paste")?;
-        ratatui::try_restore().context("failed to restore terminal")?;
         Ok(())
     }
 }
@@ -337,31 +1067,207 @@ impl Widget for &mut SteelApp {
     where
         Self: Sized,
     {
-        let [text_area, input_area] =
-            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        // the server switcher is only shown when there is something to switch to
+        let servers_height = u16::from(self.servers.len() > 1);
+        let [servers_area, tabs_area, panel_area, input_area] = Layout::vertical([
+            Constraint::Length(servers_height),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        let theme = self.ui.theme;
 
-        let lock = LOGGER.lock();
-        let text = &lock.text;
+        let names: Vec<String> = self
+            .servers
+            .iter()
+            .map(|server| {
+                if server.server_token.is_cancelled() {
+                    format!("{} (stopped)", server.name)
+                } else {
+                    server.name.clone()
+                }
+            })
+            .collect();
+        let names = names.iter().map(String::as_str).collect();
+        panels::render_tabs(names, self.selected, &theme, servers_area, buf);
 
-        let content_size = Size::new(text_area.width - 1, text.lines.len() as u16);
-        let mut scroll_view = ScrollView::new(content_size)
-            .horizontal_scrollbar_visibility(ScrollbarVisibility::Never);
+        #[cfg(feature = "plugin")]
+        let plugin_titles = plugin::panels::titles();
+        let titles = self
+            .tabs()
+            .map(|panel| match panel {
+                Panel::Custom(index) => self.custom_panels[index].title(),
+                #[cfg(feature = "plugin")]
+                Panel::Plugin(index) => plugin_titles.get(index).map_or("", String::as_str),
+                panel => panel.title(),
+            })
+            .collect();
+        let selected = self.tabs().position(|panel| panel == self.panel);
+        panels::render_tabs(titles, selected.unwrap_or_default(), &theme, tabs_area, buf);
 
-        if self.scroll_view_state.offset().y + text_area.height > content_size.height {
-            self.scroll_bottom = true;
+        let current = &mut self.servers[self.selected];
+        let server = current.server.as_deref();
+        match (self.panel, server) {
+            (Panel::Console, _) => current.console.render(panel_area, buf),
+            (Panel::Players, _) => {
+                current
+                    .players
+                    .render(current.status.as_ref(), &theme, panel_area, buf);
+            }
+            (Panel::Performance, _) => {
+                current
+                    .performance
+                    .render(current.status.as_ref(), server, &theme, panel_area, buf)
+            }
+            (Panel::PlayerData, Some(server)) => {
+                current.player_data.render(server, &theme, panel_area, buf);
+            }
+            (Panel::Afk, _) => current
+                .afk
+                .render(current.console.log(), &theme, panel_area, buf),
+            (Panel::Jobs, _) => current
+                .jobs
+                .render(current.console.log(), &theme, panel_area, buf),
+            (Panel::Gamerules, Some(server)) => {
+                current.gamerules.render(server, &theme, panel_area, buf);
+            }
+            (Panel::Datapacks, _) => {
+                current
+                    .datapacks
+                    .render(current.console.log(), &theme, panel_area, buf);
+            }
+            (Panel::Registry, _) => current.registry.render(&theme, panel_area, buf),
+            (Panel::Permissions, Some(server)) => {
+                current.permissions.render(server, &theme, panel_area, buf);
+            }
+            (Panel::Worlds, Some(server)) => current.worlds.render(server, &theme, panel_area, buf),
+            (Panel::Security, _) => current.security.render(&theme, panel_area, buf),
+            (Panel::Custom(index), Some(server)) => {
+                self.custom_panels[index].render(server, &theme, panel_area, buf);
+            }
+            #[cfg(feature = "plugin")]
+            (Panel::Plugin(index), _) => plugin::panels::render(index, &theme, panel_area, buf),
+            (_, None) => Paragraph::new("Only a running server has this tab")
+                .style(Style::new().fg(theme.muted))
+                .render(panel_area, buf),
         }
 
-        if self.scroll_bottom {
-            self.scroll_view_state.scroll_to_bottom();
+        let stopped = STOPPED.lock().get(&current.console.log()).cloned();
+        if let Some(error) = stopped {
+            current
+                .stopped_menu
+                .render(error.is_some(), &theme, panel_area, buf);
         }
 
+        // widths are measured in terminal columns, wide characters like CJK and emoji take two
+        let [prompt_area, value_area] =
+            Layout::horizontal([Constraint::Length(2), Constraint::Fill(1)]).areas(input_area);
+        let scroll = self
+            .input
+            .visual_scroll(value_area.width.saturating_sub(1) as usize);
         self.cursor_position = Position {
-            x: self.input.cursor() as u16 + 2,
+            x: value_area.x + (self.input.visual_cursor() - scroll) as u16,
             y: input_area.y,
         };
+        // terminals draw the text being composed with an input method at the cursor, it belongs where the text goes
+        match self.panel {
+            Panel::PlayerData => {
+                self.cursor_position = self.servers[self.selected].player_data.cursor();
+            }
+            Panel::Registry => self.cursor_position = self.servers[self.selected].registry.cursor(),
+            Panel::Permissions => {
+                self.cursor_position = self.servers[self.selected].permissions.cursor();
+            }
+            _ => (),
+        }
+        "> ".fg(theme.prompt).render(prompt_area, buf);
+        #[allow(unused_mut)]
+        let mut input = vec![self.input.value().into()];
+        #[cfg(feature = "plugin")]
+        if let Some(hint) = completion::hint(self.input.value()) {
+            input.push(hint.fg(theme.muted));
+        }
+        Paragraph::new(Line::from(input))
+            .scroll((0, scroll as u16))
+            .render(value_area, buf);
+        let mut status = Vec::new();
+        if let Some(save) = self.current().saves.status() {
+            status.push(save.fg(theme.muted));
+        }
+        // stays until maintenance is turned off, so nobody forgets players can't join
+        if maintenance::is_active(self.current().console.log()) {
+            status.push(" MAINTENANCE ".white().on_red().bold());
+        }
+        if let Some(port) = query::port(self.current().console.log()) {
+            status.push(format!(" query :{port}").fg(theme.muted));
+        }
+        #[cfg(feature = "tokio-console")]
+        if let Some(address) = tokio_console::address() {
+            status.push(format!(" tokio-console {address}").fg(theme.muted));
+        }
+        Line::from(status).right_aligned().render(input_area, buf);
+
+        // shows how players will see a text component before it is sent
+        if let Some(json) = text_preview::component(self.input.value()) {
+            let preview = text_preview::preview(json).unwrap_or_else(|err| Text::from(err).red());
+            let height = (preview.lines.len() as u16 + 2).min(panel_area.height);
+            let area = Rect {
+                y: panel_area.bottom() - height,
+                height,
+                ..panel_area
+            };
+            Clear.render(area, buf);
+            Paragraph::new(preview)
+                .block(Block::bordered().title("Preview").border_style(theme.muted))
+                .render(area, buf);
+        }
+
+        let mut toast_area = panel_area;
+        for (notification, _) in &self.toasts {
+            Toast::new(notification, theme).render(toast_area, buf);
+            toast_area.y += Toast::HEIGHT;
+            toast_area.height = toast_area.height.saturating_sub(Toast::HEIGHT);
+        }
 
-        scroll_view.render_widget(text, scroll_view.area());
-        scroll_view.render(text_area, buf, &mut self.scroll_view_state);
-        Span::raw(format!("> {}", self.input.value())).render(input_area, buf);
+        #[cfg(feature = "plugin")]
+        if let Some(progress) = plugin::startup::progress() {
+            let lines = progress
+                .into_iter()
+                .map(|plugin| {
+                    Line::from(vec![
+                        format!("{:<24}", plugin.name).into(),
+                        format!("{:<12}", plugin.stage.to_string()).fg(theme.accent),
+                        format!("{:.1?}", plugin.elapsed).fg(theme.muted),
+                    ])
+                })
+                .collect();
+            Splash::new("Starting plugins", lines, theme).render(panel_area, buf);
+        }
+
+        #[cfg(feature = "plugin")]
+        if let Some((plugin, capability, detail)) = permissions::pending() {
+            let lines = vec![
+                Line::from(format!("{plugin} requests {capability} access")).bold(),
+                Line::from(detail),
+                Line::from(""),
+                Line::from("[o] allow once  [a] allow always  [d] deny").fg(theme.muted),
+            ];
+            Splash::new("Permission", lines, theme).render(area, buf);
+        }
+
+        if let Some((title, nodes)) = inspect::take(self.session) {
+            self.inspector = Some(Tree::new(title, nodes, theme));
+        }
+        if let Some(inspector) = &mut self.inspector {
+            inspector.render(area, buf);
+        }
+
+        if let Some(confirm) = &self.confirm {
+            Popup::new(confirm.message(), theme).render(area, buf);
+        }
+        if let Some(message) = self.session.pending() {
+            Popup::new(format!("{message} [y/N]"), theme).render(area, buf);
+        }
     }
 }
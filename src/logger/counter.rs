@@ -0,0 +1,31 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+
+static ERRORS: AtomicU64 = AtomicU64::new(0);
+static WARNINGS: AtomicU64 = AtomicU64::new(0);
+
+/// A tracing layer that counts logged errors and warnings
+#[derive(Debug, Clone, Copy)]
+pub struct LogCounter;
+
+impl LogCounter {
+    /// Returns how many errors and warnings have been logged so far
+    pub fn counts() -> (u64, u64) {
+        (
+            ERRORS.load(Ordering::Relaxed),
+            WARNINGS.load(Ordering::Relaxed),
+        )
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogCounter {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        match *event.metadata().level() {
+            Level::ERROR => ERRORS.fetch_add(1, Ordering::Relaxed),
+            Level::WARN => WARNINGS.fetch_add(1, Ordering::Relaxed),
+            _ => return,
+        };
+    }
+}
@@ -1,22 +1,282 @@
-use ratatui::prelude::Text;
+use crate::logger::LineMetadata;
+use ratatui::prelude::{Line, Stylize, Text};
+use std::iter;
+use std::ops::Range;
+use std::sync::Arc;
+use tracing::Level;
+
+/// Identifies a line of a history, it stays the same while lines are inserted before it or trimmed from the start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineId(u64);
 
 pub struct LineHistory {
     pub text: Text<'static>,
+    /// The id of each line of `text`
+    ids: Vec<LineId>,
+    /// The id the next added line gets
+    next_id: u64,
+    /// The plugin each line of `text` was logged by
+    sources: Vec<Option<Arc<str>>>,
+    /// Where each line of `text` was logged from, if it came from a tracing event
+    metadata: Vec<Option<Arc<LineMetadata>>>,
+    /// Whether each line of `text` continues the message of the line before it
+    continues: Vec<bool>,
+    max_lines: usize,
+    /// Number of lines dropped from the start since the history was created
+    trimmed: usize,
 }
 
 impl LineHistory {
-    const MAX_HISTORY: usize = 1000;
+    const DEFAULT_MAX_LINES: usize = 1000;
 
     pub fn new() -> Self {
         Self {
             text: Text::default(),
+            ids: Vec::new(),
+            next_id: 0,
+            sources: Vec::new(),
+            metadata: Vec::new(),
+            continues: Vec::new(),
+            max_lines: Self::DEFAULT_MAX_LINES,
+            trimmed: 0,
         }
     }
 
+    pub const fn max_lines(&self) -> usize {
+        self.max_lines
+    }
+
+    /// Number of lines dropped from the start since the history was created, to keep views of it in place
+    pub const fn trimmed(&self) -> usize {
+        self.trimmed
+    }
+
+    pub fn set_max_lines(&mut self, max_lines: usize) {
+        self.max_lines = max_lines;
+        self.trim();
+    }
+
     pub fn push(&mut self, text: Text<'static>) {
+        self.push_from(text, None, None);
+    }
+
+    /// Appends `text` logged by the plugin `source`, from the event described by `metadata`
+    pub fn push_from(
+        &mut self,
+        text: Text<'static>,
+        source: Option<Arc<str>>,
+        metadata: Option<Arc<LineMetadata>>,
+    ) {
+        let ids = self.allocate(text.lines.len());
+        self.ids.extend(ids);
+        self.sources
+            .extend(text.lines.iter().map(|_| source.clone()));
+        self.metadata
+            .extend(text.lines.iter().map(|_| metadata.clone()));
+        self.continues
+            .extend((0..text.lines.len()).map(|line| line > 0));
         self.text.extend(text);
+        self.trim();
+    }
+
+    /// Appends `line` logged by the plugin `source`, returning its id
+    pub fn push_line_from(&mut self, line: Line<'static>, source: Option<Arc<str>>) -> LineId {
+        let id = LineId(self.next_id);
+        self.next_id += 1;
+        self.ids.push(id);
+        self.sources.push(source);
+        self.metadata.push(None);
+        self.continues.push(false);
+        self.text.lines.push(line);
+        self.trim();
+        id
+    }
+
+    /// Inserts `text` logged by the plugin `source`, from the event described by `metadata`, right after the line
+    /// `after`, returning the id of its last line
+    ///
+    /// The text is appended instead if `after` was trimmed away.
+    pub fn insert_after(
+        &mut self,
+        after: LineId,
+        text: Text<'static>,
+        source: Option<Arc<str>>,
+        metadata: Option<Arc<LineMetadata>>,
+    ) -> Option<LineId> {
+        if text.lines.is_empty() {
+            return None;
+        }
+        let Some(index) = self.position(after).map(|index| index + 1) else {
+            self.push_from(text, source, metadata);
+            return self.ids.last().copied();
+        };
+        let ids = self.allocate(text.lines.len());
+        let last = ids.last().copied();
+        self.ids.splice(index..index, ids);
+        self.sources
+            .splice(index..index, iter::repeat_n(source, text.lines.len()));
+        self.metadata
+            .splice(index..index, iter::repeat_n(metadata, text.lines.len()));
+        self.continues
+            .splice(index..index, (0..text.lines.len()).map(|line| line > 0));
+        self.text.lines.splice(index..index, text.lines);
+        self.trim();
+        last
+    }
+
+    /// The line `id`, unless it was trimmed away
+    pub fn line_mut(&mut self, id: LineId) -> Option<&mut Line<'static>> {
+        let index = self.position(id)?;
+        self.text.lines.get_mut(index)
+    }
+
+    /// The index into `text` of the line `id`, unless it was trimmed away
+    fn position(&self, id: LineId) -> Option<usize> {
+        // lines grouped under a command are inserted in the middle, so the ids are not sorted
+        self.ids.iter().rposition(|line| *line == id)
+    }
+
+    /// Hands out the ids of `count` lines about to be added
+    fn allocate(&mut self, count: usize) -> Vec<LineId> {
+        let ids = (self.next_id..).take(count).map(LineId).collect();
+        self.next_id += count as u64;
+        ids
+    }
+
+    /// The lines logged by the plugin `filter` at `level`, or every line, with the messages spanning several lines
+    /// folded to their first line if `folded`, each with its index into `text`
+    pub fn view(
+        &self,
+        filter: Option<&str>,
+        level: Option<Level>,
+        folded: bool,
+    ) -> (Text<'static>, Vec<usize>) {
+        let mut text = Text::default();
+        let mut indices = Vec::new();
+        for (index, line) in self.text.lines.iter().enumerate() {
+            if filter.is_some_and(|filter| self.sources[index].as_deref() != Some(filter))
+                || level.is_some_and(|level| {
+                    self.metadata[index]
+                        .as_ref()
+                        .is_none_or(|metadata| metadata.level != level)
+                })
+                || folded && index > 0 && self.continues[index]
+            {
+                continue;
+            }
+            let mut line = line.clone();
+            if folded {
+                let hidden = self.message(index).len() - 1;
+                if hidden > 0 {
+                    line.push_span(format!(" (+{hidden} lines)").dark_gray());
+                }
+            }
+            text.lines.push(line);
+            indices.push(index);
+        }
+        (text, indices)
+    }
+
+    /// The indices into `text` of the lines of the message the line at `index` belongs to
+    pub fn message(&self, index: usize) -> Range<usize> {
+        let start = self.continues[..=index]
+            .iter()
+            .rposition(|continues| !continues)
+            .unwrap_or(0);
+        let end = self.continues[index + 1..]
+            .iter()
+            .position(|continues| !continues)
+            .map_or(self.continues.len(), |line| index + 1 + line);
+        start..end
+    }
+
+    /// The text of the message the line at `index` belongs to, every line of it
+    pub fn message_text(&self, index: usize) -> String {
+        self.text.lines[self.message(index)]
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Where the line at `index` of `text` was logged from, if that was captured
+    pub fn metadata(&self, index: usize) -> Option<Arc<LineMetadata>> {
+        self.metadata.get(index).cloned().flatten()
+    }
+
+    /// Every line with where it was logged from, if that was captured
+    pub fn lines_with_metadata(
+        &self,
+    ) -> impl Iterator<Item = (&Line<'static>, Option<&Arc<LineMetadata>>)> {
         self.text
             .lines
-            .drain(0..self.text.lines.len().saturating_sub(Self::MAX_HISTORY));
+            .iter()
+            .zip(self.metadata.iter().map(Option::as_ref))
+    }
+
+    fn trim(&mut self) {
+        let excess = self.text.lines.len().saturating_sub(self.max_lines);
+        self.text.lines.drain(0..excess);
+        self.ids.drain(0..excess);
+        self.sources.drain(0..excess);
+        self.metadata.drain(0..excess);
+        self.continues.drain(0..excess);
+        self.trimmed += excess;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineHistory;
+    use ratatui::prelude::{Line, Text};
+
+    fn lines(history: &LineHistory) -> Vec<String> {
+        history.text.lines.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn inserts_after_a_line_that_moved() {
+        let mut history = LineHistory::new();
+        let first = history.push_line_from(Line::from("first"), None);
+        let second = history.push_line_from(Line::from("second"), None);
+        history.push(Text::from("last"));
+
+        let output = history.insert_after(first, Text::from("a"), None, None);
+        assert!(output.is_some());
+        history.insert_after(second, Text::from("b"), None, None);
+        assert_eq!(lines(&history), ["first", "a", "second", "b", "last"]);
+
+        if let Some(line) = history.line_mut(second) {
+            line.push_span(" done");
+        }
+        assert_eq!(lines(&history)[2], "second done");
+    }
+
+    #[test]
+    fn trimmed_lines_are_gone() {
+        let mut history = LineHistory::new();
+        history.set_max_lines(2);
+        let echo = history.push_line_from(Line::from("echo"), None);
+        history.push(Text::from("one\ntwo"));
+        assert!(history.line_mut(echo).is_none());
+        assert_eq!(history.trimmed(), 1);
+
+        // output of a command whose echo was trimmed is appended
+        history.insert_after(echo, Text::from("three"), None, None);
+        assert_eq!(lines(&history), ["two", "three"]);
+    }
+
+    #[test]
+    fn messages_span_their_continued_lines() {
+        let mut history = LineHistory::new();
+        history.push(Text::from("one"));
+        history.push(Text::from("two\n  more\n  lines"));
+        history.push(Text::from("three"));
+        assert_eq!(history.message(2), 1..4);
+        assert_eq!(history.message_text(3), "two\n  more\n  lines");
+
+        let (folded, indices) = history.view(None, None, true);
+        assert_eq!(indices, [0, 1, 4]);
+        assert_eq!(folded.lines[1].to_string(), "two (+2 lines)");
     }
 }
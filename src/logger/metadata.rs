@@ -0,0 +1,171 @@
+use crate::widgets::TreeNode;
+use chrono::{DateTime, Local};
+use std::cell::RefCell;
+use std::fmt::{self, Write};
+use std::sync::Arc;
+use std::thread;
+use tokio::task;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+thread_local! {
+    /// Metadata of the event being written on this thread, taken by the writer
+    static CAPTURED: RefCell<Option<Arc<LineMetadata>>> = const { RefCell::new(None) };
+}
+
+/// A span a line was logged in
+#[derive(Debug, Clone)]
+pub struct SpanContext {
+    /// Identifies the span while it is open, closed spans may pass it on
+    pub id: u64,
+    pub name: &'static str,
+    /// The fields of the span, like `x=3 z=-7`
+    pub fields: String,
+}
+
+impl SpanContext {
+    /// The name of the span followed by its fields, like `chunk_load{x=3 z=-7}`
+    pub fn label(&self) -> String {
+        if self.fields.is_empty() {
+            self.name.to_owned()
+        } else {
+            format!("{}{{{}}}", self.name, self.fields)
+        }
+    }
+}
+
+/// Where a line of the log came from, captured when it was logged
+#[derive(Debug)]
+pub struct LineMetadata {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub target: &'static str,
+    /// The file and line of the code that logged it
+    pub location: Option<(&'static str, u32)>,
+    pub thread: String,
+    /// The tokio task it was logged from, if any
+    pub task: Option<task::Id>,
+    /// The spans it was logged in, outermost first
+    pub spans: Vec<SpanContext>,
+}
+
+impl LineMetadata {
+    /// The metadata as nodes of a tree, with the spans nested in each other
+    pub fn nodes(&self) -> Vec<TreeNode> {
+        let mut nodes = vec![
+            TreeNode::leaf(format!(
+                "Time {}",
+                self.time.format("%Y-%m-%d %H:%M:%S%.6f")
+            )),
+            TreeNode::leaf(format!("Level {}", self.level)),
+            TreeNode::leaf(format!("Target {}", self.target)),
+        ];
+        if let Some((file, line)) = self.location {
+            nodes.push(TreeNode::leaf(format!("Location {file}:{line}")));
+        }
+        nodes.push(TreeNode::leaf(format!("Thread {}", self.thread)));
+        if let Some(task) = self.task {
+            nodes.push(TreeNode::leaf(format!("Task {task}")));
+        }
+        let spans = self.spans.iter().rev().fold(None, |inner, span| {
+            Some(TreeNode::branch(span.label(), inner.into_iter().collect()).expanded())
+        });
+        nodes.push(match spans {
+            Some(spans) => TreeNode::branch("Spans", vec![spans]).expanded(),
+            None => TreeNode::leaf("Spans none"),
+        });
+        nodes
+    }
+}
+
+/// Takes the metadata of the event being written on this thread
+pub(super) fn take() -> Option<Arc<LineMetadata>> {
+    CAPTURED.take()
+}
+
+/// The fields of a span as text, kept in its extensions
+#[derive(Default)]
+struct SpanFields(String);
+
+impl Visit for SpanFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        let _ = write!(self.0, "{}={value:?}", field.name());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        let _ = write!(self.0, "{}={value}", field.name());
+    }
+}
+
+/// A tracing layer that captures the level, target, thread and spans of every event for the line it is written as
+#[derive(Debug, Clone, Copy)]
+pub struct LogMetadata;
+
+impl<S> Layer<S> for LogMetadata
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = SpanFields::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(fields) = extensions.get_mut::<SpanFields>() {
+            values.record(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| SpanContext {
+                        id: span.id().into_u64(),
+                        name: span.name(),
+                        fields: span
+                            .extensions()
+                            .get::<SpanFields>()
+                            .map(|fields| fields.0.clone())
+                            .unwrap_or_default(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let current = thread::current();
+        let thread = current
+            .name()
+            .map_or_else(|| format!("{:?}", current.id()), ToOwned::to_owned);
+
+        CAPTURED.set(Some(Arc::new(LineMetadata {
+            time: Local::now(),
+            level: *metadata.level(),
+            target: metadata.target(),
+            location: metadata.file().zip(metadata.line()),
+            thread,
+            task: task::try_id(),
+            spans,
+        })));
+    }
+}
@@ -1,23 +1,166 @@
 use std::{
+    cell::{Cell, RefCell},
     io::{self, Write},
-    sync::LazyLock,
+    sync::{Arc, LazyLock, PoisonError, RwLock},
 };
 
 use crate::REDRAW;
-use crate::logger::line_history::LineHistory;
+use crate::command;
+use crate::logger::line_history::{LineHistory, LineId};
+use crate::nbt;
+use crate::recording;
+use crate::triggers;
 use ansi_to_tui::IntoText;
+use ratatui::style::{Color, Stylize};
+use ratatui::text::{Span, Text};
 use steel_utils::{
     locks::SyncMutex,
     logger::{Level, LogData, SteelLogger},
 };
 use tracing_subscriber::fmt::MakeWriter;
 
+mod counter;
 mod line_history;
+mod metadata;
+pub(crate) mod spans;
+
+pub use counter::LogCounter;
+pub use metadata::{LineMetadata, LogMetadata};
 
 pub(crate) static LOGGER: LazyLock<SyncMutex<LineHistory>> =
     LazyLock::new(|| SyncMutex::new(LineHistory::new()));
 
-/// A writer that forwards all text written into `LOGGER`
+/// Logs of the servers other than the primary one, leaked since they live as long as the process
+static SERVER_LOGS: RwLock<Vec<&'static SyncMutex<LineHistory>>> = RwLock::new(Vec::new());
+
+/// Colors the prefixes of plugin output are picked from
+const SOURCE_COLORS: [Color; 6] = [
+    Color::LightBlue,
+    Color::LightGreen,
+    Color::LightMagenta,
+    Color::LightYellow,
+    Color::LightCyan,
+    Color::LightRed,
+];
+
+thread_local! {
+    static CURRENT: Cell<ServerLog> = const { Cell::new(ServerLog::PRIMARY) };
+    static SOURCE: RefCell<Option<Arc<str>>> = const { RefCell::new(None) };
+    /// The line the next line logged on this thread is inserted after, while it is grouped under a command
+    static GROUP: Cell<Option<LineId>> = const { Cell::new(None) };
+}
+
+/// The log of one managed server
+///
+/// Text written through `TuiLoggerWriter` goes into the log attached to the writing thread,
+/// which is the primary log unless the thread belongs to the runtime of another server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ServerLog(usize);
+
+impl ServerLog {
+    /// The log of the server started from the command line, backed by `LOGGER`
+    pub const PRIMARY: Self = Self(0);
+
+    /// Creates an empty log for another server
+    #[must_use]
+    pub fn create() -> Self {
+        let history = Box::leak(Box::new(SyncMutex::new(LineHistory::new())));
+        history.lock().set_max_lines(LOGGER.lock().max_lines());
+        let mut logs = SERVER_LOGS.write().unwrap_or_else(PoisonError::into_inner);
+        logs.push(history);
+        Self(logs.len())
+    }
+
+    /// Sends everything written on the current thread into this log from now on
+    pub fn attach(self) {
+        CURRENT.set(self);
+    }
+
+    /// Runs `f` with everything it writes on the current thread going into this log
+    pub fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        let previous = CURRENT.replace(self);
+        let result = f();
+        CURRENT.set(previous);
+        result
+    }
+
+    /// The log attached to the current thread
+    pub(crate) fn current() -> Self {
+        CURRENT.get()
+    }
+
+    pub(crate) fn history(self) -> &'static SyncMutex<LineHistory> {
+        match self.0 {
+            0 => &LOGGER,
+            index => SERVER_LOGS.read().unwrap_or_else(PoisonError::into_inner)[index - 1],
+        }
+    }
+}
+
+/// Sets the number of lines kept by the log of every server
+pub(crate) fn set_max_lines(max_lines: usize) {
+    LOGGER.lock().set_max_lines(max_lines);
+    for history in SERVER_LOGS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+    {
+        history.lock().set_max_lines(max_lines);
+    }
+}
+
+/// Runs `f` with everything it logs on the current thread tagged as output of the plugin `source`
+#[cfg(feature = "plugin")]
+pub(crate) fn with_source<R>(source: &str, f: impl FnOnce() -> R) -> R {
+    let previous = SOURCE.replace(Some(Arc::from(source)));
+    let result = f();
+    SOURCE.set(previous);
+    result
+}
+
+/// Runs `f` with everything it logs on the current thread grouped under the line `line` of its log
+pub(crate) fn grouped_under<R>(line: LineId, f: impl FnOnce() -> R) -> R {
+    let previous = GROUP.replace(Some(line));
+    let result = f();
+    GROUP.set(previous);
+    result
+}
+
+/// Indents every line of `text` to show it belongs to the line above
+fn indent(text: &mut Text<'static>) {
+    for line in &mut text.lines {
+        line.spans.insert(0, Span::raw("  │ ").fg(Color::DarkGray));
+    }
+}
+
+/// Colors the `[source]` prefix plugin output is logged with, the same color for the same plugin
+fn color_source(text: &mut Text<'static>, source: &str) {
+    let prefix = format!("[{source}]");
+    let hash = source.bytes().fold(0usize, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(usize::from(byte))
+    });
+    let color = SOURCE_COLORS[hash % SOURCE_COLORS.len()];
+
+    for line in &mut text.lines {
+        let found = line.spans.iter().enumerate().find_map(|(index, span)| {
+            let (before, after) = span.content.split_once(&prefix)?;
+            Some((
+                index,
+                [
+                    Span::styled(before.to_owned(), span.style),
+                    Span::styled(prefix.clone(), span.style).fg(color).bold(),
+                    Span::styled(after.to_owned(), span.style),
+                ],
+            ))
+        });
+        if let Some((index, spans)) = found {
+            line.spans.splice(index..=index, spans);
+            return;
+        }
+    }
+}
+
+/// A writer that forwards all text written into the log of the writing thread
 #[derive(Debug, Clone, Copy)]
 pub struct TuiLoggerWriter;
 
@@ -29,8 +172,25 @@ impl Write for TuiLoggerWriter {
             return Ok(0);
         }
 
-        let text = buf.into_text().expect("failed ansi-to-tui conversion");
-        LOGGER.lock().push(text);
+        recording::log(&buf);
+        let mut text = buf.into_text().expect("failed ansi-to-tui conversion");
+        triggers::highlight(&mut text);
+        let source = SOURCE.with_borrow(Clone::clone);
+        let metadata = metadata::take();
+        if let Some(source) = &source {
+            color_source(&mut text, source);
+        }
+        let mut history = ServerLog::current().history().lock();
+        match GROUP.get() {
+            Some(after) => {
+                indent(&mut text);
+                if let Some(last) = history.insert_after(after, text, source, metadata) {
+                    GROUP.set(Some(last));
+                }
+            }
+            None => history.push_from(text, source, metadata),
+        }
+        drop(history);
         REDRAW.notify_one();
 
         Ok(buf.len())
@@ -59,6 +219,10 @@ impl SteelLogger for Logger {
         match level {
             Level::Tracing(_) => (),
             Level::Console => {
+                let message = message.to_string();
+                command::capture(&message);
+                // a single line of SNBT can fill the whole log, it is easier to read as a tree
+                let message = nbt::open_long(&message).unwrap_or(message);
                 tracing::info!("[Console] {message}");
             }
             Level::Chat(player) => {
@@ -0,0 +1,76 @@
+use crate::logger::line_history::LineHistory;
+use crate::logger::metadata::SpanContext;
+use crate::widgets::TreeNode;
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Span};
+
+/// A line of the log, or a span with the lines logged in it
+enum Node {
+    Line(Line<'static>),
+    Span {
+        id: u64,
+        label: String,
+        children: Vec<Node>,
+    },
+}
+
+impl Node {
+    /// Number of log lines in this node
+    fn lines(&self) -> usize {
+        match self {
+            Self::Line(_) => 1,
+            Self::Span { children, .. } => children.iter().map(Self::lines).sum(),
+        }
+    }
+
+    fn into_tree(self) -> TreeNode {
+        let lines = self.lines();
+        match self {
+            Self::Line(line) => TreeNode::leaf(line),
+            Self::Span {
+                label, children, ..
+            } => TreeNode::branch(
+                Line::from(vec![
+                    Span::from(label).cyan(),
+                    format!(" {lines} lines").dark_gray(),
+                ]),
+                children.into_iter().map(Self::into_tree).collect(),
+            ),
+        }
+    }
+}
+
+/// Adds `line` under the span nodes of `spans`, creating the ones that don't exist yet
+fn insert(nodes: &mut Vec<Node>, spans: &[SpanContext], line: Line<'static>) {
+    let Some((span, inner)) = spans.split_first() else {
+        nodes.push(Node::Line(line));
+        return;
+    };
+    // the lines of concurrent tasks interleave in the log, they still belong to the span they were logged in
+    let index = nodes
+        .iter()
+        .rposition(|node| matches!(node, Node::Span { id, .. } if *id == span.id))
+        .unwrap_or_else(|| {
+            nodes.push(Node::Span {
+                id: span.id,
+                label: span.label(),
+                children: Vec::new(),
+            });
+            nodes.len() - 1
+        });
+    if let Node::Span { children, .. } = &mut nodes[index] {
+        insert(children, inner, line);
+    }
+}
+
+/// The lines of `history` nested under the spans they were logged in, each span collapsed
+///
+/// Lines logged outside of any span, or without captured metadata, stay at the top level.
+pub fn tree(history: &LineHistory) -> Vec<TreeNode> {
+    let mut nodes = Vec::new();
+    for (line, metadata) in history.lines_with_metadata() {
+        let spans = metadata.map_or(&[][..], |metadata| &metadata.spans);
+        insert(&mut nodes, spans, line.clone());
+    }
+    nodes.into_iter().map(Node::into_tree).collect()
+}
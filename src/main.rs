@@ -1,121 +1,775 @@
 //! Main entry point for the Steel Minecraft server with a TUI.
+use crate::bench::StartupBench;
+use crate::cli::{Cli, Command};
+use anyhow::{Context, anyhow, bail};
+use clap::Parser;
+use futures::future::BoxFuture;
+use std::io::{self, IsTerminal};
+use std::net::Ipv4Addr;
 use std::num::NonZero;
 use std::path::Path;
-use std::sync::Arc;
-use std::thread;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use std::{env, thread};
 use steel::SteelServer;
 use steel::config::{LogConfig, LogTimeFormat, SteelConfig, load_or_create};
-use steel_tui::{Logger, SteelApp, TuiLoggerWriter};
+use steel_core::server::Server;
+use steel_registry::REGISTRY;
+#[cfg(feature = "plugin")]
+use steel_tui::load_plugins;
+#[cfg(target_os = "linux")]
+use steel_tui::systemd_stopping;
+use steel_tui::{
+    BindFailed, LogCounter, LogMetadata, LogTriggers, Logger, ServerConfig, ServerLog, Signal,
+    Signals, SteelApp, TuiConfig, TuiLoggerWriter, demo, install_panic_hook, on_log_reload,
+    panicked, pregenerate_on, read_commands, reload_configs, replay, session_summary,
+    start_recording, track_io_pool, track_server_config,
+};
+#[cfg(feature = "tokio-console")]
+use steel_tui::{console_layer, is_console_instrumentation};
 use steel_utils::logger::STEEL_LOGGER;
 use steel_utils::text::DisplayResolutor;
 use text_components::fmt::set_display_resolutor;
-use tokio::runtime::{Builder, Runtime};
+use tokio::net::TcpStream;
+use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::time::{sleep, timeout};
+use tokio::{pin, select};
 use tokio_util::sync::CancellationToken;
-use tracing_subscriber::fmt::time;
-use tracing_subscriber::layer::SubscriberExt;
+use tokio_util::task::TaskTracker;
+use tracing::{Level, error};
+#[cfg(feature = "tokio-console")]
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::fmt::time::{self, Uptime};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::{Layered, SubscriberExt};
 use tracing_subscriber::util::SubscriberInitExt;
-use tracing_subscriber::{EnvFilter, fmt};
+use tracing_subscriber::{EnvFilter, Layer, Registry, fmt, reload};
+
+mod bench;
+mod cli;
+#[cfg(windows)]
+mod service;
 
 #[cfg(feature = "mimalloc")]
 #[global_allocator]
 static ALLOC: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-fn init_logger(config: &SteelConfig) {
-    let env_filter = EnvFilter::builder()
-        .with_default_directive(tracing::Level::INFO.into())
-        .from_env_lossy();
+/// How the server console is presented
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frontend {
+    /// The interactive terminal ui
+    Tui,
+    /// Plain log output on stdout, or the journal when running as a systemd unit
+    Headless,
+    /// Running as a Windows service, logging into `logs/service.log`
+    #[cfg(windows)]
+    Service,
+}
+
+/// Why the server exited, reported to supervisors as the process exit code
+///
+/// The codes follow `sysexits.h`, so a supervisor can tell a crash worth restarting
+/// apart from a mistake in the configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exit {
+    /// The server was stopped on request
+    Stopped = 0,
+    /// The server port could not be bound (`EX_UNAVAILABLE`)
+    Bind = 69,
+    /// A thread panicked or the server failed while running (`EX_SOFTWARE`)
+    Crash = 70,
+    /// The world could not be loaded (`EX_IOERR`)
+    World = 74,
+    /// A config file could not be loaded (`EX_CONFIG`)
+    Config = 78,
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        Self::from(exit as u8)
+    }
+}
+
+/// How the process exits after a server failed with `err`
+fn failure(err: &anyhow::Error) -> Exit {
+    if err.is::<BindFailed>() {
+        Exit::Bind
+    } else {
+        Exit::Crash
+    }
+}
+
+/// The layers every log event goes through before it is written
+type Subscriber =
+    Layered<LogMetadata, Layered<LogTriggers, Layered<LogCounter, Layered<EnvFilter, Registry>>>>;
+
+/// The layer writing the log, boxed so it can be rebuilt when the log settings are reloaded
+type OutputLayer = Box<dyn Layer<Subscriber> + Send + Sync>;
+
+fn init_logger(config: &SteelConfig, frontend: Frontend, log_level: Option<Level>) {
+    STEEL_LOGGER
+        .set(Arc::new(Logger))
+        .map_err(|_| ())
+        .expect("logger already initialized");
+    set_display_resolutor(&DisplayResolutor);
+
+    let env_filter = match log_level {
+        Some(level) => EnvFilter::default().add_directive(level.into()),
+        None => EnvFilter::builder()
+            .with_default_directive(Level::INFO.into())
+            .from_env_lossy(),
+    };
+
+    #[cfg(target_os = "linux")]
+    if frontend == Frontend::Headless && env::var_os("JOURNAL_STREAM").is_some() {
+        match tracing_journald::layer() {
+            Ok(journald_layer) => {
+                tracing_subscriber::registry()
+                    .with(env_filter)
+                    .with(LogCounter)
+                    .with(LogTriggers)
+                    .with(journald_layer)
+                    .init();
+                return;
+            }
+            Err(err) => eprintln!("Failed to connect to journald: {err}"),
+        }
+    }
 
-    let log = config.log.clone().unwrap_or(LogConfig {
+    // tokio-console reads the trace level instrumentation of tokio, which the log leaves out
+    #[cfg(feature = "tokio-console")]
+    let env_filter = env_filter
+        .add_directive("tokio=trace".parse().expect("valid directive"))
+        .add_directive("runtime=trace".parse().expect("valid directive"));
+
+    // the uptime keeps counting from the start when the log settings are reloaded
+    let started = Instant::now();
+    let (output, handle) = reload::Layer::new(output_layer(config.log.as_ref(), frontend, started));
+    #[cfg(feature = "tokio-console")]
+    let output = output.with_filter(filter_fn(|metadata| !is_console_instrumentation(metadata)));
+    let subscriber = tracing_subscriber::registry()
+        .with(env_filter)
+        .with(LogCounter)
+        .with(LogTriggers)
+        .with(LogMetadata)
+        .with(output);
+    #[cfg(feature = "tokio-console")]
+    let subscriber = subscriber.with(console_layer());
+    subscriber.init();
+    on_log_reload(move |log| {
+        if let Err(err) = handle.reload(output_layer(log, frontend, started)) {
+            eprintln!("Failed to apply the new log settings: {err}");
+        }
+    });
+}
+
+/// The log output for `frontend` with the settings of `log`, counting the uptime from `started`
+fn output_layer(log: Option<&LogConfig>, frontend: Frontend, started: Instant) -> OutputLayer {
+    let log = log.cloned().unwrap_or(LogConfig {
         time: LogTimeFormat::Uptime,
         module_path: false,
         extra: false,
     });
 
+    let writer = match frontend {
+        Frontend::Tui => BoxMakeWriter::new(TuiLoggerWriter),
+        Frontend::Headless => BoxMakeWriter::new(io::stdout),
+        #[cfg(windows)]
+        Frontend::Service => service::log_writer().unwrap_or_else(|err| {
+            eprintln!("Failed to open the service log: {err}");
+            BoxMakeWriter::new(io::sink)
+        }),
+    };
     let fmt_layer = fmt::layer()
+        .with_ansi(frontend == Frontend::Tui)
         .with_ansi_sanitization(false)
-        .with_writer(TuiLoggerWriter)
+        .with_writer(writer)
         .with_target(log.module_path);
 
-    set_display_resolutor(&DisplayResolutor);
     match log.time {
-        LogTimeFormat::None => {
-            tracing_subscriber::registry()
-                .with(env_filter)
-                .with(fmt_layer.without_time())
-                .init();
+        LogTimeFormat::None => fmt_layer.without_time().boxed(),
+        LogTimeFormat::Date => fmt_layer
+            .with_timer(time::ChronoUtc::new("%T:%3f".to_string()))
+            .boxed(),
+        LogTimeFormat::Uptime => fmt_layer.with_timer(Uptime::from(started)).boxed(),
+    }
+}
+
+/// Loads the server and tui configs, applying the overrides given on the command line
+fn load_configs(cli: &Cli) -> anyhow::Result<(SteelConfig, TuiConfig)> {
+    let (mut steel_config, mut tui_config) = load_server_configs(&cli.config)?;
+    // reloads compare against the file, which doesn't have the overrides
+    track_server_config(ServerLog::PRIMARY, cli.config.clone(), steel_config.clone());
+
+    if let Some(port) = cli.port {
+        steel_config.server_port = port;
+    }
+    if let Some(world) = &cli.world {
+        steel_config.world_path.clone_from(world);
+    }
+    if let Some(plugins_dir) = &cli.plugins_dir {
+        tui_config.plugins_dir.clone_from(plugins_dir);
+    }
+    Ok((steel_config, tui_config))
+}
+
+/// Loads the server config at `config` and the tui config next to it
+fn load_server_configs(config: &Path) -> anyhow::Result<(SteelConfig, TuiConfig)> {
+    let steel_config =
+        load_or_create(config).map_err(|err| anyhow!("failed to load configuration: {err}"))?;
+    let tui_config = TuiConfig::load_or_create(&config.with_file_name("tui.toml"))?;
+    Ok((steel_config, tui_config))
+}
+
+/// Checks the server config at `config` and the tui config next to it, without writing either if it is missing
+fn validate_server_configs(config: &Path) -> anyhow::Result<TuiConfig> {
+    let tui_config = config.with_file_name("tui.toml");
+    for path in [config, &tui_config] {
+        if !path.exists() {
+            bail!("{} does not exist", path.display());
+        }
+    }
+    load_or_create(config).map_err(|err| anyhow!("failed to load configuration: {err}"))?;
+    TuiConfig::load(&tui_config)
+}
+
+/// Another server managed from the same terminal ui
+///
+/// It runs on a runtime of its own, whose threads write into the log of the server.
+/// The runtime is leaked like the log, runtimes can't be dropped from async code.
+struct ManagedServer {
+    name: String,
+    config: ServerConfig,
+    log: ServerLog,
+    runtime: Handle,
+    steel_config: SteelConfig,
+    tui_config: TuiConfig,
+}
+
+/// The configs of the managed server `config` points to
+fn load_managed_configs(config: &ServerConfig) -> anyhow::Result<(SteelConfig, TuiConfig)> {
+    let (steel_config, mut tui_config) = load_server_configs(&config.config)
+        .with_context(|| format!("failed to load the configuration of {}", config.name))?;
+    // named like in the server switcher, not like in its own config
+    tui_config.name.clone_from(&config.name);
+    Ok((steel_config, tui_config))
+}
+
+impl ManagedServer {
+    fn load(config: &ServerConfig) -> anyhow::Result<Self> {
+        let (steel_config, tui_config) = load_managed_configs(config)?;
+        let log = ServerLog::create();
+        track_server_config(log, config.config.clone(), steel_config.clone());
+        let runtime = runtime_builder(
+            format!("steel-{}", config.name),
+            tui_config.runtime.main_workers,
+        )
+        .on_thread_start(move || log.attach())
+        .build()?;
+        Ok(Self {
+            name: config.name.clone(),
+            config: config.clone(),
+            log,
+            runtime: Box::leak(Box::new(runtime)).handle().clone(),
+            steel_config,
+            tui_config,
+        })
+    }
+}
+
+/// Lets the menu of a stopped server start it again on `runtime`, with its configs loaded anew by `load`
+///
+/// The restarted server runs on `restarted`, which is waited on before the process exits.
+fn restarter(
+    runtime: Handle,
+    chunk_runtime: Arc<Runtime>,
+    servers_token: CancellationToken,
+    restarted: TaskTracker,
+    load: impl Fn() -> anyhow::Result<(SteelConfig, TuiConfig)> + Send + Sync + 'static,
+) -> impl Fn() -> BoxFuture<'static, anyhow::Result<(Arc<Server>, CancellationToken)>>
++ Send
++ Sync
++ 'static {
+    let load = Arc::new(load);
+    move || {
+        let runtime = runtime.clone();
+        let chunk_runtime = chunk_runtime.clone();
+        let server_token = servers_token.child_token();
+        let restarted = restarted.clone();
+        let load = load.clone();
+        Box::pin(async move {
+            let (steel_config, tui_config) = load()?;
+            let steel_server = runtime
+                .spawn(SteelServer::new(
+                    chunk_runtime,
+                    server_token.clone(),
+                    steel_config,
+                ))
+                .await?
+                .map_err(|err| anyhow!("failed to load the world: {err:?}"))?;
+            let server = steel_server.server.clone();
+            let stopped = server_token.clone();
+            restarted.spawn_on(
+                async move {
+                    if let Err(err) =
+                        SteelApp::start_server(steel_server, tui_config, stopped).await
+                    {
+                        error!("Server failed: {err:#}");
+                    }
+                },
+                &runtime,
+            );
+            Ok((server, server_token))
+        })
+    }
+}
+
+/// Most threads the chunk runtime blocks on for world IO unless configured, the default of tokio
+const DEFAULT_IO_THREADS: usize = 512;
+
+fn half_cpus() -> usize {
+    (thread::available_parallelism().map_or(4, NonZero::get) / 2).max(2)
+}
+
+/// A multi-threaded runtime with `workers` threads, or half the cores, named `{prefix}-N` for profiles and `top -H`
+fn runtime_builder(prefix: String, workers: Option<NonZero<usize>>) -> Builder {
+    let next = AtomicUsize::new(0);
+    let mut builder = Builder::new_multi_thread();
+    builder
+        .worker_threads(workers.map_or_else(half_cpus, NonZero::get))
+        .thread_name_fn(move || format!("{prefix}-{}", next.fetch_add(1, Ordering::Relaxed)))
+        .enable_all();
+    builder
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Some(Command::ValidateConfig) => {
+            return match validate_server_configs(&cli.config).and_then(|tui_config| {
+                LogTriggers::load(&tui_config.triggers)?;
+                for server in &tui_config.servers {
+                    validate_server_configs(&server.config).with_context(|| {
+                        format!("failed to load the configuration of {}", server.name)
+                    })?;
+                }
+                Ok(())
+            }) {
+                Ok(_) => {
+                    println!("Configuration is valid");
+                    ExitCode::SUCCESS
+                }
+                Err(err) => {
+                    eprintln!("Invalid configuration: {err:#}");
+                    Exit::Config.into()
+                }
+            };
         }
-        LogTimeFormat::Date => {
-            tracing_subscriber::registry()
-                .with(env_filter)
-                .with(fmt_layer.with_timer(time::ChronoUtc::new("%T:%3f".to_string())))
-                .init();
+        Some(Command::Replay { ref file, speed }) => {
+            let result = TuiConfig::load_or_create(&cli.tui_config()).and_then(|tui_config| {
+                Builder::new_current_thread()
+                    .enable_all()
+                    .build()?
+                    .block_on(replay(file, speed, tui_config.ui))
+            });
+            return match result {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    eprintln!("Failed to replay {}: {err:#}", file.display());
+                    ExitCode::FAILURE
+                }
+            };
         }
-        LogTimeFormat::Uptime => {
-            tracing_subscriber::registry()
-                .with(env_filter)
-                .with(fmt_layer.with_timer(time::uptime()))
-                .init();
+        #[cfg(windows)]
+        Some(Command::InstallService) => {
+            return match service::install() {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    eprintln!("Failed to install the service: {err:#}");
+                    ExitCode::FAILURE
+                }
+            };
         }
+        None => (),
     }
 
-    STEEL_LOGGER
-        .set(Arc::new(Logger))
-        .map_err(|_| ())
-        .expect("logger already initialized");
+    if cli.demo {
+        let result = TuiConfig::load_or_create(&cli.tui_config()).and_then(|tui_config| {
+            Builder::new_current_thread()
+                .enable_all()
+                .build()?
+                .block_on(demo(tui_config.ui))
+        });
+        return match result {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("Demo failed: {err:#}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    #[cfg(windows)]
+    if cli.service {
+        service::start();
+        return ExitCode::SUCCESS;
+    }
+
+    // systemd connects stdout to the journal when running as a unit, there is no terminal to draw on
+    let journal = cfg!(target_os = "linux") && env::var_os("JOURNAL_STREAM").is_some();
+    // drawing into a pipe, like the console of a panel wrapping the server, would only garble it
+    let piped = !io::stdout().is_terminal();
+    let frontend = if cli.no_tui || journal || piped {
+        Frontend::Headless
+    } else {
+        Frontend::Tui
+    };
+    run(cli, CancellationToken::new(), frontend).into()
 }
 
+/// Runs the server until it stops or `token` is cancelled
 #[allow(clippy::unwrap_used)]
-fn main() {
-    let half_cpus = (thread::available_parallelism().map_or(4, NonZero::get) / 2).max(2);
+fn run(cli: Cli, token: CancellationToken, frontend: Frontend) -> Exit {
+    // the runtimes are needed before the server is loaded, a broken config is reported once it is
+    let config = TuiConfig::load_or_create(&cli.tui_config())
+        .map(|config| config.runtime)
+        .unwrap_or_default();
 
+    let io_threads = config.io_threads.map_or(DEFAULT_IO_THREADS, NonZero::get);
     let chunk_runtime = Arc::new(
-        Builder::new_multi_thread()
-            .worker_threads(half_cpus)
-            .thread_name("chunk-worker")
-            .enable_all()
+        runtime_builder("steel-chunk".to_owned(), config.chunk_workers)
+            .max_blocking_threads(io_threads)
             .build()
             .unwrap(),
     );
+    // chunks are saved and loaded on its blocking pool
+    track_io_pool(chunk_runtime.handle().clone(), io_threads);
+    pregenerate_on(chunk_runtime.handle().clone());
 
-    let main_runtime = Builder::new_multi_thread()
-        .worker_threads(half_cpus)
-        .thread_name("main-worker")
-        .enable_all()
+    let main_runtime = runtime_builder("steel-main".to_owned(), config.main_workers)
         .build()
         .unwrap();
 
-    main_runtime.block_on(main_async(chunk_runtime.clone()));
+    #[cfg(feature = "plugin")]
+    steel_tui::compile_plugins_on(chunk_runtime.handle().clone());
+
+    // a panic of the main task ends it like any other task, the process still exits with its code
+    let main_task = main_runtime.spawn(main_async(chunk_runtime.clone(), cli, token, frontend));
+    let exit = main_runtime.block_on(main_task).unwrap_or(Exit::Crash);
 
     drop(main_runtime);
     drop(chunk_runtime);
+
+    // panics in server tasks are caught by tokio and never reach the main task
+    if exit == Exit::Stopped && panicked() {
+        Exit::Crash
+    } else {
+        exit
+    }
 }
 
-async fn main_async(chunk_runtime: Arc<Runtime>) {
-    let steel_config = match load_or_create(Path::new("config/config.toml")) {
-        Ok(config) => config,
+/// How long `--bench-startup` waits for the server to listen
+const BIND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often `--bench-startup` checks whether the server listens yet
+const BIND_POLL: Duration = Duration::from_millis(5);
+
+/// Times each phase of starting the server and prints them as a table, without running it
+async fn bench_startup(chunk_runtime: Arc<Runtime>, cli: &Cli) -> Exit {
+    let mut bench = StartupBench::new();
+    #[cfg_attr(not(feature = "plugin"), allow(unused_variables))]
+    let (steel_config, tui_config) = match bench.phase("Config", || load_configs(cli)) {
+        Ok(configs) => configs,
         Err(error) => {
-            eprintln!("Failed to load configuration: {error}");
-            return;
+            eprintln!("Failed to load configuration: {error:#}");
+            return Exit::Config;
         }
     };
+    init_logger(&steel_config, Frontend::Headless, cli.log_level);
 
-    init_logger(&steel_config);
-
+    bench.phase("Registries", || LazyLock::force(&REGISTRY));
+    let port = steel_config.server_port;
     let token = CancellationToken::new();
-    let server_token = token.child_token();
-
-    let steel_server = SteelServer::new(chunk_runtime, server_token.clone(), steel_config)
+    let world = SteelServer::new(chunk_runtime, token.clone(), steel_config);
+    let mut steel_server = match bench.phase_async("World load", world).await {
+        Ok(steel_server) => steel_server,
+        Err(err) => {
+            eprintln!("Failed to load the world: {err:?}");
+            return Exit::World;
+        }
+    };
+    #[cfg(feature = "plugin")]
+    if let Err(err) = bench
+        .phase_async(
+            "Plugins",
+            load_plugins(tui_config.plugins_dir, tui_config.plugin_limits),
+        )
         .await
-        .expect("failed to create steel server");
+    {
+        eprintln!("Failed to load the plugins: {err:#}");
+    }
+    // the listener is bound as the server starts, it counts as bound once it accepts connections
+    let tracker = TaskTracker::new();
+    let mut serving = tokio::spawn(async move { steel_server.start(tracker).await });
+    let bound = bench
+        .phase_async(
+            "Network bind",
+            timeout(BIND_TIMEOUT, async {
+                loop {
+                    select! {
+                        // it stops right away if binding failed
+                        started = &mut serving => return Some(match started {
+                            Ok(Err(err)) => err.to_string(),
+                            Ok(Ok(())) => "the server stopped before listening".to_owned(),
+                            Err(_) => "the server panicked".to_owned(),
+                        }),
+                        connected = TcpStream::connect((Ipv4Addr::LOCALHOST, port)) => {
+                            if connected.is_ok() {
+                                return None;
+                            }
+                        }
+                    }
+                    sleep(BIND_POLL).await;
+                }
+            }),
+        )
+        .await;
+    token.cancel();
+    match bound {
+        Ok(None) => {
+            let _ = serving.await;
+        }
+        Ok(Some(err)) => {
+            eprintln!("Failed to bind port {port}: {err}");
+            return Exit::Bind;
+        }
+        Err(_) => {
+            eprintln!("The server didn't listen on port {port} within {BIND_TIMEOUT:?}");
+            return Exit::Bind;
+        }
+    }
 
-    let mut steel_app = SteelApp::new(steel_server.server.clone(), token.clone(), server_token);
-    let app_handle = tokio::spawn(async move {
-        steel_app.run().await.expect("error while running server");
-    });
+    for line in bench.table() {
+        println!("{line}");
+    }
+    Exit::Stopped
+}
 
-    SteelApp::start_server(steel_server)
-        .await
-        .expect("failed to start server");
+async fn main_async(
+    chunk_runtime: Arc<Runtime>,
+    cli: Cli,
+    token: CancellationToken,
+    frontend: Frontend,
+) -> Exit {
+    if cli.bench_startup {
+        return bench_startup(chunk_runtime, &cli).await;
+    }
+
+    let (steel_config, tui_config) = match load_configs(&cli) {
+        Ok(configs) => configs,
+        Err(error) => {
+            eprintln!("Failed to load configuration: {error:#}");
+            return Exit::Config;
+        }
+    };
+
+    if let Some(record) = &cli.record
+        && let Err(err) = start_recording(record)
+    {
+        eprintln!("Failed to start recording into {}: {err}", record.display());
+        return Exit::Config;
+    }
+
+    if let Err(error) = LogTriggers::load(&tui_config.triggers) {
+        eprintln!("Failed to load configuration: {error:#}");
+        return Exit::Config;
+    }
+
+    let mut managed = Vec::new();
+    for server in &tui_config.servers {
+        match ManagedServer::load(server) {
+            Ok(server) => managed.push(server),
+            Err(error) => {
+                eprintln!("Failed to load configuration: {error:#}");
+                return Exit::Config;
+            }
+        }
+    }
+
+    init_logger(&steel_config, frontend, cli.log_level);
+    install_panic_hook();
+
+    // cancelled on SIGTERM to stop every server, each one can also be stopped on its own
+    let servers_token = token.child_token();
+    let server_token = servers_token.child_token();
+
+    let steel_server =
+        match SteelServer::new(chunk_runtime.clone(), server_token.clone(), steel_config).await {
+            Ok(steel_server) => steel_server,
+            Err(err) => {
+                eprintln!("Failed to load the world: {err:?}");
+                return Exit::World;
+            }
+        };
+
+    // the other servers are created on their own runtimes, sharing the chunk runtime
+    let mut managed_servers = Vec::new();
+    for server in managed {
+        let server_token = servers_token.child_token();
+        let steel_server = server.runtime.spawn(SteelServer::new(
+            chunk_runtime.clone(),
+            server_token.clone(),
+            server.steel_config.clone(),
+        ));
+        match steel_server.await {
+            Ok(Ok(steel_server)) => managed_servers.push((server, steel_server, server_token)),
+            Ok(Err(err)) => {
+                eprintln!("Failed to load the world of {}: {err:?}", server.name);
+                return Exit::World;
+            }
+            Err(_) => return Exit::Crash,
+        }
+    }
+
+    // listened for from here on, the servers are only stopped once they are running
+    let mut signals = Signals::listen();
+    if frontend == Frontend::Headless {
+        read_commands(steel_server.server.clone());
+    }
+
+    let restarted = TaskTracker::new();
+    let app_handle = if frontend != Frontend::Tui {
+        None
+    } else {
+        let primary_cli = cli.clone();
+        let mut builder = SteelApp::builder(steel_server.server.clone())
+            .name(tui_config.name.clone())
+            .ui(tui_config.ui.clone())
+            .token(token.clone())
+            .server_token(server_token.clone())
+            .restart(
+                ServerLog::PRIMARY,
+                restarter(
+                    Handle::current(),
+                    chunk_runtime.clone(),
+                    servers_token.clone(),
+                    restarted.clone(),
+                    // tracks the config read again in place of the one the stopped server was loaded with
+                    move || load_configs(&primary_cli),
+                ),
+            );
+        for (server, steel_server, server_token) in &managed_servers {
+            let (config, log) = (server.config.clone(), server.log);
+            builder = builder
+                .server(
+                    server.name.clone(),
+                    steel_server.server.clone(),
+                    server_token.clone(),
+                    server.log,
+                )
+                .restart(
+                    server.log,
+                    restarter(
+                        server.runtime.clone(),
+                        chunk_runtime.clone(),
+                        servers_token.clone(),
+                        restarted.clone(),
+                        move || {
+                            let configs = load_managed_configs(&config)?;
+                            track_server_config(log, config.config.clone(), configs.0.clone());
+                            Ok(configs)
+                        },
+                    ),
+                );
+        }
+        let mut steel_app = match builder.build() {
+            Ok(steel_app) => steel_app,
+            Err(err) => {
+                eprintln!("Failed to start the terminal ui: {err:#}");
+                return Exit::Config;
+            }
+        };
+        Some(tokio::spawn(async move { steel_app.run().await }))
+    };
+
+    let handles: Vec<_> = managed_servers
+        .into_iter()
+        .map(|(server, steel_server, server_token)| {
+            let handle = server.runtime.spawn(SteelApp::start_server(
+                steel_server,
+                server.tui_config,
+                server_token,
+            ));
+            (server.name, handle)
+        })
+        .collect();
+
+    let served = async move {
+        let mut exit = match SteelApp::start_server(steel_server, tui_config, server_token).await {
+            Ok(()) => Exit::Stopped,
+            Err(err) => {
+                error!("Server failed: {err:#}");
+                failure(&err)
+            }
+        };
 
-    app_handle.await.expect("error while awaiting app");
+        for (name, handle) in handles {
+            match handle.await {
+                Ok(Ok(())) => (),
+                Ok(Err(err)) => {
+                    error!("Server {name} failed: {err:#}");
+                    exit = failure(&err);
+                }
+                Err(_) => exit = Exit::Crash,
+            }
+        }
+
+        if let Some(app_handle) = app_handle {
+            match app_handle.await {
+                Ok(Ok(())) => (),
+                Ok(Err(err)) => {
+                    eprintln!("Terminal ui failed: {err:#}");
+                    exit = Exit::Crash;
+                }
+                Err(_) => exit = Exit::Crash,
+            }
+            // servers started again from their menu stop along with the terminal ui
+            restarted.close();
+            restarted.wait().await;
+            // the log was only shown inside the terminal ui, which is gone now
+            for line in session_summary() {
+                println!("{line}");
+            }
+        }
+        exit
+    };
+    pin!(served);
+
+    // a stop goes through the same shutdown as the stop command, which saves the worlds and players
+    let tui_config_path = cli.tui_config();
+    let mut stopping = false;
+    loop {
+        select! {
+            exit = &mut served => {
+                #[cfg(target_os = "linux")]
+                if !stopping {
+                    systemd_stopping();
+                }
+                return exit;
+            }
+            // only the process shutting down stops the unit, not a single server stopping
+            () = token.cancelled(), if !stopping => {
+                stopping = true;
+                #[cfg(target_os = "linux")]
+                systemd_stopping();
+            }
+            signal = signals.recv() => match signal {
+                Signal::Stop => token.cancel(),
+                Signal::Reload => reload_configs(&tui_config_path),
+            },
+        }
+    }
 }
@@ -0,0 +1,120 @@
+use crate::config::MaintenanceConfig;
+use crate::confirmation;
+use crate::logger::ServerLog;
+use anyhow::bail;
+use std::sync::LazyLock;
+use steel_core::server::Server;
+use steel_utils::locks::SyncMutex;
+
+const USAGE: &str = "usage: maintenance [on | off]";
+
+/// The maintenance mode of one server, by the log of the server
+struct Maintenance {
+    log: ServerLog,
+    config: MaintenanceConfig,
+    /// Whether the whitelist was enabled before maintenance, to restore it afterwards
+    active: Option<bool>,
+}
+
+static SERVERS: LazyLock<SyncMutex<Vec<Maintenance>>> =
+    LazyLock::new(|| SyncMutex::new(Vec::new()));
+
+/// Sets up maintenance mode for the server the current thread logs into
+pub fn configure(config: MaintenanceConfig) {
+    let log = ServerLog::current();
+    let mut servers = SERVERS.lock();
+    servers.retain(|maintenance| maintenance.log != log);
+    servers.push(Maintenance {
+        log,
+        config,
+        active: None,
+    });
+}
+
+/// Whether the server logging into `log` is in maintenance mode
+pub fn is_active(log: ServerLog) -> bool {
+    SERVERS
+        .lock()
+        .iter()
+        .any(|maintenance| maintenance.log == log && maintenance.active.is_some())
+}
+
+/// Runs the `maintenance` console command, returning its output
+///
+/// `maintenance` toggles maintenance mode, `maintenance on` and `maintenance off` set it. While it is on, the
+/// whitelist is enabled and players that are not on it are kicked.
+pub fn command(server: &Server, args: &str) -> anyhow::Result<Vec<String>> {
+    let log = ServerLog::current();
+    let enable = match args {
+        "" => !is_active(log),
+        "on" => true,
+        "off" => false,
+        _ => bail!(USAGE),
+    };
+    // asked before locking, the answer may take a while
+    let unlisted = unlisted(server);
+    if enable
+        && !is_active(log)
+        && unlisted > 0
+        && !confirmation::confirm(format!(
+            "This will kick {unlisted} players not on the whitelist, continue?"
+        ))
+    {
+        return Ok(vec!["Maintenance mode stays off".to_owned()]);
+    }
+
+    let mut servers = SERVERS.lock();
+    let Some(maintenance) = servers
+        .iter_mut()
+        .find(|maintenance| maintenance.log == log)
+    else {
+        bail!("the server has not started yet");
+    };
+
+    match (enable, maintenance.active) {
+        (true, None) => {
+            maintenance.active = Some(server.whitelist.is_enabled());
+            server.whitelist.set_enabled(true);
+            let kicked = kick_unlisted(server, &maintenance.config.kick_message);
+            Ok(vec![format!(
+                "Maintenance mode is on, kicked {kicked} players not on the whitelist"
+            )])
+        }
+        (false, Some(whitelist)) => {
+            maintenance.active = None;
+            server.whitelist.set_enabled(whitelist);
+            Ok(vec!["Maintenance mode is off".to_owned()])
+        }
+        (true, Some(_)) => Ok(vec!["Maintenance mode is already on".to_owned()]),
+        (false, None) => Ok(vec!["Maintenance mode is already off".to_owned()]),
+    }
+}
+
+/// Number of players that are not on the whitelist
+fn unlisted(server: &Server) -> usize {
+    let mut unlisted = 0;
+    for world in server.worlds.values() {
+        world.players.iter_players(|_, player| {
+            if !server.whitelist.contains(&player.gameprofile.id) {
+                unlisted += 1;
+            }
+            true
+        });
+    }
+    unlisted
+}
+
+/// Kicks every player that is not on the whitelist, returning how many were kicked
+fn kick_unlisted(server: &Server, message: &str) -> usize {
+    let mut kicked = 0;
+    for world in server.worlds.values() {
+        world.players.iter_players(|_, player| {
+            if !server.whitelist.contains(&player.gameprofile.id) {
+                player.kick(message);
+                kicked += 1;
+            }
+            true
+        });
+    }
+    kicked
+}
@@ -0,0 +1,68 @@
+use crate::logger::LogCounter;
+use crate::status;
+use std::fmt::Write;
+use steel_core::server::Server;
+
+struct Exposition(String);
+
+impl Exposition {
+    fn metric(&mut self, name: &str, kind: &str, help: &str) -> &mut Self {
+        let _ = writeln!(self.0, "# HELP {name} {help}");
+        let _ = writeln!(self.0, "# TYPE {name} {kind}");
+        self
+    }
+
+    fn sample(&mut self, name: &str, labels: &str, value: impl Into<f64>) -> &mut Self {
+        let value = value.into();
+        if labels.is_empty() {
+            let _ = writeln!(self.0, "{name} {value}");
+        } else {
+            let _ = writeln!(self.0, "{name}{{{labels}}} {value}");
+        }
+        self
+    }
+}
+
+/// Renders the server statistics in the Prometheus text exposition format
+pub fn render(server: &Server) -> String {
+    let mut out = Exposition(String::new());
+
+    let ticks = status::ticks(server);
+    out.metric("steel_tps", "gauge", "Ticks per second")
+        .sample("steel_tps", "", ticks.tps);
+    out.metric("steel_tick_time_ms", "gauge", "Average time spent per tick")
+        .sample("steel_tick_time_ms", "", ticks.mspt);
+
+    out.metric("steel_players", "gauge", "Connected players")
+        .sample("steel_players", "", status::players(server).len() as f64);
+
+    out.metric("steel_loaded_chunks", "gauge", "Chunks loaded per world");
+    for world in status::worlds(server) {
+        let labels = format!("world=\"{}\"", world.name);
+        out.sample("steel_loaded_chunks", &labels, world.loaded_chunks as f64);
+    }
+
+    if let Some(resident_bytes) = status::memory().resident_bytes {
+        out.metric(
+            "steel_memory_resident_bytes",
+            "gauge",
+            "Resident memory of the server process",
+        )
+        .sample("steel_memory_resident_bytes", "", resident_bytes as f64);
+    }
+
+    let (errors, warnings) = LogCounter::counts();
+    out.metric(
+        "steel_log_messages_total",
+        "counter",
+        "Logged messages per level",
+    )
+    .sample("steel_log_messages_total", "level=\"error\"", errors as f64)
+    .sample(
+        "steel_log_messages_total",
+        "level=\"warn\"",
+        warnings as f64,
+    );
+
+    out.0
+}
@@ -0,0 +1,204 @@
+use crate::command::{self, CommandOrigin};
+use crate::inspect;
+use crate::widgets::TreeNode;
+use anyhow::{Context, bail};
+use ratatui::style::{Color, Stylize};
+use ratatui::text::{Line, Span};
+use steel_utils::nbt::{NbtCompound, NbtTag};
+
+/// Length from which SNBT in the output of a console command opens in the viewer instead of being logged
+const LONG_SNBT: usize = 120;
+
+/// A node for the compound `nbt` called `name`, with a node for each of its tags
+pub fn compound(name: &str, nbt: &NbtCompound) -> TreeNode {
+    let children: Vec<TreeNode> = nbt.iter().map(|(name, tag)| tag_node(name, tag)).collect();
+    TreeNode::branch(branch_label(name, '{', children.len()), children)
+}
+
+/// Turns an NBT tag into a node, nesting compounds and lists below it
+fn tag_node(name: &str, tag: &NbtTag) -> TreeNode {
+    match tag {
+        NbtTag::Compound(nbt) => compound(name, nbt),
+        NbtTag::List(tags) => TreeNode::branch(
+            branch_label(name, '[', tags.len()),
+            tags.iter()
+                .enumerate()
+                .map(|(index, tag)| tag_node(&index.to_string(), tag))
+                .collect(),
+        ),
+        tag => TreeNode::leaf(leaf_label(name, &tag.to_string())),
+    }
+}
+
+/// The label of a compound or list, like `Items [3]`
+fn branch_label(name: &str, open: char, len: usize) -> Line<'static> {
+    let close = if open == '{' { '}' } else { ']' };
+    Line::from(vec![
+        Span::raw(name.to_owned()).cyan(),
+        Span::raw(format!(" {open}{len}{close}")).dark_gray(),
+    ])
+}
+
+/// The label of a value, colored like SNBT is by its type
+fn leaf_label(name: &str, value: &str) -> Line<'static> {
+    let color = match value.chars().next() {
+        Some('"' | '\'') => Color::Green,
+        Some('0'..='9' | '-' | '.') => Color::Yellow,
+        Some(_) if value == "true" || value == "false" => Color::Magenta,
+        _ => Color::Reset,
+    };
+    Line::from(vec![
+        Span::raw(name.to_owned()).cyan(),
+        Span::raw(": ").dark_gray(),
+        Span::raw(value.to_owned()).fg(color),
+    ])
+}
+
+/// Parses SNBT text, like the output of `data get`, into a node called `name`
+pub fn parse(name: &str, snbt: &str) -> anyhow::Result<TreeNode> {
+    let mut parser = Parser { text: snbt, pos: 0 };
+    let node = parser.value(name)?;
+    parser.skip_whitespace();
+    if parser.pos < snbt.len() {
+        bail!("unexpected text after the SNBT at {}", parser.pos);
+    }
+    Ok(node)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn expect(&mut self, expected: char) -> anyhow::Result<()> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            bail!("expected `{expected}` at {}", self.pos);
+        }
+        self.pos += expected.len_utf8();
+        Ok(())
+    }
+
+    /// A quoted string including its quotes, or a bare word
+    fn token(&mut self) -> anyhow::Result<&str> {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut escaped = false;
+                let end = self.text[start + 1..]
+                    .char_indices()
+                    .find(|(_, char)| {
+                        let end = !escaped && *char == quote;
+                        escaped = !escaped && *char == '\\';
+                        end
+                    })
+                    .map(|(index, _)| start + 1 + index + 1)
+                    .context("unterminated string")?;
+                self.pos = end;
+            }
+            _ => {
+                let rest = &self.text[start..];
+                self.pos += rest
+                    .find(|char: char| {
+                        matches!(char, ',' | ':' | '}' | ']' | ';') || char.is_whitespace()
+                    })
+                    .unwrap_or(rest.len());
+            }
+        }
+        if self.pos == start {
+            bail!("expected a value at {start}");
+        }
+        Ok(&self.text[start..self.pos])
+    }
+
+    fn value(&mut self, name: &str) -> anyhow::Result<TreeNode> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                let mut children = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() == Some('}') {
+                        self.pos += 1;
+                        break;
+                    }
+                    let key = self.token()?.trim_matches(['"', '\'']).to_owned();
+                    self.expect(':')?;
+                    children.push(self.value(&key)?);
+                    self.skip_whitespace();
+                    if self.peek() == Some(',') {
+                        self.pos += 1;
+                    }
+                }
+                Ok(TreeNode::branch(
+                    branch_label(name, '{', children.len()),
+                    children,
+                ))
+            }
+            Some('[') => {
+                self.pos += 1;
+                // typed arrays start with their type, like `[I; 1, 2, 3]`
+                let rest = self.text[self.pos..].trim_start();
+                if rest.len() >= 2
+                    && rest.as_bytes()[1] == b';'
+                    && matches!(rest.as_bytes()[0], b'B' | b'I' | b'L')
+                {
+                    self.skip_whitespace();
+                    self.pos += 2;
+                }
+                let mut children = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() == Some(']') {
+                        self.pos += 1;
+                        break;
+                    }
+                    children.push(self.value(&children.len().to_string())?);
+                    self.skip_whitespace();
+                    if self.peek() == Some(',') {
+                        self.pos += 1;
+                    }
+                }
+                Ok(TreeNode::branch(
+                    branch_label(name, '[', children.len()),
+                    children,
+                ))
+            }
+            _ => {
+                let value = self.token()?.to_owned();
+                Ok(TreeNode::leaf(leaf_label(name, &value)))
+            }
+        }
+    }
+}
+
+/// Opens long SNBT in a console message in the viewer, returning the message to log in its place
+///
+/// Only messages answering a command typed into the console are opened, like `data get entity Steve`, other
+/// interfaces can't show the viewer.
+pub fn open_long(message: &str) -> Option<String> {
+    if command::origin() != Some(CommandOrigin::Console) {
+        return None;
+    }
+    let start = message.find('{')?;
+    let (prefix, snbt) = message.split_at(start);
+    if snbt.len() < LONG_SNBT {
+        return None;
+    }
+    let title = prefix.trim().trim_end_matches(':');
+    let node = parse("data", snbt.trim_end()).ok()?;
+    inspect::open(title.to_owned(), vec![node.expanded()]);
+    Some(format!("{prefix}{{…}} (opened in the NBT viewer)"))
+}
@@ -0,0 +1,129 @@
+use crate::logger::ServerLog;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use steel_utils::locks::SyncMutex;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+
+static BUS: LazyLock<broadcast::Sender<Notification>> = LazyLock::new(|| broadcast::channel(64).0);
+
+/// The names of the servers, by the log of each one
+static NAMES: LazyLock<SyncMutex<HashMap<ServerLog, String>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// The server finished starting
+    ServerStarted,
+    /// The server stopped
+    ServerStopped,
+    /// A player joined the server
+    PlayerJoined,
+    /// A player left the server
+    PlayerLeft,
+    /// A thread of the server panicked
+    Crash,
+    /// The ticks per second dropped below the configured threshold
+    LowTps,
+    /// Ticks took longer than the thresholds of the watchdog
+    SlowTicks,
+    /// A log trigger matched a logged message
+    Trigger,
+    /// A plugin was reloaded after its file changed, or failed to
+    PluginReloaded,
+    /// A plugin trapped or ran over its limits and was disabled
+    PluginCrashed,
+    /// A save of the worlds and players finished or failed
+    SaveFinished,
+    /// Something about the terminal ui itself, only shown as a toast and never sent on the bus
+    Ui,
+}
+
+/// How urgent a notification is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Something worth knowing about
+    Info,
+    /// Something that may need attention
+    Warning,
+    /// Something that went wrong
+    Error,
+}
+
+/// A message shown as a toast in the terminal ui and forwarded to every other subscriber
+#[derive(Debug, Clone)]
+pub struct Notification {
+    /// What the notification is about
+    pub kind: NotificationKind,
+    /// How urgent the notification is
+    pub severity: Severity,
+    /// The text of the notification
+    pub message: String,
+    /// The log of the server the notification is about
+    pub server: ServerLog,
+}
+
+impl Notification {
+    /// The text of the notification, naming its server when there are several
+    pub fn text(&self) -> String {
+        let names = NAMES.lock();
+        match names.get(&self.server) {
+            Some(name) if names.len() > 1 => format!("{name}: {}", self.message),
+            _ => self.message.clone(),
+        }
+    }
+}
+
+/// Names the server logging into `log` in the notifications about it
+pub fn name_server(log: ServerLog, name: &str) {
+    NAMES.lock().insert(log, name.to_owned());
+}
+
+/// The name of the server logging into `log`, once it started
+pub fn server_name(log: ServerLog) -> Option<String> {
+    NAMES.lock().get(&log).cloned()
+}
+
+/// Sends a notification about the current server to everyone subscribed to the bus
+pub fn send(kind: NotificationKind, severity: Severity, message: impl Into<String>) {
+    // nobody is listening if this fails, which is fine
+    let _ = BUS.send(Notification {
+        kind,
+        severity,
+        message: message.into(),
+        server: ServerLog::current(),
+    });
+}
+
+/// Subscribes to every notification sent from now on
+pub fn subscribe() -> broadcast::Receiver<Notification> {
+    BUS.subscribe()
+}
+
+/// Whether a subscriber configured with `events` wants notifications of `kind`, every kind is wanted if `events` is empty
+pub fn wanted(events: &[NotificationKind], kind: NotificationKind) -> bool {
+    events.is_empty() || events.contains(&kind)
+}
+
+/// Waits for the next notification on `bus`, skipping over the ones missed while lagging behind
+///
+/// Returns `None` once the bus is closed. `subscriber` names who missed notifications in the log.
+pub async fn next(
+    bus: &mut broadcast::Receiver<Notification>,
+    subscriber: &str,
+) -> Option<Notification> {
+    loop {
+        match bus.recv().await {
+            Ok(notification) => return Some(notification),
+            Err(RecvError::Lagged(skipped)) => {
+                debug!("{subscriber} skipped {skipped} notifications");
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
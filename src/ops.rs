@@ -0,0 +1,134 @@
+use crate::status;
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use steel_core::server::Server;
+use uuid::Uuid;
+
+const USAGE: &str = "usage: perm [player [level]]";
+
+/// The file the permission levels of players are persisted in, like a vanilla server
+const OPS_FILE: &str = "ops.json";
+
+/// The permission levels commands check, each one allowing everything the levels below it allow
+pub const LEVELS: [(&str, &str); 5] = [
+    ("everyone", "commands every player may use"),
+    ("moderator", "bypasses the spawn protection"),
+    ("gamemaster", "cheat commands and command blocks"),
+    ("admin", "kicking, banning and opping players"),
+    ("owner", "stopping and saving the server"),
+];
+
+/// A player with a permission level above everyone, as written into `ops.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Op {
+    pub uuid: Uuid,
+    pub name: String,
+    pub level: u8,
+    #[serde(default)]
+    pub bypasses_player_limit: bool,
+}
+
+/// The name of the permission level `level`
+pub fn level_name(level: u8) -> &'static str {
+    LEVELS
+        .get(usize::from(level))
+        .map_or("unknown", |(name, _)| *name)
+}
+
+/// The levels `level` inherits from, highest first, like `gamemaster, moderator, everyone` for an admin
+pub fn inherited(level: u8) -> Vec<&'static str> {
+    (0..level).rev().map(level_name).collect()
+}
+
+/// The players with a permission level, as persisted
+pub fn load() -> anyhow::Result<Vec<Op>> {
+    match fs::read_to_string(OPS_FILE) {
+        Ok(content) => serde_json::from_str(&content).context("failed to parse ops.json"),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err).context("failed to read ops.json"),
+    }
+}
+
+fn save(ops: &[Op]) -> anyhow::Result<()> {
+    let content = serde_json::to_string_pretty(ops).context("failed to serialize ops.json")?;
+    fs::write(OPS_FILE, content).context("failed to write ops.json")
+}
+
+/// Sets the permission level of the player `name` to `level`, persisting it and applying it if they are online
+fn set_level(server: &Server, name: &str, level: u8) -> anyhow::Result<()> {
+    if usize::from(level) >= LEVELS.len() {
+        bail!("the level has to be between 0 and {}", LEVELS.len() - 1);
+    }
+    let mut ops = load()?;
+    let online = status::players(server)
+        .into_iter()
+        .find(|player| player.name.eq_ignore_ascii_case(name));
+    let uuid = match (
+        &online,
+        ops.iter().find(|op| op.name.eq_ignore_ascii_case(name)),
+    ) {
+        (Some(player), _) => player.uuid,
+        (None, Some(op)) => op.uuid,
+        (None, None) => bail!("{name} is neither online nor has a permission level"),
+    };
+
+    ops.retain(|op| op.uuid != uuid);
+    if level > 0 {
+        ops.push(Op {
+            uuid,
+            name: online.map_or_else(|| name.to_owned(), |player| player.name),
+            level,
+            bypasses_player_limit: false,
+        });
+    }
+    save(&ops)?;
+
+    for world in server.worlds.values() {
+        world.players.iter_players(|_, player| {
+            if player.gameprofile.id == uuid {
+                player.set_permission_level(level);
+            }
+            true
+        });
+    }
+    Ok(())
+}
+
+/// Runs the `perm` console command, returning its output
+///
+/// `perm` lists the players with a permission level, `perm <player>` shows the level of one with what it inherits
+/// and `perm <player> <level>` changes it.
+pub fn command(server: &Server, args: &str) -> anyhow::Result<Vec<String>> {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    match words.as_slice() {
+        [] => Ok(load()?
+            .into_iter()
+            .map(|op| format!("{}: {} ({})", op.name, op.level, level_name(op.level)))
+            .collect()),
+        [name] => {
+            let level = load()?
+                .into_iter()
+                .find(|op| op.name.eq_ignore_ascii_case(name))
+                .map_or(0, |op| op.level);
+            let mut output = vec![format!("{name}: {level} ({})", level_name(level))];
+            if level > 0 {
+                output.push(format!("Inherits {}", inherited(level).join(", ")));
+            }
+            Ok(output)
+        }
+        [name, level] => {
+            let level: u8 = level
+                .parse()
+                .with_context(|| format!("`{level}` is not a permission level"))?;
+            set_level(server, name, level)?;
+            Ok(vec![format!(
+                "Set the permission level of {name} to {level} ({})",
+                level_name(level)
+            )])
+        }
+        _ => bail!(USAGE),
+    }
+}
@@ -0,0 +1,80 @@
+use crate::afk;
+use crate::config::Theme;
+use crate::logger::ServerLog;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Row, Table, TableState};
+use std::time::Duration;
+
+/// How much Left/Right change the auto kick timeout by
+const KICK_AFTER_STEP: Duration = Duration::from_secs(60);
+
+/// Formats `duration` in minutes and seconds, like `12m 05s`
+fn minutes(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}m {:02}s", secs / 60, secs % 60)
+}
+
+/// The players of a server that have been idle for a while, with the auto kick settings
+#[derive(Default)]
+pub struct AfkView {
+    table_state: TableState,
+}
+
+impl AfkView {
+    /// Handles a key pressed while the idle tab is shown, returning whether it was used
+    pub fn handle_key(&mut self, log: ServerLog, event: KeyEvent) -> bool {
+        match event.code {
+            KeyCode::Up => self.table_state.select_previous(),
+            KeyCode::Down => self.table_state.select_next(),
+            KeyCode::Char('k') => afk::toggle_auto_kick(log),
+            KeyCode::Left => afk::adjust_kick_after(log, KICK_AFTER_STEP, false),
+            KeyCode::Right => afk::adjust_kick_after(log, KICK_AFTER_STEP, true),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Renders the idle players of the server logging into `log`
+    pub fn render(&mut self, log: ServerLog, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let players = afk::idle_players(log);
+        let auto_kick = match afk::auto_kick(log) {
+            Some((true, after)) => format!("auto kick after {}", minutes(after)),
+            Some((false, _)) => "auto kick off".to_owned(),
+            None => String::new(),
+        };
+
+        let header =
+            Row::new(["Name", "UUID", "Idle for"]).style(Style::new().fg(theme.accent).bold());
+        let rows = players.iter().map(|player| {
+            Row::new([
+                player.name.clone(),
+                player.uuid.to_string(),
+                minutes(player.idle),
+            ])
+        });
+        StatefulWidget::render(
+            Table::new(
+                rows,
+                [
+                    Constraint::Length(17),
+                    Constraint::Length(37),
+                    Constraint::Fill(1),
+                ],
+            )
+            .header(header)
+            .row_highlight_style(Style::new().reversed())
+            .block(
+                Block::new()
+                    .title(format!("{} players idle", players.len()))
+                    .title(Line::from(auto_kick.fg(theme.muted)).right_aligned())
+                    .title_bottom(
+                        "k toggles the auto kick, Left/Right change its timeout".fg(theme.muted),
+                    ),
+            ),
+            area,
+            buf,
+            &mut self.table_state,
+        );
+    }
+}
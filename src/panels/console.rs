@@ -0,0 +1,488 @@
+use crate::audit;
+use crate::clipboard;
+use crate::logger::{LineMetadata, ServerLog};
+use ratatui::crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{Level, warn};
+use tui_scrollview::{ScrollView, ScrollViewState, ScrollbarVisibility};
+
+/// Longest time between the clicks of a double or triple click
+const MULTI_CLICK: Duration = Duration::from_millis(400);
+
+/// Most lines pinned at once, pinning another one unpins the oldest
+const MAX_PINNED: usize = 5;
+
+/// Characters that end a word selected with a double click, besides whitespace
+const WORD_BOUNDARIES: &[char] = &['[', ']', '(', ')', '<', '>', ',', ';', '"', '\''];
+
+/// Part of a line of the log selected with the mouse, by line index and character range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Selection {
+    line: usize,
+    start: usize,
+    end: usize,
+}
+
+/// The badge shown before the lines logged at `level`
+fn badge(level: Level) -> Span<'static> {
+    match level {
+        Level::ERROR => "E".red(),
+        Level::WARN => "W".yellow(),
+        Level::INFO => "I".green(),
+        Level::DEBUG => "D".blue(),
+        _ => "T".magenta(),
+    }
+    .bold()
+}
+
+/// The range of the word around `column` in `line`, if there is one
+fn word_at(line: &str, column: usize) -> Option<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let is_word = |c: &char| !c.is_whitespace() && !WORD_BOUNDARIES.contains(c);
+    if !chars.get(column).is_some_and(is_word) {
+        return None;
+    }
+    let start = chars[..column]
+        .iter()
+        .rposition(|c| !is_word(c))
+        .map_or(0, |index| index + 1);
+    let end = chars[column..]
+        .iter()
+        .position(|c| !is_word(c))
+        .map_or(chars.len(), |index| column + index);
+    Some((start, end))
+}
+
+/// Scroll state of the view of a server log
+pub struct Console {
+    log: ServerLog,
+    filter: Option<String>,
+    scroll_view_state: ScrollViewState,
+    scroll_bottom: bool,
+    /// Whether the terminal was resized since the last render
+    resized: bool,
+    /// Where the log was last drawn and how many lines it had
+    area: Rect,
+    lines: u16,
+    /// Whether the thumb of the scrollbar is being dragged
+    dragging: bool,
+    /// When and where the log was last clicked, with how many clicks in a row that was
+    last_click: Option<(Instant, Position, u8)>,
+    selection: Option<Selection>,
+    /// Lines trimmed from the log as of the last render
+    trimmed: usize,
+    /// Whether messages spanning several lines are folded to their first line
+    folded: bool,
+    /// The only level lines are shown of, picked by clicking its badge
+    level: Option<Level>,
+    /// Where the level badges were last drawn
+    gutter: Rect,
+    /// Lines kept above the log while scrolling, by their index counting the lines trimmed from the start
+    pinned: Vec<(usize, Line<'static>)>,
+    /// Where the pinned lines were last drawn
+    pinned_area: Rect,
+    /// The index into the log of every line shown, unless every line is shown as it is
+    rows: Option<Vec<usize>>,
+}
+
+impl Console {
+    pub const fn new(log: ServerLog) -> Self {
+        Self {
+            log,
+            filter: None,
+            scroll_view_state: ScrollViewState::new(),
+            scroll_bottom: true,
+            resized: false,
+            area: Rect::ZERO,
+            lines: 0,
+            dragging: false,
+            last_click: None,
+            selection: None,
+            trimmed: 0,
+            folded: false,
+            level: None,
+            gutter: Rect::ZERO,
+            pinned: Vec::new(),
+            pinned_area: Rect::ZERO,
+            rows: None,
+        }
+    }
+
+    /// The log this console shows
+    pub const fn log(&self) -> ServerLog {
+        self.log
+    }
+
+    /// The plugin whose output is shown exclusively, or the audit log source for executed commands
+    pub fn filter(&self) -> Option<&str> {
+        self.filter.as_deref()
+    }
+
+    /// Shows only the output of the plugin `filter`, or everything if it is `None`
+    pub fn set_filter(&mut self, filter: Option<String>) {
+        self.filter = filter;
+        self.scroll_bottom = true;
+    }
+
+    /// Folds messages spanning several lines to their first line, or unfolds them again
+    pub fn toggle_folded(&mut self) {
+        self.folded = !self.folded;
+        self.selection = None;
+        self.scroll_bottom = true;
+    }
+
+    /// Pins the selected line above the log, or unpins it if it is pinned already
+    pub fn toggle_pin(&mut self) {
+        let Some(index) = self
+            .selection
+            .and_then(|selection| self.index(selection.line))
+        else {
+            return;
+        };
+        let lock = self.log.history().lock();
+        let Some(line) = lock.text.lines.get(index) else {
+            return;
+        };
+        let id = lock.trimmed() + index;
+        if let Some(at) = self.pinned.iter().position(|(pinned, _)| *pinned == id) {
+            self.pinned.remove(at);
+        } else {
+            if self.pinned.len() == MAX_PINNED {
+                self.pinned.remove(0);
+            }
+            self.pinned.push((id, line.clone()));
+        }
+    }
+
+    /// Shows only the lines logged at the level of the line shown at `row`, or every line again if only one level is
+    /// shown already
+    fn toggle_level(&mut self, row: usize) {
+        if self.level.is_some() {
+            self.level = None;
+        } else {
+            let metadata = self
+                .index(row)
+                .and_then(|index| self.log.history().lock().metadata(index));
+            let Some(metadata) = metadata else {
+                return;
+            };
+            self.level = Some(metadata.level);
+        }
+        self.selection = None;
+        self.scroll_bottom = true;
+    }
+
+    /// The index into the log of the line shown at `row`
+    fn index(&self, row: usize) -> Option<usize> {
+        match &self.rows {
+            Some(rows) => rows.get(row).copied(),
+            None => Some(row),
+        }
+    }
+
+    /// Scrolls up by a line and stops following new output
+    pub const fn scroll_up(&mut self) {
+        self.scroll_bottom = false;
+        self.scroll_view_state.scroll_up();
+    }
+
+    /// Scrolls down by a line
+    pub const fn scroll_down(&mut self) {
+        self.scroll_view_state.scroll_down();
+    }
+
+    /// Scrolls up by `lines` and stops following new output
+    pub fn scroll_up_by(&mut self, lines: u16) {
+        let offset = self.scroll_view_state.offset().y;
+        self.scroll_to(offset.saturating_sub(lines));
+    }
+
+    /// Scrolls down by `lines`, following new output again at the end of the log
+    pub fn scroll_down_by(&mut self, lines: u16) {
+        let offset = self.scroll_view_state.offset().y;
+        self.scroll_to(offset.saturating_add(lines));
+    }
+
+    /// Scrolls up by the height of the view and stops following new output
+    pub fn page_up(&mut self) {
+        let offset = self.scroll_view_state.offset().y;
+        self.scroll_to(offset.saturating_sub(self.area.height));
+    }
+
+    /// Scrolls down by the height of the view, following new output again at the end of the log
+    pub fn page_down(&mut self) {
+        let offset = self.scroll_view_state.offset().y;
+        self.scroll_to(offset.saturating_add(self.area.height));
+    }
+
+    /// Jumps to the oldest line kept in the log and stops following new output
+    pub fn scroll_top(&mut self) {
+        self.scroll_to(0);
+    }
+
+    /// Jumps to the end of the log and follows new output
+    pub const fn scroll_bottom(&mut self) {
+        self.scroll_bottom = true;
+    }
+
+    /// Handles a mouse event on the log or its scrollbar, returning whether it was used
+    ///
+    /// Dragging the thumb jumps through the log and clicking the track above or below it scrolls by a page.
+    /// Double clicking a word selects it and triple clicking selects the whole line, copying it to the clipboard.
+    /// Clicking the level badge of a line shows only the lines of that level, clicking a badge again shows every line.
+    /// Clicking a pinned line unpins it.
+    pub fn handle_mouse(&mut self, event: MouseEvent) -> bool {
+        let height = self.area.height;
+        let max_offset = self.lines.saturating_sub(height);
+        let on_scrollbar = event.column + 1 == self.area.right()
+            && (self.area.top()..self.area.bottom()).contains(&event.row);
+        let row = event.row.saturating_sub(self.area.y);
+
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) if on_scrollbar && max_offset > 0 => {
+                // the thumb is as much of the track as the view is of the log, multiplied in u32 as long logs overflow
+                let thumb = (u32::from(height) * u32::from(height) / u32::from(self.lines)).max(1);
+                let offset = self.scroll_view_state.offset().y.min(max_offset);
+                let thumb_start = (u32::from(offset) * (u32::from(height) - thumb)
+                    / u32::from(max_offset)) as u16;
+                let thumb = thumb as u16;
+                if row < thumb_start {
+                    self.scroll_to(offset.saturating_sub(height));
+                } else if row >= thumb_start + thumb {
+                    self.scroll_to(offset + height);
+                } else {
+                    self.dragging = true;
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging => {
+                let track = u32::from(height.saturating_sub(1).max(1));
+                let offset = u32::from(row.min(height)) * u32::from(max_offset) / track;
+                self.scroll_to(offset as u16);
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.dragging => self.dragging = false,
+            MouseEventKind::Down(MouseButton::Left)
+                if self.pinned_area.contains(position(event)) =>
+            {
+                let row = usize::from(event.row - self.pinned_area.y);
+                if row < self.pinned.len() {
+                    self.pinned.remove(row);
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left) if self.gutter.contains(position(event)) => {
+                self.toggle_level(usize::from(self.scroll_view_state.offset().y + row));
+            }
+            MouseEventKind::Down(MouseButton::Left) if self.area.contains(position(event)) => {
+                self.click(position(event));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Selects a word on a double click and a line on a triple click at `position`
+    ///
+    /// A triple click copies the whole message the line belongs to, with every line of it.
+    fn click(&mut self, position: Position) {
+        let now = Instant::now();
+        let clicks = match self.last_click {
+            Some((at, last, clicks)) if last == position && now - at <= MULTI_CLICK => {
+                clicks % 3 + 1
+            }
+            _ => 1,
+        };
+        self.last_click = Some((now, position, clicks));
+
+        let line = usize::from(self.scroll_view_state.offset().y + position.y - self.area.y);
+        let column = usize::from(position.x - self.area.x);
+        let lines = {
+            let lock = self.log.history().lock();
+            self.index(line)
+                .filter(|index| *index < lock.text.lines.len())
+                .map(|index| (lock.text.lines[index].to_string(), lock.message_text(index)))
+        };
+        let Some((text, message)) = lines else {
+            self.selection = None;
+            return;
+        };
+
+        self.selection = match clicks {
+            2 => word_at(&text, column).map(|(start, end)| Selection { line, start, end }),
+            3 => Some(Selection {
+                line,
+                start: 0,
+                end: text.chars().count(),
+            }),
+            _ => None,
+        };
+        if let Some(selection) = self.selection {
+            let selected: String = if clicks == 3 {
+                message
+            } else {
+                text.chars()
+                    .skip(selection.start)
+                    .take(selection.end - selection.start)
+                    .collect()
+            };
+            if let Err(err) = clipboard::copy(&selected) {
+                warn!("Failed to copy the selection: {err}");
+            }
+        }
+    }
+
+    /// Where the line holding the selection was logged from, if that was captured
+    pub fn selected_metadata(&self) -> Option<Arc<LineMetadata>> {
+        let index = self.index(self.selection?.line)?;
+        self.log.history().lock().metadata(index)
+    }
+
+    /// Scrolls to the line at `offset`, following new output again once the end of the log is reached
+    fn scroll_to(&mut self, offset: u16) {
+        let max_offset = self.lines.saturating_sub(self.area.height);
+        self.scroll_bottom = offset >= max_offset;
+        self.scroll_view_state
+            .set_offset(Position::new(0, offset.min(max_offset)));
+    }
+
+    /// Clamps the scroll position to the new size of the terminal on the next render
+    pub const fn resize(&mut self) {
+        self.resized = true;
+    }
+
+    /// Renders the log of the server
+    pub fn render(&mut self, mut area: Rect, buf: &mut Buffer) {
+        let lock = self.log.history().lock();
+        let mut shown = Vec::new();
+        if let Some(filter) = &self.filter {
+            shown.push(if filter == audit::SOURCE {
+                "executed commands".to_owned()
+            } else {
+                format!("the output of {filter}")
+            });
+        }
+        if let Some(level) = self.level {
+            shown.push(format!("{level} lines"));
+        }
+        if !shown.is_empty() {
+            Line::from(format!("Only showing {}", shown.join(" and ")))
+                .italic()
+                .render(area, buf);
+            area.y += 1;
+            area.height = area.height.saturating_sub(1);
+        }
+        // pinned lines stay in view however far the log is scrolled
+        self.pinned_area = Rect::ZERO;
+        if !self.pinned.is_empty() {
+            let [pinned_area, rest] = Layout::vertical([
+                Constraint::Length(self.pinned.len() as u16 + 1),
+                Constraint::Fill(1),
+            ])
+            .areas(area);
+            let block = Block::new()
+                .borders(Borders::BOTTOM)
+                .border_style(Color::DarkGray)
+                .title_bottom("pinned, click to unpin".dark_gray());
+            self.pinned_area = block.inner(pinned_area);
+            Text::from_iter(self.pinned.iter().map(|(_, line)| line.clone()))
+                .render(self.pinned_area, buf);
+            block.render(pinned_area, buf);
+            area = rest;
+        }
+        let [gutter, area] =
+            Layout::horizontal([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+        self.gutter = gutter;
+
+        let view;
+        let text = if self.filter.is_none() && self.level.is_none() && !self.folded {
+            self.rows = None;
+            &lock.text
+        } else {
+            let (text, rows) = lock.view(self.filter.as_deref(), self.level, self.folded);
+            view = text;
+            self.rows = Some(rows);
+            &view
+        };
+
+        // lines dropped at the scrollback limit would shift what is being read upwards, unless the offset follows them
+        let trimmed = lock.trimmed() - self.trimmed;
+        self.trimmed = lock.trimmed();
+        if trimmed > 0 && self.rows.is_none() {
+            let offset = self.scroll_view_state.offset();
+            let removed = u16::try_from(trimmed).unwrap_or(u16::MAX);
+            self.scroll_view_state
+                .set_offset(Position::new(offset.x, offset.y.saturating_sub(removed)));
+            self.selection = self.selection.and_then(|selection| {
+                Some(Selection {
+                    line: selection.line.checked_sub(trimmed)?,
+                    ..selection
+                })
+            });
+        }
+
+        let content_size = Size::new(area.width - 1, text.lines.len() as u16);
+        self.area = area;
+        self.lines = content_size.height;
+        let mut scroll_view = ScrollView::new(content_size)
+            .horizontal_scrollbar_visibility(ScrollbarVisibility::Never);
+
+        // a taller terminal can leave the offset past the end of the log, which the scroll view can't show
+        if self.resized {
+            self.resized = false;
+            let max_offset = content_size.height.saturating_sub(area.height);
+            let offset = self.scroll_view_state.offset();
+            self.scroll_view_state
+                .set_offset(Position::new(0, offset.y.min(max_offset)));
+        }
+
+        if self.scroll_view_state.offset().y + area.height > content_size.height {
+            self.scroll_bottom = true;
+        }
+
+        if self.scroll_bottom {
+            self.scroll_view_state.scroll_to_bottom();
+        }
+
+        scroll_view.render_widget(text, scroll_view.area());
+        scroll_view.render(area, buf, &mut self.scroll_view_state);
+
+        // one badge per message, at its first line
+        let offset = usize::from(self.scroll_view_state.offset().y);
+        for row in 0..gutter.height {
+            let level = self
+                .index(offset + usize::from(row))
+                .filter(|index| {
+                    *index < lock.text.lines.len() && lock.message(*index).start == *index
+                })
+                .and_then(|index| lock.metadata(index))
+                .map(|metadata| metadata.level);
+            if let Some(level) = level {
+                let badge = if self.level == Some(level) {
+                    badge(level).reversed()
+                } else {
+                    badge(level)
+                };
+                badge.render(Rect::new(gutter.x, gutter.y + row, 1, 1), buf);
+            }
+        }
+
+        if let Some(selection) = self.selection
+            && let Some(row) = selection
+                .line
+                .checked_sub(usize::from(self.scroll_view_state.offset().y))
+                .filter(|row| *row < usize::from(area.height))
+        {
+            let start = area.x + selection.start as u16;
+            let end = (area.x + selection.end as u16).min(area.right().saturating_sub(1));
+            buf.set_style(
+                Rect::new(start, area.y + row as u16, end.saturating_sub(start), 1),
+                Style::new().reversed(),
+            );
+        }
+    }
+}
+
+const fn position(event: MouseEvent) -> Position {
+    Position::new(event.column, event.row)
+}
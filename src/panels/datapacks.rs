@@ -0,0 +1,113 @@
+use crate::config::Theme;
+use crate::datapacks::{self, Datapack};
+use crate::logger::ServerLog;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Row, Table, TableState};
+
+/// The datapacks installed into the world of a server, which can be switched on or off and reloaded
+#[derive(Default)]
+pub struct DatapackList {
+    table_state: TableState,
+    /// A command enabling, disabling or reloading datapacks, waiting to be run
+    picked: Option<String>,
+    /// The datapacks last read from the world, or why they couldn't be, read again on `r`
+    packs: Option<Result<Vec<Datapack>, String>>,
+}
+
+impl DatapackList {
+    /// Handles a key pressed while the datapacks tab is shown, returning whether it was used
+    pub fn handle_key(&mut self, log: ServerLog, event: KeyEvent) -> bool {
+        match event.code {
+            KeyCode::Up => self.table_state.select_previous(),
+            KeyCode::Down => self.table_state.select_next(),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let selected = self.table_state.selected();
+                let Some(pack) = self
+                    .packs(log)
+                    .as_mut()
+                    .ok()
+                    .zip(selected)
+                    .and_then(|(packs, index)| packs.get_mut(index))
+                else {
+                    return false;
+                };
+                let action = if pack.enabled { "disable" } else { "enable" };
+                self.picked = Some(format!("datapack {action} {}", pack.name));
+                // the command renames it in the background, a failure is logged and `r` shows the real state
+                pack.enabled = !pack.enabled;
+            }
+            KeyCode::Char('r') => {
+                self.packs = None;
+                self.picked = Some("reload".to_owned());
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// The cached datapacks of the server logging into `log`, read from its world the first time
+    fn packs(&mut self, log: ServerLog) -> &mut Result<Vec<Datapack>, String> {
+        self.packs
+            .get_or_insert_with(|| log.scope(datapacks::list).map_err(|err| format!("{err:#}")))
+    }
+
+    /// Takes the picked command, to be run right away
+    pub const fn take_picked(&mut self) -> Option<String> {
+        self.picked.take()
+    }
+
+    /// Renders the datapacks of the server logging into `log`, with why they fail to load next to them
+    pub fn render(&mut self, log: ServerLog, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        if let Err(err) = self.packs(log) {
+            Line::from(err.clone()).red().render(area, buf);
+            return;
+        }
+        let Some(Ok(packs)) = &self.packs else {
+            return;
+        };
+
+        let header =
+            Row::new(["Name", "State", "Description"]).style(Style::new().fg(theme.accent).bold());
+        let rows = packs.iter().map(|pack| {
+            let state = if pack.enabled {
+                "enabled".green()
+            } else {
+                "disabled".fg(theme.muted)
+            };
+            let description = match &pack.error {
+                Some(error) => Line::from(error.clone()).red(),
+                None => Line::from(pack.description.clone()),
+            };
+            Row::new([
+                Line::from(pack.name.clone()),
+                Line::from(state),
+                description,
+            ])
+        });
+
+        StatefulWidget::render(
+            Table::new(
+                rows,
+                [
+                    Constraint::Length(32),
+                    Constraint::Length(10),
+                    Constraint::Fill(1),
+                ],
+            )
+            .header(header)
+            .row_highlight_style(Style::new().reversed())
+            .block(
+                Block::new()
+                    .title(format!("{} datapacks", packs.len()))
+                    .title_bottom(
+                        "Enter enables or disables the selected datapack, r reloads them"
+                            .fg(theme.muted),
+                    ),
+            ),
+            area,
+            buf,
+            &mut self.table_state,
+        );
+    }
+}
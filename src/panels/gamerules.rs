@@ -0,0 +1,133 @@
+use crate::config::Theme;
+use crate::worlds;
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Row, Table, TableState};
+use steel_core::server::Server;
+use steel_core::world::GameRuleValue;
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+
+/// The gamerules of one world of a server at a time, edited in place
+///
+/// Enter flips a true or false rule and starts typing a new value for a number. The change runs as a `world` command,
+/// so it is echoed into the log and written into the audit log like any other.
+#[derive(Default)]
+pub struct GameruleView {
+    /// The index of the shown world
+    world: usize,
+    table_state: TableState,
+    /// The new value of the selected number rule while it is typed
+    editing: Option<Input>,
+    /// A command changing a gamerule, waiting to be run
+    picked: Option<String>,
+}
+
+impl GameruleView {
+    /// Handles a key pressed while the gamerules tab is shown, returning whether it was used
+    pub fn handle_key(&mut self, server: &Server, event: KeyEvent) -> bool {
+        let Some((name, world)) = server.worlds.iter().nth(self.world) else {
+            self.world = 0;
+            return false;
+        };
+        let rules = worlds::game_rules(world);
+        let selected = self
+            .table_state
+            .selected()
+            .and_then(|index| rules.get(index));
+
+        if let Some(input) = &mut self.editing {
+            match event.code {
+                KeyCode::Esc => self.editing = None,
+                KeyCode::Enter => {
+                    if let Some((rule, _)) = selected {
+                        self.picked = Some(format!(
+                            "world {name} gamerule {rule} {}",
+                            input.value().trim()
+                        ));
+                    }
+                    self.editing = None;
+                }
+                _ => {
+                    input.handle_event(&Event::Key(event));
+                }
+            }
+            return true;
+        }
+
+        match event.code {
+            KeyCode::Up => self.table_state.select_previous(),
+            KeyCode::Down => self.table_state.select_next(),
+            KeyCode::Left | KeyCode::Right => {
+                let count = server.worlds.len().max(1);
+                self.world = if event.code == KeyCode::Left {
+                    (self.world + count - 1) % count
+                } else {
+                    (self.world + 1) % count
+                };
+                self.table_state.select_first();
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => match selected {
+                Some((rule, GameRuleValue::Bool(value))) => {
+                    self.picked = Some(format!("world {name} gamerule {rule} {}", !value));
+                }
+                Some((_, GameRuleValue::Int(value))) => {
+                    self.editing = Some(Input::new(value.to_string()));
+                }
+                None => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /// Takes the command changing a gamerule, to be run right away
+    pub const fn take_picked(&mut self) -> Option<String> {
+        self.picked.take()
+    }
+
+    /// Renders the gamerules of the shown world of `server`
+    pub fn render(&mut self, server: &Server, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let Some((name, world)) = server.worlds.iter().nth(self.world) else {
+            self.world = 0;
+            return;
+        };
+        let rules = worlds::game_rules(world);
+        if self.table_state.selected().is_none() && !rules.is_empty() {
+            self.table_state.select_first();
+        }
+        let selected = self.table_state.selected();
+
+        let header = Row::new(["Gamerule", "Value"]).style(Style::new().fg(theme.accent).bold());
+        let rows = rules.iter().enumerate().map(|(index, (rule, value))| {
+            let value = match (value, &self.editing) {
+                (_, Some(input)) if selected == Some(index) => {
+                    format!("{}_", input.value()).fg(theme.accent)
+                }
+                (GameRuleValue::Bool(true), _) => "true".green(),
+                (GameRuleValue::Bool(false), _) => "false".red(),
+                (GameRuleValue::Int(value), _) => Span::raw(value.to_string()),
+            };
+            Row::new([Line::from(rule.clone()), Line::from(value)])
+        });
+        let hint = if self.editing.is_some() {
+            "Enter applies the value, Esc cancels"
+        } else {
+            "Left/Right switch worlds, Enter toggles or edits the selected rule"
+        };
+
+        StatefulWidget::render(
+            Table::new(rows, [Constraint::Fill(1), Constraint::Length(16)])
+                .header(header)
+                .row_highlight_style(Style::new().reversed())
+                .block(
+                    Block::new()
+                        .title(format!("{} gamerules of {name}", rules.len()))
+                        .title_bottom(hint.fg(theme.muted)),
+                ),
+            area,
+            buf,
+            &mut self.table_state,
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use crate::config::Theme;
+use crate::jobs::{self, JobStatus};
+use crate::logger::ServerLog;
+use crate::status;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Row, Table, TableState};
+
+/// How far a command got, like `45% (1200/2650)`
+fn progress(job: &JobStatus) -> String {
+    match job.progress {
+        Some((done, total)) if total > 0 => {
+            format!("{}% ({done}/{total})", done.saturating_mul(100) / total)
+        }
+        _ => "running".to_owned(),
+    }
+}
+
+/// The long running commands of a server, which can be cancelled
+#[derive(Default)]
+pub struct JobList {
+    table_state: TableState,
+}
+
+impl JobList {
+    fn selected(&self, log: ServerLog) -> Option<JobStatus> {
+        let index = self.table_state.selected()?;
+        jobs::running(log).into_iter().nth(index)
+    }
+
+    /// Handles a key pressed while the running tab is shown, returning whether it was used
+    pub fn handle_key(&mut self, log: ServerLog, event: KeyEvent) -> bool {
+        match event.code {
+            KeyCode::Up => self.table_state.select_previous(),
+            KeyCode::Down => self.table_state.select_next(),
+            KeyCode::Char('c') | KeyCode::Delete => {
+                if let Some(job) = self.selected(log) {
+                    jobs::cancel(job.id);
+                }
+            }
+            KeyCode::Char('p') => {
+                if let Some(job) = self.selected(log) {
+                    jobs::toggle_pause(job.id);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Renders the long running commands of the server logging into `log`
+    pub fn render(&mut self, log: ServerLog, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let running = jobs::running(log);
+        if self.table_state.selected().is_none() && !running.is_empty() {
+            self.table_state.select_first();
+        }
+
+        let header = Row::new(["Command", "Running for", "Progress", "Rate", "Remaining"])
+            .style(Style::new().fg(theme.accent).bold());
+        let rows = running.iter().map(|job| {
+            let progress = if job.cancelled {
+                "cancelling".fg(theme.muted)
+            } else if job.paused == Some(true) {
+                format!("paused at {}", progress(job)).yellow()
+            } else {
+                Span::raw(progress(job))
+            };
+            Row::new([
+                Line::from(job.command.clone()),
+                Line::from(format!("{:.0?}", job.elapsed)),
+                Line::from(progress),
+                Line::from(
+                    job.rate()
+                        .map_or_else(String::new, |rate| format!("{rate:.1}/s")),
+                ),
+                Line::from(
+                    job.eta()
+                        .map_or_else(String::new, |eta| format!("{eta:.0?}")),
+                ),
+            ])
+        });
+        let memory = status::memory()
+            .resident_bytes
+            .map_or_else(String::new, |bytes| {
+                format!("{} MiB used", bytes / 1024 / 1024)
+            });
+        StatefulWidget::render(
+            Table::new(
+                rows,
+                [
+                    Constraint::Fill(1),
+                    Constraint::Length(12),
+                    Constraint::Length(28),
+                    Constraint::Length(10),
+                    Constraint::Length(10),
+                ],
+            )
+            .header(header)
+            .row_highlight_style(Style::new().reversed())
+            .block(
+                Block::new()
+                    .title(format!("{} commands running", running.len()))
+                    .title(Line::from(memory.fg(theme.muted)).right_aligned())
+                    .title_bottom(
+                        "c cancels the selected command, p pauses or resumes it".fg(theme.muted),
+                    ),
+            ),
+            area,
+            buf,
+            &mut self.table_state,
+        );
+    }
+}
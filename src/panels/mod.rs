@@ -0,0 +1,147 @@
+use crate::config::Theme;
+use ratatui::crossterm::event::KeyEvent;
+use ratatui::prelude::*;
+use ratatui::widgets::Tabs;
+use serde::{Deserialize, Serialize};
+use steel_core::server::Server;
+
+pub mod afk;
+pub mod console;
+pub mod datapacks;
+pub mod gamerules;
+pub mod jobs;
+pub mod performance;
+pub mod permissions;
+pub mod player_data;
+pub mod players;
+#[cfg(feature = "plugin")]
+pub mod plugin_events;
+#[cfg(feature = "plugin")]
+pub mod plugin_repl;
+#[cfg(feature = "plugin")]
+pub mod plugins;
+pub mod registry;
+pub mod security;
+pub mod stopped;
+pub mod worlds;
+
+/// A view that can be selected in the tab bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Panel {
+    /// The server log
+    #[default]
+    Console,
+    /// The players connected to the server
+    Players,
+    /// Tick timings, memory usage and loaded chunks
+    Performance,
+    /// The player data stored by the server, including offline players
+    PlayerData,
+    /// The worlds of the server with their time, weather and difficulty
+    Worlds,
+    /// Players that have been idle for a while
+    Afk,
+    /// Long running commands, which can be cancelled
+    Jobs,
+    /// The gamerules of every world, editable in place
+    Gamerules,
+    /// The datapacks installed into the world
+    Datapacks,
+    /// The items, blocks, biomes and recipes the server knows
+    Registry,
+    /// The permission levels of the players
+    Permissions,
+    /// Connection attempts with the throttled and blocked addresses
+    Security,
+    /// A panel added with `SteelAppBuilder::panel`, by the order it was added in
+    #[serde(skip)]
+    Custom(usize),
+    /// A panel registered by a plugin, by the order it was first registered in
+    #[cfg(feature = "plugin")]
+    #[serde(skip)]
+    Plugin(usize),
+}
+
+impl Panel {
+    /// Every built in panel in tab order
+    pub const ALL: [Self; 12] = [
+        Self::Console,
+        Self::Players,
+        Self::Performance,
+        Self::PlayerData,
+        Self::Worlds,
+        Self::Afk,
+        Self::Jobs,
+        Self::Gamerules,
+        Self::Datapacks,
+        Self::Registry,
+        Self::Permissions,
+        Self::Security,
+    ];
+
+    /// Whether the panel works on the server itself, which a made up one like the demo doesn't have
+    pub const fn needs_server(self) -> bool {
+        matches!(
+            self,
+            Self::PlayerData | Self::Worlds | Self::Gamerules | Self::Permissions | Self::Custom(_)
+        )
+    }
+
+    /// The name shown in the tab bar of a built in panel
+    pub const fn title(self) -> &'static str {
+        match self {
+            Self::Console => "Console",
+            Self::Players => "Players",
+            Self::Performance => "Performance",
+            Self::PlayerData => "Player data",
+            Self::Worlds => "Worlds",
+            Self::Afk => "Idle",
+            Self::Jobs => "Running",
+            Self::Gamerules => "Gamerules",
+            Self::Datapacks => "Datapacks",
+            Self::Registry => "Registry",
+            Self::Permissions => "Permissions",
+            Self::Security => "Security",
+            Self::Custom(_) => "Custom",
+            #[cfg(feature = "plugin")]
+            Self::Plugin(_) => "Plugin",
+        }
+    }
+}
+
+/// A panel provided by an embedder of the library, shown after the built in panels
+pub trait CustomPanel: Send {
+    /// The name shown in the tab bar
+    fn title(&self) -> &str;
+
+    /// Whether the panel shows state that has to be redrawn periodically
+    fn is_live(&self) -> bool {
+        true
+    }
+
+    /// Handles a key pressed while the panel is shown, returning whether it was used
+    ///
+    /// Keys the panel doesn't use go to the keybinds and the input line.
+    fn handle_key(&mut self, _event: KeyEvent) -> bool {
+        false
+    }
+
+    /// Renders the panel into `area`
+    fn render(&mut self, server: &Server, theme: &Theme, area: Rect, buf: &mut Buffer);
+}
+
+/// Renders the tab bar with the tab at `selected` highlighted
+pub fn render_tabs(
+    titles: Vec<&str>,
+    selected: usize,
+    theme: &Theme,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    Tabs::new(titles)
+        .select(selected)
+        .style(Style::new().fg(theme.muted))
+        .highlight_style(Style::new().fg(theme.accent).bold())
+        .render(area, buf);
+}
@@ -0,0 +1,149 @@
+use crate::config::Theme;
+use crate::distance::{Distance, MAX_DISTANCE, MIN_DISTANCE};
+use crate::io_pool;
+use crate::logger::LogCounter;
+#[cfg(all(feature = "profiling", unix))]
+use crate::profiler;
+use crate::status::{self, ServerStatus};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, LineGauge, Paragraph};
+use std::fmt::Display;
+use steel_core::server::Server;
+
+fn stat(theme: &Theme, label: &str, value: impl Display) -> Line<'static> {
+    Line::from(vec![
+        format!("{label:<16}").fg(theme.muted),
+        value.to_string().into(),
+    ])
+}
+
+/// The performance tab, with sliders for the distances the server works within
+#[derive(Default)]
+pub struct PerformanceView {
+    /// The slider Left/Right adjusts
+    selected: Distance,
+}
+
+impl PerformanceView {
+    /// Handles a key pressed while the performance tab is shown, returning whether it was used
+    pub fn handle_key(&mut self, server: &Server, event: KeyEvent) -> bool {
+        match event.code {
+            KeyCode::Up | KeyCode::Down => {
+                self.selected = match self.selected {
+                    Distance::View => Distance::Simulation,
+                    Distance::Simulation => Distance::View,
+                };
+            }
+            KeyCode::Left => self.selected.adjust(server, -1),
+            KeyCode::Right => self.selected.adjust(server, 1),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Renders the tick timings, memory usage and loaded chunks of the server, with its distances if it is running
+    pub fn render(
+        &self,
+        status: &dyn ServerStatus,
+        server: Option<&Server>,
+        theme: &Theme,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let ticks = status.ticks();
+        let memory = status::memory().resident_bytes.map_or_else(
+            || "unknown".to_owned(),
+            |bytes| format!("{} MiB", bytes / 1024 / 1024),
+        );
+        let (errors, warnings) = LogCounter::counts();
+
+        let mut lines = vec![
+            stat(theme, "TPS", format!("{:.1}", ticks.tps)),
+            stat(theme, "MSPT", format!("{:.2} ms", ticks.mspt)),
+            stat(theme, "Memory", memory),
+            stat(theme, "Errors logged", errors),
+            stat(theme, "Warnings logged", warnings),
+        ];
+        #[cfg(all(feature = "profiling", unix))]
+        if let Some(elapsed) = profiler::running() {
+            lines.push(stat(
+                theme,
+                "Profiling",
+                format!(
+                    "for {}s, `profile stop` writes the flamegraph",
+                    elapsed.as_secs()
+                ),
+            ));
+        }
+        lines.extend([
+            Line::default(),
+            Line::from("Loaded chunks".fg(theme.accent).bold()),
+        ]);
+        for world in status.worlds() {
+            lines.push(stat(theme, &world.name, world.loaded_chunks));
+        }
+        if let Some(pool) = io_pool::stats() {
+            // a full pool with tasks waiting means chunk saves and loads are held up by the disk
+            let queued = if pool.queued > 0 && pool.busy() >= pool.max {
+                pool.queued.to_string().red()
+            } else {
+                pool.queued.to_string().into()
+            };
+            lines.extend([
+                Line::default(),
+                Line::from("World IO".fg(theme.accent).bold()),
+                stat(
+                    theme,
+                    "Threads",
+                    format!(
+                        "{} busy, {} idle, {} max ({:.0}% saturated)",
+                        pool.busy(),
+                        pool.idle,
+                        pool.max,
+                        pool.saturation() * 100.0
+                    ),
+                ),
+                Line::from(vec![format!("{:<16}", "Queued").fg(theme.muted), queued]),
+            ]);
+        }
+
+        let Some(server) = server else {
+            Paragraph::new(lines).render(area, buf);
+            return;
+        };
+        let [stats_area, sliders_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(Distance::ALL.len() as u16 + 2),
+        ])
+        .areas(area);
+        Paragraph::new(lines).render(stats_area, buf);
+        self.render_sliders(server, theme, sliders_area, buf);
+    }
+
+    fn render_sliders(&self, server: &Server, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let block = Block::new()
+            .title("Distances".fg(theme.accent).bold())
+            .title_bottom("Up/Down select, Left/Right adjust".fg(theme.muted));
+        let rows =
+            Layout::vertical([Constraint::Length(1); Distance::ALL.len()]).split(block.inner(area));
+        block.render(area, buf);
+
+        for (distance, row) in Distance::ALL.into_iter().zip(rows.iter()) {
+            let chunks = distance.get(server);
+            let ratio = f64::from(chunks.saturating_sub(MIN_DISTANCE))
+                / f64::from(MAX_DISTANCE - MIN_DISTANCE);
+            let style = if distance == self.selected {
+                Style::new().fg(theme.accent)
+            } else {
+                Style::new()
+            };
+            LineGauge::default()
+                .label(format!("{:<16}{chunks:>2}", distance.name()).fg(theme.muted))
+                .ratio(ratio.clamp(0.0, 1.0))
+                .filled_style(style)
+                .unfilled_style(Style::new().fg(theme.muted))
+                .render(*row, buf);
+        }
+    }
+}
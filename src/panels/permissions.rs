@@ -0,0 +1,213 @@
+use crate::config::Theme;
+use crate::ops::{self, LEVELS};
+use crate::status;
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Row, Table, TableState};
+use std::collections::BTreeMap;
+use steel_core::server::Server;
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+use uuid::Uuid;
+
+/// A player as shown in the permissions tab
+struct Entry {
+    name: String,
+    uuid: Uuid,
+    level: u8,
+    online: bool,
+}
+
+/// The permission levels of the players that have one or are online, searchable by name and changed in place
+///
+/// While the tab is shown, typing goes into its search line. Left and Right lower or raise the level of the selected
+/// player, which runs the `perm` command once confirmed.
+#[derive(Default)]
+pub struct PermissionsView {
+    search: Input,
+    table_state: TableState,
+    /// A command changing a permission level, waiting to be confirmed and run
+    picked: Option<String>,
+    /// Where the cursor of the search line was last drawn
+    cursor: Position,
+}
+
+impl PermissionsView {
+    /// The players with a permission level and the online ones, matching the search
+    fn entries(&self, server: &Server) -> Result<Vec<Entry>, String> {
+        let ops = ops::load().map_err(|err| format!("{err:#}"))?;
+        let mut entries: BTreeMap<Uuid, Entry> = ops
+            .into_iter()
+            .map(|op| {
+                (
+                    op.uuid,
+                    Entry {
+                        name: op.name,
+                        uuid: op.uuid,
+                        level: op.level,
+                        online: false,
+                    },
+                )
+            })
+            .collect();
+        for player in status::players(server) {
+            entries
+                .entry(player.uuid)
+                .or_insert_with(|| Entry {
+                    name: player.name,
+                    uuid: player.uuid,
+                    level: 0,
+                    online: true,
+                })
+                .online = true;
+        }
+
+        let search = self.search.value().trim().to_lowercase();
+        let mut entries: Vec<Entry> = entries
+            .into_values()
+            .filter(|entry| entry.name.to_lowercase().contains(&search))
+            .collect();
+        entries.sort_by(|a, b| b.level.cmp(&a.level).then_with(|| a.name.cmp(&b.name)));
+        Ok(entries)
+    }
+
+    /// Handles a key pressed while the permissions tab is shown, returning whether it was used
+    pub fn handle_key(&mut self, server: &Server, event: KeyEvent) -> bool {
+        // keybinds with modifiers and panel switching keep working
+        if event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+        match event.code {
+            KeyCode::Up => self.table_state.select_previous(),
+            KeyCode::Down => self.table_state.select_next(),
+            KeyCode::Left | KeyCode::Right => {
+                let entries = self.entries(server).unwrap_or_default();
+                let Some(entry) = self
+                    .table_state
+                    .selected()
+                    .and_then(|index| entries.get(index))
+                else {
+                    return false;
+                };
+                let level = if event.code == KeyCode::Left {
+                    entry.level.saturating_sub(1)
+                } else {
+                    (entry.level + 1).min(LEVELS.len() as u8 - 1)
+                };
+                if level != entry.level {
+                    self.picked = Some(format!("perm {} {level}", entry.name));
+                }
+            }
+            KeyCode::Char(_)
+            | KeyCode::Backspace
+            | KeyCode::Delete
+            | KeyCode::Home
+            | KeyCode::End => {
+                self.search.handle_event(&Event::Key(event));
+                self.table_state.select_first();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Takes the command changing a permission level, to be confirmed before it runs
+    pub const fn take_picked(&mut self) -> Option<String> {
+        self.picked.take()
+    }
+
+    /// Where the cursor of the search line is, as of the last render
+    pub const fn cursor(&self) -> Position {
+        self.cursor
+    }
+
+    /// Renders the players matching the search with their levels and what the selected one inherits
+    pub fn render(&mut self, server: &Server, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let [search_area, table_area, details_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .areas(area);
+        let block = Block::new()
+            .borders(Borders::BOTTOM)
+            .border_style(theme.muted);
+        let prompt = "search> ";
+        let inner = block.inner(search_area);
+        self.cursor = Position::new(
+            inner.x + (prompt.len() + self.search.visual_cursor()) as u16,
+            inner.y,
+        );
+        Line::from(vec![prompt.fg(theme.prompt), self.search.value().into()]).render(inner, buf);
+        block.render(search_area, buf);
+
+        let entries = match self.entries(server) {
+            Ok(entries) => entries,
+            Err(err) => {
+                Line::from(err).red().render(table_area, buf);
+                return;
+            }
+        };
+
+        let header = Row::new(["Name", "UUID", "Level", "Group"])
+            .style(Style::new().fg(theme.accent).bold());
+        let rows = entries.iter().map(|entry| {
+            let name = if entry.online {
+                Line::from(entry.name.clone()).green()
+            } else {
+                Line::from(entry.name.clone())
+            };
+            Row::new([
+                name,
+                Line::from(entry.uuid.to_string()).fg(theme.muted),
+                Line::from(entry.level.to_string()),
+                Line::from(ops::level_name(entry.level)),
+            ])
+        });
+        StatefulWidget::render(
+            Table::new(
+                rows,
+                [
+                    Constraint::Length(17),
+                    Constraint::Length(37),
+                    Constraint::Length(6),
+                    Constraint::Fill(1),
+                ],
+            )
+            .header(header)
+            .row_highlight_style(Style::new().reversed())
+            .block(
+                Block::new()
+                    .title(format!("{} players", entries.len()))
+                    .title_bottom(
+                        "Type to search, Left/Right lower or raise the level".fg(theme.muted),
+                    ),
+            ),
+            table_area,
+            buf,
+            &mut self.table_state,
+        );
+
+        if let Some(entry) = self
+            .table_state
+            .selected()
+            .and_then(|index| entries.get(index))
+        {
+            let (name, allows) = LEVELS[usize::from(entry.level).min(LEVELS.len() - 1)];
+            let mut details = vec![Line::from(format!("{name}: {allows}"))];
+            if entry.level > 0 {
+                details.push(
+                    Line::from(format!(
+                        "Inherits {}",
+                        ops::inherited(entry.level).join(", ")
+                    ))
+                    .fg(theme.muted),
+                );
+            }
+            Text::from(details).render(details_area, buf);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::REDRAW;
+use crate::config::Theme;
+use crate::panels::players::client_label;
+use crate::status::{self, PlayerInfo};
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Row, Table, TableState};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use steel_core::server::Server;
+use steel_utils::locks::SyncMutex;
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+use uuid::Uuid;
+
+/// A player the server stored data of, online or not
+#[derive(Debug, Clone)]
+struct Record {
+    uuid: Uuid,
+    name: String,
+    last_seen: Option<SystemTime>,
+    position: [f64; 3],
+    dimension: String,
+    /// Number of item stacks and items in the inventory
+    items: (usize, u32),
+}
+
+/// What the panel knows about the stored players
+#[derive(Default)]
+enum Loaded {
+    #[default]
+    NotYet,
+    Loading,
+    Done(Vec<Record>),
+    Failed(String),
+}
+
+/// How long ago `time` was, like `3h ago`
+fn ago(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+    match secs {
+        0..60 => format!("{secs}s ago"),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// Browses the player data stored by a server, including players that are offline
+///
+/// While the tab is shown, typing goes into its search line, which matches names and UUIDs.
+#[derive(Default)]
+pub struct PlayerDataView {
+    search: Input,
+    table_state: TableState,
+    /// Filled in by the task reading the player data
+    loaded: Arc<SyncMutex<Loaded>>,
+    /// Where the cursor of the search line was last drawn
+    cursor: Position,
+}
+
+impl PlayerDataView {
+    /// Reads every stored player in the background
+    fn load(&self, server: &Arc<Server>) {
+        *self.loaded.lock() = Loaded::Loading;
+        let loaded = self.loaded.clone();
+        let server = server.clone();
+        tokio::spawn(async move {
+            let result = server.player_data_storage.load_all().await;
+            *loaded.lock() = match result {
+                Ok(players) => Loaded::Done(
+                    players
+                        .into_iter()
+                        .map(|(uuid, data)| Record {
+                            uuid,
+                            name: data.name,
+                            last_seen: data.last_played,
+                            position: data.position,
+                            dimension: data.dimension.to_string(),
+                            items: (
+                                data.inventory.len(),
+                                data.inventory
+                                    .iter()
+                                    .map(|item| u32::from(item.count))
+                                    .sum(),
+                            ),
+                        })
+                        .collect(),
+                ),
+                Err(err) => Loaded::Failed(format!("Failed to read the player data: {err}")),
+            };
+            REDRAW.notify_one();
+        });
+    }
+
+    /// The stored players matching the search
+    fn matching(&self, records: &[Record]) -> Vec<Record> {
+        let search = self.search.value().trim().to_lowercase();
+        records
+            .iter()
+            .filter(|record| {
+                record.name.to_lowercase().contains(&search)
+                    || record.uuid.to_string().contains(&search)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Handles a key pressed while the player data tab is shown, returning whether it was used
+    pub fn handle_key(&mut self, event: KeyEvent) -> bool {
+        // keybinds with modifiers and panel switching keep working
+        if event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+        match event.code {
+            KeyCode::Up => self.table_state.select_previous(),
+            KeyCode::Down => self.table_state.select_next(),
+            // reads the player data again on the next render
+            KeyCode::Enter => *self.loaded.lock() = Loaded::NotYet,
+            KeyCode::Char(_)
+            | KeyCode::Backspace
+            | KeyCode::Delete
+            | KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Home
+            | KeyCode::End => {
+                self.search.handle_event(&Event::Key(event));
+                self.table_state.select_first();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Where the cursor of the search line is, as of the last render
+    pub const fn cursor(&self) -> Position {
+        self.cursor
+    }
+
+    /// Renders the stored players of `server` matching the search, reading them first if needed
+    pub fn render(&mut self, server: &Arc<Server>, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        if matches!(*self.loaded.lock(), Loaded::NotYet) {
+            self.load(server);
+        }
+
+        let [search_area, table_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+        let block = Block::new()
+            .borders(Borders::BOTTOM)
+            .border_style(theme.muted);
+        let prompt = "search> ";
+        let inner = block.inner(search_area);
+        self.cursor = Position::new(
+            inner.x + (prompt.len() + self.search.visual_cursor()) as u16,
+            inner.y,
+        );
+        Line::from(vec![prompt.fg(theme.prompt), self.search.value().into()]).render(inner, buf);
+        block.render(search_area, buf);
+
+        let records = match &*self.loaded.lock() {
+            Loaded::NotYet | Loaded::Loading => {
+                Line::from("Reading the player data...")
+                    .fg(theme.muted)
+                    .render(table_area, buf);
+                return;
+            }
+            Loaded::Failed(err) => {
+                Line::from(err.as_str()).red().render(table_area, buf);
+                return;
+            }
+            Loaded::Done(records) => self.matching(records),
+        };
+
+        let online: HashMap<Uuid, PlayerInfo> = status::players(server)
+            .into_iter()
+            .map(|player| (player.uuid, player))
+            .collect();
+        let header = Row::new([
+            "Name",
+            "Client",
+            "UUID",
+            "Last seen",
+            "Position",
+            "Inventory",
+        ])
+        .style(Style::new().fg(theme.accent).bold());
+        let rows = records.iter().map(|record| {
+            let player = online.get(&record.uuid);
+            let last_seen = if player.is_some() {
+                "online".green()
+            } else {
+                Span::from(record.last_seen.map_or_else(|| "unknown".to_owned(), ago))
+            };
+            let [x, y, z] = record.position;
+            Row::new([
+                Line::from(record.name.clone()),
+                // the client is only known while the player is connected
+                Line::from(
+                    player
+                        .map_or_else(|| "-".fg(theme.muted), |player| client_label(player, theme)),
+                ),
+                Line::from(record.uuid.to_string()).fg(theme.muted),
+                Line::from(last_seen),
+                Line::from(format!("{x:.0} {y:.0} {z:.0} in {}", record.dimension)),
+                Line::from(format!(
+                    "{} items in {} stacks",
+                    record.items.1, record.items.0
+                )),
+            ])
+        });
+
+        StatefulWidget::render(
+            Table::new(
+                rows,
+                [
+                    Constraint::Length(17),
+                    Constraint::Length(12),
+                    Constraint::Length(36),
+                    Constraint::Length(10),
+                    Constraint::Fill(1),
+                    Constraint::Length(24),
+                ],
+            )
+            .header(header)
+            .row_highlight_style(Style::new().reversed())
+            .block(
+                Block::new()
+                    .title(format!("{} stored players", records.len()))
+                    .title_bottom("Type to search, Enter reloads".fg(theme.muted)),
+            ),
+            table_area,
+            buf,
+            &mut self.table_state,
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::config::Theme;
+use crate::status::{Client, PlayerInfo, ServerStatus};
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Clear, List, ListState, Row, Table, TableState};
+use steel_core::server::Server;
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+
+/// Moderation actions offered for the selected player
+const ACTIONS: [Moderation; 7] = [
+    Moderation::Kick,
+    Moderation::Ban,
+    Moderation::TeleportToSpawn,
+    Moderation::Gamemode("survival"),
+    Moderation::Gamemode("creative"),
+    Moderation::Gamemode("adventure"),
+    Moderation::Gamemode("spectator"),
+];
+
+/// Something the operator can do to a player from the players tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Moderation {
+    Kick,
+    Ban,
+    TeleportToSpawn,
+    Gamemode(&'static str),
+}
+
+impl Moderation {
+    fn label(self) -> String {
+        match self {
+            Self::Kick => "Kick...".to_owned(),
+            Self::Ban => "Ban".to_owned(),
+            Self::TeleportToSpawn => "Teleport to spawn".to_owned(),
+            Self::Gamemode(mode) => format!("Gamemode {mode}"),
+        }
+    }
+
+    /// The console command doing this to `player`, teleporting only works on a running server
+    fn command(self, server: Option<&Server>, player: &PlayerInfo, reason: &str) -> Option<String> {
+        let name = &player.name;
+        Some(match self {
+            Self::Kick if reason.is_empty() => format!("kick {name}"),
+            Self::Kick => format!("kick {name} {reason}"),
+            Self::Ban => format!("ban {name}"),
+            Self::TeleportToSpawn => format!("tp {name} {}", spawn_of(server?, player)?),
+            Self::Gamemode(mode) => format!("gamemode {mode} {name}"),
+        })
+    }
+}
+
+/// The coordinates of the spawn of the world `player` is in
+fn spawn_of(server: &Server, player: &PlayerInfo) -> Option<String> {
+    server.worlds.values().find_map(|world| {
+        let mut found = false;
+        world.players.iter_players(|_, online| {
+            found = online.gameprofile.id == player.uuid;
+            !found
+        });
+        found.then(|| {
+            let spawn = world.spawn_position();
+            format!("{} {} {}", spawn.x, spawn.y, spawn.z)
+        })
+    })
+}
+
+/// The edition a player plays with, Bedrock standing out since those players can't do everything Java players can
+///
+/// Shows the brand the client announced, or the edition until it did.
+pub(super) fn client_label(player: &PlayerInfo, theme: &Theme) -> Span<'static> {
+    let label = player
+        .brand
+        .clone()
+        .unwrap_or_else(|| player.client.name().to_owned());
+    match player.client {
+        Client::Java => label.fg(theme.muted),
+        Client::Bedrock => label.fg(theme.accent),
+    }
+}
+
+/// The actions menu opened on a player
+struct Menu {
+    player: PlayerInfo,
+    state: ListState,
+    /// The reason being typed in, while kicking
+    reason: Option<Input>,
+}
+
+/// The players connected to a server, one of which can be selected to moderate it
+#[derive(Default)]
+pub struct PlayerList {
+    table_state: TableState,
+    menu: Option<Menu>,
+    /// A command picked from the menu, waiting to be confirmed and run
+    picked: Option<String>,
+}
+
+impl PlayerList {
+    /// Handles a key pressed while the players tab is shown, returning whether it was used
+    pub fn handle_key(
+        &mut self,
+        status: &dyn ServerStatus,
+        server: Option<&Server>,
+        event: KeyEvent,
+    ) -> bool {
+        let Some(menu) = &mut self.menu else {
+            match event.code {
+                KeyCode::Up => self.table_state.select_previous(),
+                KeyCode::Down => self.table_state.select_next(),
+                KeyCode::Enter => {
+                    let players = status.players();
+                    let Some(player) = self
+                        .table_state
+                        .selected()
+                        .and_then(|index| players.get(index))
+                    else {
+                        return false;
+                    };
+                    self.menu = Some(Menu {
+                        player: player.clone(),
+                        state: ListState::default().with_selected(Some(0)),
+                        reason: None,
+                    });
+                }
+                _ => return false,
+            }
+            return true;
+        };
+
+        if let Some(reason) = &mut menu.reason {
+            match event.code {
+                KeyCode::Esc => menu.reason = None,
+                KeyCode::Enter => {
+                    self.picked =
+                        Moderation::Kick.command(server, &menu.player, reason.value().trim());
+                    self.menu = None;
+                }
+                _ => {
+                    reason.handle_event(&Event::Key(event));
+                }
+            }
+            return true;
+        }
+
+        match event.code {
+            KeyCode::Esc => self.menu = None,
+            KeyCode::Up => menu.state.select_previous(),
+            KeyCode::Down => menu.state.select_next(),
+            KeyCode::Enter => {
+                let action = ACTIONS[menu.state.selected().unwrap_or_default() % ACTIONS.len()];
+                if action == Moderation::Kick {
+                    menu.reason = Some(Input::default());
+                } else {
+                    self.picked = action.command(server, &menu.player, "");
+                    self.menu = None;
+                }
+            }
+            _ => (),
+        }
+        true
+    }
+
+    /// Takes the command picked from the menu, to be confirmed before it runs
+    pub const fn take_picked(&mut self) -> Option<String> {
+        self.picked.take()
+    }
+
+    /// Renders a table of the players connected to the server, with the menu over it while it is open
+    pub fn render(
+        &mut self,
+        status: &dyn ServerStatus,
+        theme: &Theme,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let players = status.players();
+
+        let header =
+            Row::new(["Name", "Client", "UUID"]).style(Style::new().fg(theme.accent).bold());
+        let rows = players.iter().map(|player| {
+            Row::new([
+                Line::from(player.name.clone()),
+                Line::from(client_label(player, theme)),
+                Line::from(player.uuid.to_string()),
+            ])
+        });
+
+        let hint = if players.is_empty() {
+            ""
+        } else {
+            "Up/Down select, Enter for actions"
+        };
+        StatefulWidget::render(
+            Table::new(
+                rows,
+                [
+                    Constraint::Length(17),
+                    Constraint::Length(12),
+                    Constraint::Fill(1),
+                ],
+            )
+            .header(header)
+            .row_highlight_style(Style::new().reversed())
+            .block(
+                Block::new()
+                    .title(format!("{} players online", players.len()))
+                    .title_bottom(hint.fg(theme.muted)),
+            ),
+            area,
+            buf,
+            &mut self.table_state,
+        );
+
+        let Some(menu) = &mut self.menu else {
+            return;
+        };
+        let block = Block::bordered()
+            .title(menu.player.name.as_str())
+            .border_style(theme.accent);
+        if let Some(reason) = &menu.reason {
+            let popup = area.centered(Constraint::Length(48), Constraint::Length(3));
+            Clear.render(popup, buf);
+            Line::from(vec!["Reason: ".fg(theme.muted), reason.value().into()])
+                .render(block.inner(popup), buf);
+            block.render(popup, buf);
+            return;
+        }
+
+        let popup = area.centered(
+            Constraint::Length(24),
+            Constraint::Length(ACTIONS.len() as u16 + 2),
+        );
+        Clear.render(popup, buf);
+        StatefulWidget::render(
+            List::new(ACTIONS.map(Moderation::label))
+                .block(block)
+                .highlight_style(Style::new().fg(theme.accent).reversed()),
+            popup,
+            buf,
+            &mut menu.state,
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use crate::config::Theme;
+use crate::panels::CustomPanel;
+use crate::plugin::monitor::{self, EventRecord};
+use crate::status;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Row, Table};
+use steel_core::server::Server;
+
+/// Shows the events going through the plugin handlers as they are dispatched
+#[derive(Default)]
+pub struct EventMonitorPanel {
+    /// The events shown while paused
+    paused: Option<Vec<EventRecord>>,
+    /// Only events handled by this plugin are shown
+    filter: Option<String>,
+}
+
+impl EventMonitorPanel {
+    /// Filters the events to the next enabled plugin, back to every event after the last one
+    fn cycle_filter(&mut self) {
+        let plugins = status::plugins();
+        let next = match &self.filter {
+            None => plugins.first(),
+            Some(current) => plugins
+                .iter()
+                .skip_while(|plugin| *plugin != current)
+                .nth(1),
+        };
+        self.filter = next.cloned();
+    }
+}
+
+impl CustomPanel for EventMonitorPanel {
+    fn title(&self) -> &str {
+        "Events"
+    }
+
+    fn handle_key(&mut self, event: KeyEvent) -> bool {
+        if !event.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        match event.code {
+            KeyCode::Char('p') => {
+                self.paused = match self.paused {
+                    Some(_) => None,
+                    None => Some(monitor::recent()),
+                };
+            }
+            KeyCode::Char('f') => self.cycle_filter(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn render(&mut self, _server: &Server, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let events = self.paused.clone().unwrap_or_else(monitor::recent);
+        let rows = events
+            .iter()
+            .filter(|event| {
+                self.filter
+                    .as_ref()
+                    .is_none_or(|filter| event.handlers.contains(filter))
+            })
+            .map(|event| {
+                let outcome = if !event.crashed.is_empty() {
+                    format!("{} crashed", event.crashed.join(", ")).red()
+                } else if event.cancelled {
+                    "cancelled".yellow()
+                } else {
+                    "".into()
+                };
+                Row::new([
+                    Line::from(event.topic),
+                    Line::from(event.handlers.join(", ")),
+                    Line::from(outcome),
+                    Line::from(format!("{:.2?}", event.duration)),
+                ])
+            });
+
+        let mut title = vec!["Ctrl+P pauses, Ctrl+F filters by plugin".fg(theme.muted)];
+        if self.paused.is_some() {
+            title.push(" paused".yellow());
+        }
+        if let Some(filter) = &self.filter {
+            title.push(format!(" only {filter}").fg(theme.accent));
+        }
+
+        Table::new(
+            rows,
+            [
+                Constraint::Length(20),
+                Constraint::Fill(1),
+                Constraint::Length(20),
+                Constraint::Length(10),
+            ],
+        )
+        .header(
+            Row::new(["Event", "Handlers", "Outcome", "Duration"])
+                .style(Style::new().fg(theme.accent).bold()),
+        )
+        .block(Block::new().title(Line::from(title)))
+        .render(area, buf);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::REDRAW;
+use crate::config::Theme;
+use crate::panels::CustomPanel;
+use crate::plugin::{self, repl};
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use std::sync::Arc;
+use steel_core::server::Server;
+use steel_utils::locks::SyncMutex;
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+
+/// Lines of output kept in the REPL
+const MAX_OUTPUT: usize = 500;
+
+/// Calls exported functions of plugins, a developer tool to test plugins without in-game events
+///
+/// While the panel is shown, typing goes to its own input line instead of the console.
+#[derive(Default)]
+pub struct PluginReplPanel {
+    input: Input,
+    /// Shared with the running evaluations, which append their result
+    output: Arc<SyncMutex<Vec<Line<'static>>>>,
+}
+
+impl PluginReplPanel {
+    fn submit(&mut self) {
+        let line = self.input.value_and_reset();
+        if line.trim().is_empty() {
+            return;
+        }
+        push(
+            &self.output,
+            vec![Line::from(format!("repl> {line}")).bold()],
+        );
+
+        let Some(manager) = plugin::manager() else {
+            push(
+                &self.output,
+                vec![Line::from("The plugin system is not running")],
+            );
+            return;
+        };
+        let output = self.output.clone();
+        tokio::spawn(async move {
+            let result = repl::eval(manager, &line).await;
+            push(&output, result.into_iter().map(Line::from).collect());
+        });
+    }
+}
+
+fn push(output: &SyncMutex<Vec<Line<'static>>>, lines: Vec<Line<'static>>) {
+    let mut output = output.lock();
+    output.extend(lines);
+    let excess = output.len().saturating_sub(MAX_OUTPUT);
+    output.drain(..excess);
+    REDRAW.notify_one();
+}
+
+impl CustomPanel for PluginReplPanel {
+    fn title(&self) -> &str {
+        "REPL"
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+
+    fn handle_key(&mut self, event: KeyEvent) -> bool {
+        // keybinds with modifiers and panel switching keep working
+        if event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+        match event.code {
+            KeyCode::Enter => self.submit(),
+            KeyCode::Char(_)
+            | KeyCode::Backspace
+            | KeyCode::Delete
+            | KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Home
+            | KeyCode::End => {
+                self.input.handle_event(&Event::Key(event));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn render(&mut self, _server: &Server, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let [output_area, input_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(2)]).areas(area);
+
+        let output = self.output.lock();
+        let lines: Vec<Line> = if output.is_empty() {
+            vec![
+                Line::from("<plugin> lists the exported functions of a plugin").fg(theme.muted),
+                Line::from("<plugin> <function> [args...] calls one").fg(theme.muted),
+            ]
+        } else {
+            output.clone()
+        };
+        // keeps the newest output in view
+        let scroll = lines.len().saturating_sub(output_area.height.into());
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll as u16, 0))
+            .render(output_area, buf);
+
+        let block = Block::new().borders(Borders::TOP).fg(theme.muted);
+        let inner = block.inner(input_area);
+        block.render(input_area, buf);
+        let cursor = self.input.cursor();
+        let value = self.input.value();
+        let (before, after) = value.split_at(
+            value
+                .char_indices()
+                .nth(cursor)
+                .map_or(value.len(), |(index, _)| index),
+        );
+        let mut after = after.chars();
+        let at_cursor = after.next().map_or_else(|| " ".to_owned(), String::from);
+        Line::from(vec![
+            "repl> ".fg(theme.prompt),
+            before.into(),
+            at_cursor.reversed(),
+            after.as_str().to_owned().into(),
+        ])
+        .render(inner, buf);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::config::Theme;
+use crate::panels::CustomPanel;
+use crate::plugin::{self, PluginState, profile, settings, storage};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Row, Table, TableState};
+use steel_core::server::Server;
+
+/// Height of the config and storage views below the plugin list, including their border
+const CONFIG_HEIGHT: u16 = 10;
+
+/// Lists the loaded plugins with their state and resource usage, and the config and stored values of the
+/// selected one
+#[derive(Default)]
+pub struct PluginsPanel {
+    table_state: TableState,
+}
+
+impl PluginsPanel {
+    /// Renders keys and their values with a hint on how to change them
+    fn render_entries(
+        title: String,
+        hint: String,
+        entries: anyhow::Result<Vec<(String, String)>>,
+        theme: &Theme,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let block = Block::new()
+            .borders(Borders::TOP)
+            .title(title)
+            .title_bottom(hint.fg(theme.muted));
+        let lines: Vec<Line> = match entries {
+            Ok(entries) if entries.is_empty() => vec!["Empty".fg(theme.muted).into()],
+            Ok(entries) => entries
+                .into_iter()
+                .map(|(key, value)| {
+                    Line::from(vec![key.fg(theme.accent), " = ".into(), value.into()])
+                })
+                .collect(),
+            Err(err) => vec![format!("{err:#}").red().into()],
+        };
+        Text::from(lines).render(block.inner(area), buf);
+        block.render(area, buf);
+    }
+}
+
+impl CustomPanel for PluginsPanel {
+    fn title(&self) -> &str {
+        "Plugins"
+    }
+
+    fn handle_key(&mut self, event: KeyEvent) -> bool {
+        match event.code {
+            KeyCode::Up => self.table_state.select_previous(),
+            KeyCode::Down => self.table_state.select_next(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn render(&mut self, _server: &Server, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let plugins = plugin::manager()
+            .map(|manager| manager.list())
+            .unwrap_or_default();
+
+        let header = Row::new([
+            "Name", "Version", "State", "Fuel", "Memory", "Time", "Problem",
+        ])
+        .style(Style::new().fg(theme.accent).bold());
+        let rows = plugins.iter().map(|info| {
+            let state = match info.state {
+                PluginState::Enabled => info.state.to_string().into(),
+                PluginState::Disabled => info.state.to_string().fg(theme.muted),
+                PluginState::Crashed => info.state.to_string().red(),
+                PluginState::Blocked => info.state.to_string().yellow(),
+            };
+            Row::new([
+                Line::from(info.name.clone()),
+                Line::from(info.version.clone()),
+                Line::from(state),
+                Line::from(
+                    info.fuel_consumed
+                        .map_or_else(|| "-".to_owned(), |fuel| fuel.to_string()),
+                ),
+                Line::from(format!("{} MiB", info.memory_bytes / 1024 / 1024)),
+                Line::from(format!("{:.1?}", profile::total(&info.name))),
+                Line::from(info.problem.clone().unwrap_or_default()).fg(theme.muted),
+            ])
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(10),
+                Constraint::Length(9),
+                Constraint::Length(14),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::new().reversed())
+        .block(Block::new().title(format!("{} plugins loaded", plugins.len())));
+
+        if plugins.is_empty() {
+            Widget::render(table, area, buf);
+            return;
+        }
+
+        // keeps the selection on a plugin when plugins went away
+        let selected = self
+            .table_state
+            .selected()
+            .unwrap_or_default()
+            .min(plugins.len() - 1);
+        self.table_state.select(Some(selected));
+
+        let [table_area, config_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(CONFIG_HEIGHT)]).areas(area);
+        StatefulWidget::render(table, table_area, buf, &mut self.table_state);
+        let name = &plugins[selected].name;
+        let [config_area, storage_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
+                .spacing(1)
+                .areas(config_area);
+        Self::render_entries(
+            format!("Config of {name}"),
+            format!("plugins config {name} <key> <value> changes a setting"),
+            settings::entries(name),
+            theme,
+            config_area,
+            buf,
+        );
+        Self::render_entries(
+            format!("Stored by {name}"),
+            format!("plugins store {name} [set <key> <value> | remove <key>]"),
+            storage::entries(name),
+            theme,
+            storage_area,
+            buf,
+        );
+    }
+}
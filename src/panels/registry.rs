@@ -0,0 +1,130 @@
+use crate::config::Theme;
+use crate::registry::{self, Registry};
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, List, ListState, Paragraph, Wrap};
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+
+/// Browses the items, blocks, biomes and recipes the server knows, to look up their keys and properties
+///
+/// While the tab is shown, typing goes into its search line and Tab switches between the registries.
+#[derive(Default)]
+pub struct RegistryView {
+    search: Input,
+    /// The registry shown, every registry if `None`
+    registry: Option<Registry>,
+    list_state: ListState,
+    /// Where the cursor of the search line was last drawn
+    cursor: Position,
+}
+
+impl RegistryView {
+    /// Handles a key pressed while the registry tab is shown, returning whether it was used
+    pub fn handle_key(&mut self, event: KeyEvent) -> bool {
+        // keybinds with modifiers and panel switching keep working
+        if event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+        match event.code {
+            KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Down => self.list_state.select_next(),
+            KeyCode::Tab => {
+                let index = self
+                    .registry
+                    .and_then(|registry| Registry::ALL.iter().position(|r| *r == registry));
+                self.registry = match index {
+                    None => Some(Registry::ALL[0]),
+                    Some(index) => Registry::ALL.get(index + 1).copied(),
+                };
+                self.list_state.select_first();
+            }
+            KeyCode::Char(_)
+            | KeyCode::Backspace
+            | KeyCode::Delete
+            | KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Home
+            | KeyCode::End => {
+                self.search.handle_event(&Event::Key(event));
+                self.list_state.select_first();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Where the cursor of the search line is, as of the last render
+    pub const fn cursor(&self) -> Position {
+        self.cursor
+    }
+
+    /// Renders the entries matching the search, with the details of the selected one next to them
+    pub fn render(&mut self, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let [search_area, body_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+        let block = Block::new()
+            .borders(Borders::BOTTOM)
+            .border_style(theme.muted);
+        let prompt = "search> ";
+        let inner = block.inner(search_area);
+        self.cursor = Position::new(
+            inner.x + (prompt.len() + self.search.visual_cursor()) as u16,
+            inner.y,
+        );
+        Line::from(vec![prompt.fg(theme.prompt), self.search.value().into()]).render(inner, buf);
+        block.render(search_area, buf);
+
+        let entries = registry::search(self.registry, self.search.value());
+        let [list_area, details_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Fill(1)]).areas(body_area);
+        let shown = self.registry.map_or("all registries", Registry::name);
+        StatefulWidget::render(
+            List::new(entries.iter().map(|entry| {
+                Line::from(vec![
+                    format!("{:<8} ", entry.registry.name()).fg(theme.muted),
+                    entry.key.clone().into(),
+                ])
+            }))
+            .highlight_style(Style::new().reversed())
+            .block(
+                Block::new()
+                    .title(format!("{} entries in {shown}", entries.len()))
+                    .title_bottom("Type to search, Tab switches the registry".fg(theme.muted)),
+            ),
+            list_area,
+            buf,
+            &mut self.list_state,
+        );
+
+        let Some(entry) = self
+            .list_state
+            .selected()
+            .and_then(|index| entries.get(index))
+        else {
+            return;
+        };
+        let mut lines = vec![
+            Line::from(entry.key.clone()).fg(theme.accent).bold(),
+            Line::from(format!("{} id {}", entry.registry.name(), entry.id)).fg(theme.muted),
+            Line::from(""),
+        ];
+        lines.extend(
+            entry
+                .details
+                .iter()
+                .map(|detail| Line::from(detail.clone())),
+        );
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::new()
+                    .borders(Borders::LEFT)
+                    .border_style(theme.muted),
+            )
+            .render(details_area, buf);
+    }
+}
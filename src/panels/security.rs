@@ -0,0 +1,111 @@
+use crate::config::Theme;
+use crate::security::{self, PeerInfo, PeerState};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Row, Table, TableState};
+use std::time::Duration;
+
+/// How long `b` blocks the selected address
+const BLOCK_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// The connection attempts the network layer counted, with the addresses it throttles or that are blocked
+#[derive(Default)]
+pub struct SecurityView {
+    table_state: TableState,
+}
+
+impl SecurityView {
+    fn selected(&self) -> Option<PeerInfo> {
+        let index = self.table_state.selected()?;
+        security::stats().peers.into_iter().nth(index)
+    }
+
+    /// Handles a key pressed while the security tab is shown, returning whether it was used
+    pub fn handle_key(&mut self, event: KeyEvent) -> bool {
+        match event.code {
+            KeyCode::Up => self.table_state.select_previous(),
+            KeyCode::Down => self.table_state.select_next(),
+            KeyCode::Char('b') => {
+                if let Some(peer) = self.selected() {
+                    security::block(peer.ip, BLOCK_DURATION);
+                }
+            }
+            KeyCode::Char('u') | KeyCode::Delete => {
+                if let Some(peer) = self.selected() {
+                    security::unblock(peer.ip);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Renders the connection counters with every address that connected within the last minute
+    pub fn render(&mut self, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let stats = security::stats();
+        let [summary_area, table_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+
+        let refused = stats
+            .peers
+            .iter()
+            .filter(|peer| peer.state != PeerState::Allowed)
+            .count();
+        let block = Block::new()
+            .borders(Borders::BOTTOM)
+            .border_style(theme.muted);
+        Line::from(vec![
+            format!("{:.1}", stats.rate).fg(theme.accent).bold(),
+            " attempts/s   ".into(),
+            stats.total_attempts.to_string().bold(),
+            " connections   ".into(),
+            stats.total_rejected.to_string().bold(),
+            " rejected handshakes   ".into(),
+            stats.total_refused.to_string().bold(),
+            " refused   ".into(),
+            refused.to_string().bold(),
+            " addresses throttled or blocked".into(),
+        ])
+        .render(block.inner(summary_area), buf);
+        block.render(summary_area, buf);
+
+        let header = Row::new(["Address", "Last minute", "Rejected", "State"])
+            .style(Style::new().fg(theme.accent).bold());
+        let rows = stats.peers.iter().map(|peer| {
+            let state = match peer.state {
+                PeerState::Allowed => "allowed".fg(theme.muted),
+                PeerState::Throttled(left) => format!("throttled for {}s", left.as_secs()).yellow(),
+                PeerState::Blocked(left) => {
+                    format!("blocked for {}m", left.as_secs().div_ceil(60)).red()
+                }
+            };
+            Row::new([
+                Line::from(peer.ip.to_string()),
+                Line::from(peer.attempts.to_string()),
+                Line::from(peer.rejected.to_string()),
+                Line::from(state),
+            ])
+        });
+        StatefulWidget::render(
+            Table::new(
+                rows,
+                [
+                    Constraint::Length(40),
+                    Constraint::Length(12),
+                    Constraint::Length(9),
+                    Constraint::Fill(1),
+                ],
+            )
+            .header(header)
+            .row_highlight_style(Style::new().reversed())
+            .block(
+                Block::new().title_bottom(
+                    "b blocks the address for an hour, u unblocks it".fg(theme.muted),
+                ),
+            ),
+            table_area,
+            buf,
+            &mut self.table_state,
+        );
+    }
+}
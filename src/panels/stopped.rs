@@ -0,0 +1,107 @@
+use crate::config::Theme;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Clear, List, ListState};
+
+/// Something to do once the server has stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopChoice {
+    /// Starts the server again with its configuration read anew
+    Restart,
+    /// Writes the log of the server into a file
+    ExportLog,
+    /// Shows the error the server stopped with
+    Error,
+    /// Stops every other server and exits
+    Exit,
+}
+
+impl StopChoice {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Restart => "Restart",
+            Self::ExportLog => "Export log",
+            Self::Error => "Show the error",
+            Self::Exit => "Exit",
+        }
+    }
+}
+
+/// The menu shown over a server that has stopped
+pub struct StoppedMenu {
+    state: ListState,
+    /// Whether Esc put the menu away to read the log
+    hidden: bool,
+}
+
+impl Default for StoppedMenu {
+    fn default() -> Self {
+        Self {
+            state: ListState::default().with_selected(Some(0)),
+            hidden: false,
+        }
+    }
+}
+
+impl StoppedMenu {
+    /// The choices of the menu, with the error only if the server stopped with one
+    fn choices(failed: bool) -> Vec<StopChoice> {
+        [
+            StopChoice::Restart,
+            StopChoice::ExportLog,
+            StopChoice::Error,
+            StopChoice::Exit,
+        ]
+        .into_iter()
+        .filter(|choice| failed || *choice != StopChoice::Error)
+        .collect()
+    }
+
+    /// Handles a key pressed while the server is stopped, returning whether it was used and the picked choice
+    pub fn handle_key(&mut self, event: KeyEvent, failed: bool) -> (bool, Option<StopChoice>) {
+        if event.code == KeyCode::Esc {
+            self.hidden = !self.hidden;
+            return (true, None);
+        }
+        if self.hidden {
+            return (false, None);
+        }
+        let choices = Self::choices(failed);
+        match event.code {
+            KeyCode::Up => self.state.select_previous(),
+            KeyCode::Down => self.state.select_next(),
+            KeyCode::Enter => {
+                let index = self.state.selected().unwrap_or_default();
+                return (true, choices.get(index.min(choices.len() - 1)).copied());
+            }
+            _ => return (false, None),
+        }
+        (true, None)
+    }
+
+    /// Renders the menu over `area` unless it was put away
+    pub fn render(&mut self, failed: bool, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        if self.hidden {
+            return;
+        }
+        let choices = Self::choices(failed);
+        let area = area.centered(
+            Constraint::Length(32),
+            Constraint::Length(choices.len() as u16 + 2),
+        );
+        Clear.render(area, buf);
+        StatefulWidget::render(
+            List::new(choices.iter().map(|choice| choice.label()))
+                .block(
+                    Block::bordered()
+                        .title("The server has stopped")
+                        .title_bottom("Esc shows the log".fg(theme.muted))
+                        .border_style(theme.accent),
+                )
+                .highlight_style(Style::new().fg(theme.accent).reversed()),
+            area,
+            buf,
+            &mut self.state,
+        );
+    }
+}
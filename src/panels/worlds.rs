@@ -0,0 +1,165 @@
+use crate::config::Theme;
+use crate::worlds::Weather;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Clear, List, ListState, Row, Table, TableState};
+use steel_core::server::Server;
+
+/// The actions of the menu opened on a world, as the arguments of the `world` command
+const ACTIONS: [(&str, &str); 10] = [
+    ("Set time to day", "time day"),
+    ("Set time to night", "time night"),
+    ("Clear weather", "weather clear"),
+    ("Start rain", "weather rain"),
+    ("Start thunder", "weather thunder"),
+    ("Difficulty peaceful", "difficulty peaceful"),
+    ("Difficulty easy", "difficulty easy"),
+    ("Difficulty normal", "difficulty normal"),
+    ("Difficulty hard", "difficulty hard"),
+    ("Inspect spawn", ""),
+];
+
+/// The actions menu opened on a world
+struct Menu {
+    world: String,
+    state: ListState,
+}
+
+/// The worlds of a server, one of which can be selected to change its time, weather or difficulty
+#[derive(Default)]
+pub struct WorldList {
+    table_state: TableState,
+    menu: Option<Menu>,
+    /// A command picked from the menu, waiting to be confirmed and run
+    picked: Option<String>,
+}
+
+impl WorldList {
+    /// Handles a key pressed while the worlds tab is shown, returning whether it was used
+    pub fn handle_key(&mut self, server: &Server, event: KeyEvent) -> bool {
+        let Some(menu) = &mut self.menu else {
+            match event.code {
+                KeyCode::Up => self.table_state.select_previous(),
+                KeyCode::Down => self.table_state.select_next(),
+                KeyCode::Enter => {
+                    let Some(world) = self
+                        .table_state
+                        .selected()
+                        .and_then(|index| server.worlds.keys().nth(index))
+                    else {
+                        return false;
+                    };
+                    self.menu = Some(Menu {
+                        world: world.to_string(),
+                        state: ListState::default().with_selected(Some(0)),
+                    });
+                }
+                _ => return false,
+            }
+            return true;
+        };
+
+        match event.code {
+            KeyCode::Esc => self.menu = None,
+            KeyCode::Up => menu.state.select_previous(),
+            KeyCode::Down => menu.state.select_next(),
+            KeyCode::Enter => {
+                let (_, args) = ACTIONS[menu.state.selected().unwrap_or_default() % ACTIONS.len()];
+                self.picked = if args.is_empty() {
+                    server.worlds.iter().find_map(|(name, world)| {
+                        (name.to_string() == menu.world).then(|| {
+                            let spawn = world.spawn_position();
+                            format!("inspect {} {} {} {name}", spawn.x, spawn.y, spawn.z)
+                        })
+                    })
+                } else {
+                    Some(format!("world {} {args}", menu.world))
+                };
+                self.menu = None;
+            }
+            _ => (),
+        }
+        true
+    }
+
+    /// Takes the command picked from the menu, to be confirmed before it runs
+    pub const fn take_picked(&mut self) -> Option<String> {
+        self.picked.take()
+    }
+
+    /// Renders a table of the worlds of `server`, with the menu over it while it is open
+    pub fn render(&mut self, server: &Server, theme: &Theme, area: Rect, buf: &mut Buffer) {
+        let header = Row::new([
+            "Name",
+            "Seed",
+            "Spawn",
+            "Time",
+            "Weather",
+            "Difficulty",
+            "Chunks",
+        ])
+        .style(Style::new().fg(theme.accent).bold());
+        let rows = server.worlds.iter().map(|(name, world)| {
+            let spawn = world.spawn_position();
+            Row::new([
+                name.to_string(),
+                world.seed().to_string(),
+                format!("{} {} {}", spawn.x, spawn.y, spawn.z),
+                format!(
+                    "day {}, {}",
+                    world.day_time() / 24000,
+                    world.day_time() % 24000
+                ),
+                Weather::of(world).name().to_owned(),
+                world.difficulty().to_string(),
+                world.chunk_map.loaded_chunk_count().to_string(),
+            ])
+        });
+
+        StatefulWidget::render(
+            Table::new(
+                rows,
+                [
+                    Constraint::Fill(1),
+                    Constraint::Length(20),
+                    Constraint::Length(16),
+                    Constraint::Length(16),
+                    Constraint::Length(8),
+                    Constraint::Length(10),
+                    Constraint::Length(8),
+                ],
+            )
+            .header(header)
+            .row_highlight_style(Style::new().reversed())
+            .block(
+                Block::new()
+                    .title(format!("{} worlds", server.worlds.len()))
+                    .title_bottom("Up/Down select, Enter for actions".fg(theme.muted)),
+            ),
+            area,
+            buf,
+            &mut self.table_state,
+        );
+
+        let Some(menu) = &mut self.menu else {
+            return;
+        };
+        let popup = area.centered(
+            Constraint::Length(28),
+            Constraint::Length(ACTIONS.len() as u16 + 2),
+        );
+        Clear.render(popup, buf);
+        StatefulWidget::render(
+            List::new(ACTIONS.map(|(label, _)| label))
+                .block(
+                    Block::bordered()
+                        .title(menu.world.as_str())
+                        .border_style(theme.accent),
+                )
+                .highlight_style(Style::new().fg(theme.accent).reversed()),
+            popup,
+            buf,
+            &mut menu.state,
+        );
+    }
+}
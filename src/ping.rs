@@ -0,0 +1,230 @@
+use crate::logger::ServerLog;
+use crate::{reload, text_preview};
+use anyhow::{Context, bail};
+use serde::Deserialize;
+use serde_json::Value;
+use std::net::Ipv4Addr;
+use std::str;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time;
+use tracing::{info, warn};
+
+const USAGE: &str = "usage: ping self";
+
+/// How long the whole exchange may take before the server counts as not serving
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest status response read, vanilla caps the JSON at 32767 characters
+const MAX_PACKET: usize = 4 * 32767 + 8;
+
+/// Asks for whatever version the server speaks, like server list pingers do
+const ANY_PROTOCOL: i32 = -1;
+
+const HANDSHAKE: i32 = 0x00;
+const STATUS_REQUEST: i32 = 0x00;
+const PING_REQUEST: i32 = 0x01;
+/// Asks the server to switch to the status state in the handshake
+const NEXT_STATE_STATUS: i32 = 1;
+
+#[derive(Deserialize)]
+struct Status {
+    version: Version,
+    players: Option<Players>,
+    description: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct Version {
+    name: String,
+    protocol: i32,
+}
+
+#[derive(Deserialize)]
+struct Players {
+    online: u32,
+    max: u32,
+}
+
+fn write_var_int(buf: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7F == 0 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+}
+
+async fn read_var_int(stream: &mut TcpStream) -> anyhow::Result<i32> {
+    let mut value = 0;
+    for shift in (0..35).step_by(7) {
+        let byte = stream.read_u8().await?;
+        value |= i32::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("VarInt is too long")
+}
+
+/// Sends a packet with the id `id` and the fields in `data`, prefixed with its length
+async fn write_packet(stream: &mut TcpStream, id: i32, data: &[u8]) -> anyhow::Result<()> {
+    let mut body = Vec::with_capacity(data.len() + 5);
+    write_var_int(&mut body, id);
+    body.extend_from_slice(data);
+    let mut packet = Vec::with_capacity(body.len() + 5);
+    write_var_int(&mut packet, body.len() as i32);
+    packet.extend_from_slice(&body);
+    stream.write_all(&packet).await?;
+    Ok(())
+}
+
+/// Reads a packet, returning its id and its fields
+async fn read_packet(stream: &mut TcpStream) -> anyhow::Result<(i32, Vec<u8>)> {
+    let length = read_var_int(stream).await?;
+    let length = usize::try_from(length)
+        .ok()
+        .filter(|length| (1..=MAX_PACKET).contains(length))
+        .with_context(|| format!("invalid packet length {length}"))?;
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).await?;
+
+    // the id is a VarInt too, but every id of the status state fits into one byte
+    let id = i32::from(body[0]);
+    body.remove(0);
+    Ok((id, body))
+}
+
+/// Splits the VarInt length off a string field, returning the string
+fn read_string(data: &[u8]) -> anyhow::Result<&str> {
+    let mut length = 0usize;
+    for (index, byte) in data.iter().enumerate().take(5) {
+        length |= usize::from(byte & 0x7F) << (7 * index);
+        if byte & 0x80 == 0 {
+            let string = data
+                .get(index + 1..index + 1 + length)
+                .context("the string is longer than the packet")?;
+            return str::from_utf8(string).context("the string is not UTF-8");
+        }
+    }
+    bail!("invalid string length")
+}
+
+/// What the server answered to the ping
+struct Pong {
+    status: Status,
+    /// How long the status response took, which includes building it
+    status_time: Duration,
+    /// Round trip of the ping packet alone
+    latency: Duration,
+}
+
+/// Connects to the server on `port` like the server list does, asking for its status and pinging it
+async fn ping(port: u16) -> anyhow::Result<Pong> {
+    let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+        .await
+        .with_context(|| format!("failed to connect to port {port}"))?;
+    stream.set_nodelay(true)?;
+
+    let host = Ipv4Addr::LOCALHOST.to_string();
+    let mut handshake = Vec::new();
+    write_var_int(&mut handshake, ANY_PROTOCOL);
+    write_var_int(&mut handshake, host.len() as i32);
+    handshake.extend_from_slice(host.as_bytes());
+    handshake.extend_from_slice(&port.to_be_bytes());
+    write_var_int(&mut handshake, NEXT_STATE_STATUS);
+    write_packet(&mut stream, HANDSHAKE, &handshake).await?;
+
+    let started = Instant::now();
+    write_packet(&mut stream, STATUS_REQUEST, &[]).await?;
+    let (id, data) = read_packet(&mut stream).await?;
+    if id != STATUS_REQUEST {
+        bail!("expected a status response, got packet {id:#04x}");
+    }
+    let status_time = started.elapsed();
+    let status: Status =
+        serde_json::from_str(read_string(&data)?).context("the status response is not valid")?;
+
+    let payload = started.elapsed().as_millis() as i64;
+    let started = Instant::now();
+    write_packet(&mut stream, PING_REQUEST, &payload.to_be_bytes()).await?;
+    let (id, data) = read_packet(&mut stream).await?;
+    let latency = started.elapsed();
+    if id != PING_REQUEST || data != payload.to_be_bytes() {
+        bail!("the server answered the ping with something else");
+    }
+
+    Ok(Pong {
+        status,
+        status_time,
+        latency,
+    })
+}
+
+/// The answer of the server as lines of output
+fn report(port: u16, pong: Pong) -> Vec<String> {
+    let Pong {
+        status,
+        status_time,
+        latency,
+    } = pong;
+    let mut output = vec![format!(
+        "Port {port} is serving, {} (protocol {})",
+        status.version.name, status.version.protocol
+    )];
+    if let Some(description) = status.description {
+        // the MOTD is a text component, shown without its colors
+        let motd = text_preview::preview(&description.to_string())
+            .map(|text| {
+                text.lines
+                    .iter()
+                    .map(|line| {
+                        line.spans
+                            .iter()
+                            .map(|span| span.content.as_ref())
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" / ")
+            })
+            .unwrap_or_else(|_| description.to_string());
+        output.push(format!("MOTD: {motd}"));
+    }
+    if let Some(players) = status.players {
+        output.push(format!("Players: {}/{}", players.online, players.max));
+    }
+    output.push(format!(
+        "Status in {status_time:.1?}, ping latency {latency:.1?}"
+    ));
+    output
+}
+
+/// Runs the `ping self` console command, returning its output
+///
+/// `ping self` connects to the server like the server list does, asking for its status and measuring the latency of
+/// a ping. It checks that the listener actually serves, the answer is logged once it arrives.
+pub fn command(args: &str) -> anyhow::Result<Vec<String>> {
+    if args != "self" {
+        bail!(USAGE);
+    }
+    let port = reload::server_config()?.server_port;
+    let log = ServerLog::current();
+    tokio::spawn(async move {
+        match time::timeout(TIMEOUT, ping(port)).await {
+            Ok(Ok(pong)) => log.scope(|| {
+                for line in report(port, pong) {
+                    info!("{line}");
+                }
+            }),
+            Ok(Err(err)) => log.scope(|| warn!("Pinging port {port} failed: {err:#}")),
+            Err(_) => log.scope(|| {
+                warn!("Port {port} didn't answer the ping within {TIMEOUT:?}");
+            }),
+        }
+    });
+    Ok(vec![format!("Pinging the server on port {port}...")])
+}
@@ -0,0 +1,72 @@
+use serde::Deserialize;
+use std::sync::LazyLock;
+use steel_utils::locks::SyncMutex;
+
+/// Commands registered by plugins in the order they were registered in
+static COMMANDS: LazyLock<SyncMutex<Vec<PluginCommand>>> =
+    LazyLock::new(|| SyncMutex::new(Vec::new()));
+
+/// A console command registered by a plugin
+#[derive(Debug, Clone)]
+pub struct PluginCommand {
+    /// The plugin handling the command
+    pub plugin: String,
+    /// What the command is called in the console
+    pub name: String,
+    /// Hints for the arguments in order, like `<player>` or `[reason]`
+    pub args: Vec<String>,
+    /// What the command does, shown by `help`
+    pub description: String,
+}
+
+/// A command sent by a plugin through the `register_command` host call, encoded as MessagePack
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommandRegistration {
+    /// What the command is called in the console
+    pub name: String,
+    /// Hints for the arguments in order
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// What the command does
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Makes the command `name` of `plugin` known to completion and `help`
+pub fn register_command(plugin: &str, name: &str, args: Vec<String>, description: &str) {
+    let command = PluginCommand {
+        plugin: plugin.to_owned(),
+        name: name.to_owned(),
+        args,
+        description: description.to_owned(),
+    };
+    let mut commands = COMMANDS.lock();
+    commands.retain(|registered| registered.name != name);
+    commands.push(command);
+}
+
+/// Forgets every command of `plugin`, done when it gets disabled
+pub fn remove(plugin: &str) {
+    COMMANDS.lock().retain(|command| command.plugin != plugin);
+}
+
+/// Every command registered by an enabled plugin
+pub fn all() -> Vec<PluginCommand> {
+    COMMANDS.lock().clone()
+}
+
+/// A line per plugin command, appended to the output of `help`
+pub fn help() -> Vec<String> {
+    COMMANDS
+        .lock()
+        .iter()
+        .map(|command| {
+            let mut usage = command.name.clone();
+            for arg in &command.args {
+                usage.push(' ');
+                usage.push_str(arg);
+            }
+            format!("/{usage} - {} ({})", command.description, command.plugin)
+        })
+        .collect()
+}
@@ -0,0 +1,138 @@
+use super::PluginManager;
+use super::monitor::{self, EventRecord};
+use super::profile;
+use flume::Receiver;
+use std::sync::Arc;
+use std::time::Instant;
+use steel_core::PluginApi;
+use steel_core::player::Player;
+use steel_host::interface::objects;
+use steel_plugin_sdk::event::{
+    BlockBreakEvent, BlockPlaceEvent, PlayerChatEvent, PlayerJoinEvent, PlayerLeaveEvent,
+    ServerTickEvent, Topic,
+};
+use steel_plugin_sdk::objects::Handle;
+use tokio::sync::oneshot;
+use tokio::time;
+use tracing::error;
+
+/// Forwards the events of the server to the enabled plugins until the server stops sending them
+pub fn spawn(manager: Arc<PluginManager>, api: Receiver<PluginApi>) {
+    tokio::spawn(async move {
+        while let Ok(event) = api.recv_async().await {
+            forward(&manager, event).await;
+        }
+    });
+}
+
+async fn forward(manager: &PluginManager, event: PluginApi) {
+    match event {
+        PluginApi::PlayerJoinEvent(player) => {
+            dispatch_with_player(manager, player, |player| PlayerJoinEvent { player }).await;
+        }
+        PluginApi::PlayerLeaveEvent(player) => {
+            dispatch_with_player(manager, player, |player| PlayerLeaveEvent { player }).await;
+        }
+        PluginApi::PlayerChatEvent(player, message, reply) => {
+            let cancelled = dispatch_with_player(manager, player, |player| PlayerChatEvent {
+                player,
+                message,
+            })
+            .await;
+            allow(reply, cancelled);
+        }
+        PluginApi::BlockBreakEvent(player, position, reply) => {
+            let cancelled = dispatch_with_player(manager, player, |player| BlockBreakEvent {
+                player,
+                position,
+            })
+            .await;
+            allow(reply, cancelled);
+        }
+        PluginApi::BlockPlaceEvent(player, position, block, reply) => {
+            let cancelled = dispatch_with_player(manager, player, |player| BlockPlaceEvent {
+                player,
+                position,
+                block,
+            })
+            .await;
+            allow(reply, cancelled);
+        }
+        PluginApi::ServerTickEvent(tick) => {
+            dispatch(manager, &ServerTickEvent { tick }).await;
+        }
+    }
+}
+
+/// Dispatches the event made by `make` with `player` registered as an object while it is handled
+///
+/// Returns whether a handler cancelled the event, the server waiting on the answer gets it even if the player couldn't
+/// be unregistered again.
+async fn dispatch_with_player<E: Topic>(
+    manager: &PluginManager,
+    player: Arc<Player>,
+    make: impl FnOnce(Handle) -> E,
+) -> bool {
+    let state = &manager.host().state;
+    let key = state
+        .register_object_handler(objects::make_player_handler(player))
+        .await;
+    let cancelled = dispatch(manager, &make(Handle::from_raw(key))).await;
+    if let Err(err) = state.unregister_object_handler(key).await {
+        error!("Failed to unregister the player of an event from the plugins: {err:#}");
+    }
+    cancelled
+}
+
+/// Dispatches `event` to the handlers of every enabled plugin, disabling the plugins whose handler fails
+///
+/// Returns whether a handler cancelled the event.
+async fn dispatch<E: Topic>(manager: &PluginManager, event: &E) -> bool {
+    let topic = monitor::topic::<E>();
+    let registry = manager.host().state.handler_registry.read().await;
+    let handlers = registry.subscribers::<E>();
+    let started = Instant::now();
+    let mut cancelled = false;
+    let mut crashed = Vec::new();
+    // one plugin at a time, so the time and fuel each plugin takes can be told apart
+    for plugin in &handlers {
+        let fuel_before = manager.fuel_consumed(plugin);
+        let handler_started = Instant::now();
+        let handled = registry.dispatch_topic_to(plugin, event);
+        let dispatched = match manager.watchdog() {
+            Some(deadline) => match time::timeout(deadline, handled).await {
+                Ok(dispatched) => dispatched.map_err(|err| err.source),
+                Err(elapsed) => Err(anyhow::Error::new(elapsed)
+                    .context("the handler was stuck in a host call past its deadline")),
+            },
+            None => handled.await.map_err(|err| err.source),
+        };
+        let fuel = manager
+            .fuel_consumed(plugin)
+            .zip(fuel_before)
+            .map(|(after, before)| after.saturating_sub(before));
+        profile::record(plugin, topic, handler_started.elapsed(), fuel);
+
+        match dispatched {
+            Ok(handled) => cancelled |= handled,
+            // a handler that crashed can not cancel the event
+            Err(err) => crashed.push((plugin.clone(), err)),
+        }
+    }
+    let mut record = EventRecord::new(topic, handlers, started.elapsed());
+    drop(registry);
+
+    for (plugin, err) in crashed {
+        manager.isolate(&plugin, err).await;
+        record.crashed.push(plugin);
+    }
+    record.cancelled = cancelled;
+    monitor::record(record);
+    cancelled
+}
+
+/// Tells the server whether it may go on with the action behind an event
+fn allow(reply: oneshot::Sender<bool>, cancelled: bool) {
+    // the server stopped waiting if this fails
+    let _ = reply.send(!cancelled);
+}
@@ -0,0 +1,201 @@
+use super::commands::{self, CommandRegistration};
+use super::{panels, plugin_log, settings, storage};
+use anyhow::{Context, bail};
+use steel_host::PluginContext;
+use steel_host::wasmtime::{Caller, Extern, Linker, Memory};
+use tracing::{Level, warn};
+
+/// The module plugins import the host calls of the tui from
+const MODULE: &str = "steel_tui";
+
+/// What a host call returns when it failed, the error is logged
+const FAILED: i32 = -1;
+
+/// What `store_get` returns when nothing is stored under the key
+const NOT_STORED: i64 = -2;
+
+/// The memory of the plugin making a host call
+fn memory(caller: &mut Caller<'_, PluginContext>) -> anyhow::Result<Memory> {
+    match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => Ok(memory),
+        _ => bail!("the plugin exports no memory"),
+    }
+}
+
+/// Copies the `len` bytes at `ptr` out of the memory of the plugin making a host call
+fn read(caller: &mut Caller<'_, PluginContext>, ptr: u32, len: u32) -> anyhow::Result<Vec<u8>> {
+    let memory = memory(caller)?;
+    let start = ptr as usize;
+    let end = start
+        .checked_add(len as usize)
+        .context("the pointer is out of bounds")?;
+    memory
+        .data(&caller)
+        .get(start..end)
+        .map(<[u8]>::to_vec)
+        .context("the pointer is out of bounds")
+}
+
+/// Reads the UTF-8 string of `len` bytes at `ptr` out of the memory of the plugin making a host call
+fn read_str(caller: &mut Caller<'_, PluginContext>, ptr: u32, len: u32) -> anyhow::Result<String> {
+    String::from_utf8(read(caller, ptr, len)?).context("the string is not UTF-8")
+}
+
+/// Copies `data` into the buffer of `cap` bytes at `ptr` if it fits, returning its length
+///
+/// A plugin whose buffer was too small calls again with one of the returned length.
+fn write(
+    caller: &mut Caller<'_, PluginContext>,
+    ptr: u32,
+    cap: u32,
+    data: &[u8],
+) -> anyhow::Result<i64> {
+    if data.len() <= cap as usize {
+        memory(caller)?
+            .write(caller, ptr as usize, data)
+            .context("the pointer is out of bounds")?;
+    }
+    Ok(i64::try_from(data.len())?)
+}
+
+/// The length a host call returning data gives `plugin`, logging what it failed with
+fn length(plugin: &str, call: &str, result: anyhow::Result<i64>) -> i64 {
+    result.unwrap_or_else(|err| {
+        warn!("The {call} host call of {plugin} failed: {err:#}");
+        FAILED.into()
+    })
+}
+
+/// The status a host call returns to `plugin`, logging what it failed with
+fn status(plugin: &str, call: &str, result: anyhow::Result<()>) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(err) => {
+            warn!("The {call} host call of {plugin} failed: {err:#}");
+            FAILED
+        }
+    }
+}
+
+/// The level of a line logged through the `log` host call, from 0 for errors to 4 for trace
+const fn level(level: u32) -> Level {
+    match level {
+        0 => Level::ERROR,
+        1 => Level::WARN,
+        2 => Level::INFO,
+        3 => Level::DEBUG,
+        _ => Level::TRACE,
+    }
+}
+
+/// Registers the host calls of the tui with `linker`, so plugins can import them from `steel_tui`
+///
+/// Data is passed as a pointer and length into the memory of the plugin, structured data encoded as MessagePack.
+/// Calls returning data write it into a buffer the plugin passes the same way.
+pub(super) fn define(linker: &mut Linker<PluginContext>) -> anyhow::Result<()> {
+    linker.func_wrap(
+        MODULE,
+        "log",
+        |mut caller: Caller<'_, PluginContext>, severity: u32, ptr: u32, len: u32| {
+            let plugin = caller.data().plugin_name().to_owned();
+            let result = read(&mut caller, ptr, len).map(|message| {
+                plugin_log(&plugin, level(severity), &String::from_utf8_lossy(&message));
+            });
+            status(&plugin, "log", result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "data_dir",
+        |mut caller: Caller<'_, PluginContext>, ptr: u32, cap: u32| {
+            let plugin = caller.data().plugin_name().to_owned();
+            let result = settings::plugin_data_dir(&plugin)
+                .and_then(|dir| write(&mut caller, ptr, cap, dir.to_string_lossy().as_bytes()));
+            length(&plugin, "data_dir", result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "config",
+        |mut caller: Caller<'_, PluginContext>, ptr: u32, cap: u32| {
+            let plugin = caller.data().plugin_name().to_owned();
+            let result = settings::plugin_config(&plugin)
+                .and_then(|config| write(&mut caller, ptr, cap, config.as_bytes()));
+            length(&plugin, "config", result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "register_command",
+        |mut caller: Caller<'_, PluginContext>, ptr: u32, len: u32| {
+            let plugin = caller.data().plugin_name().to_owned();
+            let result = read(&mut caller, ptr, len).and_then(|command| {
+                let command: CommandRegistration =
+                    rmp_serde::from_slice(&command).context("failed to decode the command")?;
+                commands::register_command(
+                    &plugin,
+                    &command.name,
+                    command.args,
+                    &command.description,
+                );
+                Ok(())
+            });
+            status(&plugin, "register_command", result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "store_get",
+        |mut caller: Caller<'_, PluginContext>, key: u32, key_len: u32, ptr: u32, cap: u32| {
+            let plugin = caller.data().plugin_name().to_owned();
+            let result = read_str(&mut caller, key, key_len)
+                .and_then(|key| storage::plugin_store_get(&plugin, &key))
+                .and_then(|value| match value {
+                    Some(value) => write(&mut caller, ptr, cap, value.as_bytes()),
+                    None => Ok(NOT_STORED),
+                });
+            length(&plugin, "store_get", result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "store_set",
+        |mut caller: Caller<'_, PluginContext>,
+         key: u32,
+         key_len: u32,
+         value: u32,
+         value_len: u32| {
+            let plugin = caller.data().plugin_name().to_owned();
+            let result = read_str(&mut caller, key, key_len).and_then(|key| {
+                let value = read_str(&mut caller, value, value_len)?;
+                storage::plugin_store_set(&plugin, &key, &value)
+            });
+            status(&plugin, "store_set", result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "store_remove",
+        |mut caller: Caller<'_, PluginContext>, key: u32, key_len: u32| {
+            let plugin = caller.data().plugin_name().to_owned();
+            // 1 if something was removed, 0 if nothing was stored under the key
+            match read_str(&mut caller, key, key_len)
+                .and_then(|key| storage::plugin_store_remove(&plugin, &key))
+            {
+                Ok(removed) => i32::from(removed),
+                Err(err) => status(&plugin, "store_remove", Err(err)),
+            }
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "update_panel",
+        |mut caller: Caller<'_, PluginContext>, ptr: u32, len: u32| {
+            let plugin = caller.data().plugin_name().to_owned();
+            let result = read(&mut caller, ptr, len)
+                .and_then(|update| panels::update_panel(&plugin, &update));
+            status(&plugin, "update_panel", result)
+        },
+    )?;
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use anyhow::{Context, bail};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::path::Path;
+use tokio::fs;
+
+/// A plugin file fetched for installation
+pub struct Download {
+    /// Name the file gets in the plugins directory
+    pub file_name: String,
+    /// The wasm module
+    pub wasm: Vec<u8>,
+    /// Hex encoded SHA-256 of the module
+    pub sha256: String,
+}
+
+/// Downloads the plugin at `source` if it is an http(s) URL, otherwise copies it from the local path
+///
+/// Fails if `sha256` is given and doesn't match the checksum of the fetched file.
+pub async fn fetch(source: &str, sha256: Option<&str>) -> anyhow::Result<Download> {
+    let is_url = source.starts_with("http://") || source.starts_with("https://");
+    let file_name = if is_url {
+        source
+            .split(['?', '#'])
+            .next()
+            .and_then(|url| url.rsplit('/').next())
+    } else {
+        Path::new(source).file_name().and_then(|name| name.to_str())
+    };
+    let Some(file_name) = file_name.filter(|name| name.ends_with(".wasm")) else {
+        bail!("{source} is not a .wasm file");
+    };
+
+    let wasm = if is_url {
+        reqwest::get(source)
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .with_context(|| format!("failed to download {source}"))?
+            .bytes()
+            .await
+            .with_context(|| format!("failed to download {source}"))?
+            .to_vec()
+    } else {
+        fs::read(source)
+            .await
+            .with_context(|| format!("failed to read {source}"))?
+    };
+
+    let digest = Sha256::digest(&wasm)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        });
+    if let Some(expected) = sha256
+        && !expected.eq_ignore_ascii_case(&digest)
+    {
+        bail!("checksum mismatch, expected {expected} but {file_name} has {digest}");
+    }
+
+    Ok(Download {
+        file_name: file_name.to_owned(),
+        wasm,
+        sha256: digest,
+    })
+}
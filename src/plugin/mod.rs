@@ -0,0 +1,1155 @@
+use crate::config::PluginLimits;
+use crate::logger;
+use crate::notifications::{self, NotificationKind, Severity};
+use crate::status::PLUGINS;
+use anyhow::{Context, bail};
+use flume::Receiver;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use startup::Stage;
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::num::NonZero;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::Duration;
+use steel_core::PluginApi;
+use steel_host::wasmtime::{Config, FuncType, OptLevel, Trap, Val};
+use steel_host::{Plugin, PluginHost, PluginMeta, SDK_VERSION, discover_plugins};
+use steel_utils::locks::SyncMutex;
+use tokio::fs::{self, create_dir_all};
+use tokio::select;
+use tokio::sync::{Mutex, Semaphore, mpsc};
+use tokio::time;
+use tokio::time::error::Elapsed;
+use tokio_util::sync::CancellationToken;
+use tracing::{Level, debug, error, info, trace, warn};
+
+pub mod commands;
+pub mod events;
+mod imports;
+pub mod install;
+pub mod monitor;
+pub mod panels;
+pub mod permissions;
+pub mod profile;
+pub mod repl;
+pub mod settings;
+pub mod startup;
+pub mod storage;
+
+/// How often the epoch of the plugin engine advances, the granularity of `timeout_ms`
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// How much longer than `timeout_ms` a handler may take before the watchdog gives up on it
+///
+/// Only reached while a handler waits on an async host call, the epoch interrupts running wasm.
+const WATCHDOG_GRACE: Duration = Duration::from_secs(1);
+
+/// How often the resource usage shown for each plugin is refreshed
+const USAGE_REFRESH: Duration = Duration::from_secs(1);
+
+/// How long a plugin file has to stay unchanged before it is reloaded, writes come in bursts
+const HOT_RELOAD_DELAY: Duration = Duration::from_millis(500);
+
+const USAGE: &str = "Usage: plugins [list | info <name> | enable <name> | disable <name> | reload <name> | \
+                     config <name> [<key> <value>] | profile [reset] | \
+                     install <url | path> [sha256] | uninstall <name> | permissions <name> [reset] | \
+                     store <name> [set <key> <value> | remove <key>]]";
+
+/// The manager of the first server that started the plugin system, used by console commands
+static MANAGER: OnceLock<Arc<PluginManager>> = OnceLock::new();
+
+/// Whether a plugin currently receives events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginState {
+    Enabled,
+    Disabled,
+    /// Disabled after one of its callbacks trapped or ran over its limits
+    Crashed,
+    /// Can't be enabled because a dependency is missing or part of a cycle
+    Blocked,
+}
+
+impl fmt::Display for PluginState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Enabled => f.write_str("enabled"),
+            Self::Disabled => f.write_str("disabled"),
+            Self::Crashed => f.write_str("crashed"),
+            Self::Blocked => f.write_str("blocked"),
+        }
+    }
+}
+
+/// What is known about a plugin, readable without waiting for a running plugin operation
+#[derive(Debug, Clone)]
+pub struct PluginInfo {
+    pub name: String,
+    pub version: String,
+    pub path: PathBuf,
+    pub state: PluginState,
+    /// Names of the plugins this plugin needs to be enabled first
+    pub dependencies: Vec<String>,
+    /// Why the plugin is blocked
+    pub problem: Option<String>,
+    /// Fuel burned so far, only tracked if fuel is limited
+    pub fuel_consumed: Option<u64>,
+    /// Size of the linear memory of the plugin
+    pub memory_bytes: usize,
+}
+
+struct ManagedPlugin {
+    meta: PluginMeta,
+    plugin: Plugin,
+    state: PluginState,
+    problem: Option<String>,
+}
+
+impl ManagedPlugin {
+    fn info(&self, host: &PluginHost) -> PluginInfo {
+        let usage = host.usage(&self.plugin);
+        PluginInfo {
+            name: self.plugin.name().to_owned(),
+            version: self.meta.version.clone(),
+            path: self.meta.path.clone(),
+            state: self.state,
+            dependencies: self.meta.dependencies.clone(),
+            problem: self.problem.clone(),
+            fuel_consumed: usage.fuel_consumed,
+            memory_bytes: usage.memory_bytes,
+        }
+    }
+}
+
+/// Loads the plugins of the plugins directory into the host and controls them one by one
+pub struct PluginManager {
+    host: Arc<PluginHost>,
+    folder: PathBuf,
+    limits: PluginLimits,
+    plugins: Mutex<Vec<ManagedPlugin>>,
+    /// Plugins built against an incompatible SDK, never loaded
+    incompatible: SyncMutex<Vec<PluginInfo>>,
+    infos: SyncMutex<Vec<PluginInfo>>,
+}
+
+impl PluginManager {
+    /// Creates the plugin host and loads every plugin found in `plugins_folder` without enabling it
+    pub async fn new(plugins_folder: PathBuf, limits: PluginLimits) -> anyhow::Result<Self> {
+        let mut config = Config::new();
+        config.cranelift_opt_level(OptLevel::Speed);
+        config.wasm_multi_memory(false);
+        config.consume_fuel(limits.fuel.is_some());
+        config.epoch_interruption(limits.timeout_ms.is_some());
+
+        create_dir_all(&plugins_folder)
+            .await
+            .context("failed to create plugin directory")?;
+
+        let mut host = PluginHost::new(config, plugins_folder.clone())
+            .context("failed to create the plugin host")?;
+        imports::define(host.linker_mut()).context("failed to define the host calls")?;
+        permissions::install(&mut host);
+        let host = Arc::new(host);
+
+        let discovered_plugins = discover_plugins(&plugins_folder)
+            .await
+            .context("failed to discover plugins")?;
+
+        // loading these would only fail with a linker error about some missing import
+        let (discovered_plugins, incompatible): (Vec<_>, Vec<_>) = discovered_plugins
+            .into_iter()
+            .partition(|meta| sdk_problem(meta).is_none());
+        let incompatible = incompatible
+            .into_iter()
+            .map(|meta| {
+                let problem = sdk_problem(&meta);
+                error!(
+                    "{} can't be loaded: {}",
+                    meta.name,
+                    problem.as_deref().unwrap_or_default()
+                );
+                PluginInfo {
+                    name: meta.name,
+                    version: meta.version,
+                    path: meta.path,
+                    state: PluginState::Blocked,
+                    dependencies: meta.dependencies,
+                    problem,
+                    fuel_consumed: None,
+                    memory_bytes: 0,
+                }
+            })
+            .collect();
+
+        let resolved = resolve(discovered_plugins);
+        startup::discovered(resolved.iter().map(|(meta, _)| meta.name.as_str()));
+
+        // compiled and linked concurrently, large modules take a while, but only a few at once
+        let compile_runtime = startup::compile_runtime();
+        let parallel = Arc::new(Semaphore::new(
+            thread::available_parallelism().map_or(1, NonZero::get),
+        ));
+        let mut loading = Vec::new();
+        for (meta, problem) in resolved {
+            if let Some(problem) = &problem {
+                error!("{} can't be enabled: {problem}", meta.name);
+            }
+            let host = host.clone();
+            let folder = plugins_folder.clone();
+            let parallel = parallel.clone();
+            let prepare = meta.clone();
+            loading.push((
+                meta,
+                problem,
+                compile_runtime.spawn(async move {
+                    let _permit = parallel.acquire_owned().await?;
+                    let name = prepare.name.clone();
+                    let plugin = host
+                        .prepare_plugin(prepare)
+                        .await
+                        .context("failed to prepare plugin")
+                        .inspect_err(|_| startup::advance(&name, Stage::Failed))?;
+                    startup::advance(&name, Stage::Compiled);
+                    load(&host, &folder, limits, &plugin)
+                        .await
+                        .inspect_err(|_| startup::advance(&name, Stage::Failed))?;
+                    startup::advance(&name, Stage::Linked);
+                    anyhow::Ok(plugin)
+                }),
+            ));
+        }
+
+        let manager = Self {
+            host,
+            folder: plugins_folder,
+            limits,
+            plugins: Mutex::new(Vec::new()),
+            incompatible: SyncMutex::new(incompatible),
+            infos: SyncMutex::new(Vec::new()),
+        };
+
+        // collected in dependency order, which is also the order they are enabled in
+        let mut plugins = Vec::new();
+        for (meta, problem, handle) in loading {
+            let plugin = handle.await.context("tokio thread panicked")??;
+
+            plugins.push(ManagedPlugin {
+                meta,
+                plugin,
+                state: if problem.is_some() {
+                    PluginState::Blocked
+                } else {
+                    PluginState::Disabled
+                },
+                problem,
+            });
+        }
+
+        manager.update(&plugins);
+        *manager.plugins.lock().await = plugins;
+        Ok(manager)
+    }
+
+    /// Loads a prepared plugin into the host under the configured limits
+    async fn load(&self, plugin: &Plugin) -> anyhow::Result<()> {
+        load(&self.host, &self.folder, self.limits, plugin).await
+    }
+
+    /// The host running the plugins
+    pub const fn host(&self) -> &Arc<PluginHost> {
+        &self.host
+    }
+
+    /// The directory the plugin called `name` keeps its files in
+    pub fn data_dir(&self, name: &str) -> PathBuf {
+        self.folder.join(name)
+    }
+
+    /// Enables every loaded plugin that is not enabled yet
+    pub async fn enable_all(&self) -> anyhow::Result<()> {
+        let mut plugins = self.plugins.lock().await;
+        for plugin in plugins
+            .iter_mut()
+            .filter(|plugin| plugin.state == PluginState::Disabled)
+        {
+            let name = plugin.plugin.name();
+            self.host
+                .enable_plugin(&plugin.plugin)
+                .await
+                .inspect_err(|_| startup::advance(name, Stage::Failed))
+                .context("failed to enable plugin")?;
+            startup::advance(name, Stage::Enabled);
+            plugin.state = PluginState::Enabled;
+        }
+        self.update(&plugins);
+        Ok(())
+    }
+
+    /// Every loaded plugin
+    pub fn list(&self) -> Vec<PluginInfo> {
+        self.infos.lock().clone()
+    }
+
+    /// The loaded plugin called `name`
+    pub fn info(&self, name: &str) -> Option<PluginInfo> {
+        self.infos
+            .lock()
+            .iter()
+            .find(|info| info.name == name)
+            .cloned()
+    }
+
+    /// Fuel burned so far by the plugin called `name`
+    ///
+    /// Unknown while a plugin operation is running, waiting for it could deadlock the event dispatch.
+    pub fn fuel_consumed(&self, name: &str) -> Option<u64> {
+        let plugins = self.plugins.try_lock().ok()?;
+        let plugin = plugins.iter().find(|plugin| plugin.plugin.name() == name)?;
+        self.host.usage(&plugin.plugin).fuel_consumed
+    }
+
+    /// The loaded plugin whose file is called `file_name`
+    fn by_file_name(&self, file_name: &OsStr) -> Option<PluginInfo> {
+        self.infos
+            .lock()
+            .iter()
+            .find(|info| info.path.file_name() == Some(file_name))
+            .cloned()
+    }
+
+    /// Fails with the reason the plugin called `name` was never loaded, if it wasn't
+    fn ensure_loaded(&self, name: &str) -> anyhow::Result<()> {
+        match self
+            .incompatible
+            .lock()
+            .iter()
+            .find(|info| info.name == name)
+        {
+            Some(info) => bail!(
+                "{name} can't be loaded: {}",
+                info.problem.as_deref().unwrap_or_default()
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Enables the disabled plugin called `name`, a crashed plugin is loaded again from its file first
+    pub async fn enable(&self, name: &str) -> anyhow::Result<()> {
+        self.ensure_loaded(name)?;
+        let mut plugins = self.plugins.lock().await;
+        if let Some(dependency) = disabled_dependency(&plugins, name) {
+            bail!("{name} depends on {dependency}, which is not enabled");
+        }
+        let plugin = find(&mut plugins, name)?;
+        match plugin.state {
+            PluginState::Enabled => bail!("{name} is already enabled"),
+            PluginState::Blocked => bail!(
+                "{name} can't be enabled: {}",
+                plugin.problem.as_deref().unwrap_or_default()
+            ),
+            PluginState::Disabled => (),
+            // the instance that trapped can't be trusted anymore
+            PluginState::Crashed => {
+                let result = self.reload_plugin(plugin).await;
+                if result.is_err() {
+                    self.update(&plugins);
+                }
+                result?;
+            }
+        }
+        let plugin = find(&mut plugins, name)?;
+        self.host
+            .enable_plugin(&plugin.plugin)
+            .await
+            .context("failed to enable plugin")?;
+        plugin.state = PluginState::Enabled;
+        self.update(&plugins);
+        Ok(())
+    }
+
+    /// Disables the enabled plugin called `name`, it stays loaded and can be enabled again
+    pub async fn disable(&self, name: &str) -> anyhow::Result<()> {
+        self.ensure_loaded(name)?;
+        let mut plugins = self.plugins.lock().await;
+        if let Some(dependent) = enabled_dependent(&plugins, name) {
+            bail!("{dependent} depends on {name}, disable it first");
+        }
+        let plugin = find(&mut plugins, name)?;
+        if plugin.state != PluginState::Enabled {
+            bail!("{name} is already disabled");
+        }
+        self.host
+            .disable_plugin(&plugin.plugin)
+            .await
+            .context("failed to disable plugin")?;
+        plugin.state = PluginState::Disabled;
+        panels::remove(name);
+        commands::remove(name);
+        self.update(&plugins);
+        Ok(())
+    }
+
+    /// Unloads the plugin called `name` and loads it again from its file, keeping its state
+    pub async fn reload(&self, name: &str) -> anyhow::Result<()> {
+        self.ensure_loaded(name)?;
+        let mut plugins = self.plugins.lock().await;
+        let result = self.reload_plugin(find(&mut plugins, name)?).await;
+        // the plugin stays disabled if loading it again failed
+        self.update(&plugins);
+        result
+    }
+
+    async fn reload_plugin(&self, plugin: &mut ManagedPlugin) -> anyhow::Result<()> {
+        let state = plugin.state;
+        if state == PluginState::Enabled {
+            self.host
+                .disable_plugin(&plugin.plugin)
+                .await
+                .context("failed to disable plugin")?;
+            plugin.state = PluginState::Disabled;
+            panels::remove(plugin.plugin.name());
+            commands::remove(plugin.plugin.name());
+        }
+        self.host
+            .unload_plugin(&plugin.plugin)
+            .await
+            .context("failed to unload plugin")?;
+
+        plugin.plugin = self
+            .host
+            .prepare_plugin(plugin.meta.clone())
+            .await
+            .context("failed to prepare plugin")?;
+        self.load(&plugin.plugin).await?;
+        match state {
+            PluginState::Enabled => {
+                self.host
+                    .enable_plugin(&plugin.plugin)
+                    .await
+                    .context("failed to enable plugin")?;
+                plugin.state = PluginState::Enabled;
+            }
+            // the new instance has not crashed, it waits to be enabled again
+            PluginState::Disabled | PluginState::Crashed => plugin.state = PluginState::Disabled,
+            // reloading doesn't change the dependencies it was resolved with
+            PluginState::Blocked => (),
+        }
+        Ok(())
+    }
+
+    /// How long a handler may take before the watchdog stops waiting for it, if handlers have a deadline
+    pub fn watchdog(&self) -> Option<Duration> {
+        self.limits
+            .timeout_ms
+            .map(|timeout| Duration::from_millis(timeout) + WATCHDOG_GRACE)
+    }
+
+    /// Disables the plugin called `name` after its callback failed with `error`, instead of letting the
+    /// error reach the server
+    ///
+    /// A plugin whose callback ran over its deadline stays enabled unless `disable_on_timeout` is set.
+    pub async fn isolate(&self, name: &str, error: anyhow::Error) {
+        let timed_out =
+            error.downcast_ref::<Trap>() == Some(&Trap::Interrupt) || error.is::<Elapsed>();
+        if timed_out && !self.limits.disable_on_timeout {
+            warn!("A handler of {name} ran over its deadline and was interrupted: {error:?}");
+            return;
+        }
+        error!(
+            "{name} crashed and was disabled, `plugins enable {name}` brings it back: {error:?}"
+        );
+
+        let mut plugins = self.plugins.lock().await;
+        let Ok(plugin) = find(&mut plugins, name) else {
+            return;
+        };
+        // several callbacks of the plugin may fail before it is disabled
+        if plugin.state != PluginState::Enabled {
+            return;
+        }
+        if let Err(err) = self.host.disable_plugin(&plugin.plugin).await {
+            error!("Failed to disable {name}: {err:#}");
+        }
+        plugin.state = PluginState::Crashed;
+        panels::remove(name);
+        commands::remove(name);
+        self.update(&plugins);
+        notifications::send(
+            NotificationKind::PluginCrashed,
+            Severity::Error,
+            format!("{name} crashed and was disabled"),
+        );
+    }
+
+    /// The exported functions of the plugin called `name` with their signatures
+    pub async fn exports(&self, name: &str) -> anyhow::Result<Vec<(String, FuncType)>> {
+        self.ensure_loaded(name)?;
+        let mut plugins = self.plugins.lock().await;
+        let plugin = find(&mut plugins, name)?;
+        Ok(self.host.exports(&plugin.plugin))
+    }
+
+    /// Calls the exported function `export` of the plugin called `name` with `args`
+    ///
+    /// A trap is returned as an error and leaves the plugin enabled, only event callbacks are isolated.
+    pub async fn call(&self, name: &str, export: &str, args: &[Val]) -> anyhow::Result<Vec<Val>> {
+        self.ensure_loaded(name)?;
+        let mut plugins = self.plugins.lock().await;
+        let plugin = find(&mut plugins, name)?;
+        if plugin.state == PluginState::Crashed {
+            bail!("{name} crashed, enable it again before calling it");
+        }
+        let results = self.host.call_export(&plugin.plugin, export, args).await;
+        // fuel and memory may have changed
+        self.update(&plugins);
+        results
+    }
+
+    /// Writes `wasm` into the plugins directory as `file_name`, then loads and enables it
+    ///
+    /// Returns the name of the installed plugin.
+    pub async fn install(&self, file_name: &str, wasm: &[u8]) -> anyhow::Result<String> {
+        let path = self.folder.join(file_name);
+        if fs::try_exists(&path).await.unwrap_or(true) {
+            bail!("{file_name} already exists in the plugins directory");
+        }
+        fs::write(&path, wasm)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?;
+
+        let result = self.load_file(&path).await;
+        if result.is_err() {
+            // it would fail again on the next start
+            let _ = fs::remove_file(&path).await;
+        }
+        result
+    }
+
+    async fn load_file(&self, path: &Path) -> anyhow::Result<String> {
+        let meta = discover_plugins(&self.folder)
+            .await
+            .context("failed to discover plugins")?
+            .into_iter()
+            .find(|meta| meta.path == path)
+            .context("the file is not a plugin")?;
+        if let Some(problem) = sdk_problem(&meta) {
+            bail!("{} can't be loaded: {problem}", meta.name);
+        }
+
+        let mut plugins = self.plugins.lock().await;
+        if plugins
+            .iter()
+            .any(|plugin| plugin.plugin.name() == meta.name)
+        {
+            bail!("a plugin named {} is already loaded", meta.name);
+        }
+        if let Some(dependency) = meta.dependencies.iter().find(|dependency| {
+            !plugins.iter().any(|plugin| {
+                plugin.plugin.name() == dependency.as_str() && plugin.state == PluginState::Enabled
+            })
+        }) {
+            bail!(
+                "{} depends on {dependency}, which is not enabled",
+                meta.name
+            );
+        }
+
+        let plugin = self
+            .host
+            .prepare_plugin(meta.clone())
+            .await
+            .context("failed to prepare plugin")?;
+        self.load(&plugin).await?;
+        if let Err(err) = self.host.enable_plugin(&plugin).await {
+            let _ = self.host.unload_plugin(&plugin).await;
+            return Err(err).context("failed to enable plugin");
+        }
+
+        let name = meta.name.clone();
+        plugins.push(ManagedPlugin {
+            meta,
+            plugin,
+            state: PluginState::Enabled,
+            problem: None,
+        });
+        self.update(&plugins);
+        Ok(name)
+    }
+
+    /// Disables and unloads the plugin called `name` and deletes its file, its data directory is kept
+    pub async fn uninstall(&self, name: &str) -> anyhow::Result<()> {
+        let mut plugins = self.plugins.lock().await;
+        let incompatible = self
+            .incompatible
+            .lock()
+            .iter()
+            .position(|info| info.name == name);
+        let path = if let Some(index) = incompatible {
+            self.incompatible.lock().remove(index).path
+        } else {
+            if let Some(dependent) = enabled_dependent(&plugins, name) {
+                bail!("{dependent} depends on {name}, uninstall it first");
+            }
+            let plugin = find(&mut plugins, name)?;
+            if plugin.state == PluginState::Enabled {
+                self.host
+                    .disable_plugin(&plugin.plugin)
+                    .await
+                    .context("failed to disable plugin")?;
+                plugin.state = PluginState::Disabled;
+                panels::remove(name);
+                commands::remove(name);
+            }
+            self.host
+                .unload_plugin(&plugin.plugin)
+                .await
+                .context("failed to unload plugin")?;
+            let path = plugin.meta.path.clone();
+            plugins.retain(|plugin| plugin.plugin.name() != name);
+            path
+        };
+        self.update(&plugins);
+
+        fs::remove_file(&path)
+            .await
+            .with_context(|| format!("failed to delete {}", path.display()))
+    }
+
+    /// Publishes the state of `plugins` to readers that can't wait for the lock
+    fn update(&self, plugins: &[ManagedPlugin]) {
+        let infos: Vec<_> = plugins
+            .iter()
+            .map(|plugin| plugin.info(&self.host))
+            .chain(self.incompatible.lock().iter().cloned())
+            .collect();
+        *PLUGINS.lock() = infos
+            .iter()
+            .filter(|info| info.state == PluginState::Enabled)
+            .map(|info| info.name.clone())
+            .collect();
+        *self.infos.lock() = infos;
+    }
+}
+
+/// Why a plugin built against the SDK version in `meta` can't run on this host
+///
+/// Versions are compatible if their major version matches, or their minor version while the major one is 0.
+fn sdk_problem(meta: &PluginMeta) -> Option<String> {
+    fn compatibility(version: &str) -> (&str, Option<&str>) {
+        let mut parts = version.split('.');
+        match parts.next() {
+            Some("0") => ("0", parts.next()),
+            major => (major.unwrap_or_default(), None),
+        }
+    }
+
+    (compatibility(&meta.sdk_version) != compatibility(SDK_VERSION)).then(|| {
+        format!(
+            "built for SDK {}, but this host requires SDK {SDK_VERSION}, rebuild it against a \
+             matching steel-plugin-sdk and restart",
+            meta.sdk_version
+        )
+    })
+}
+
+/// Loads a prepared plugin into `host` under `limits`, creating its data directory in `plugins_folder`
+async fn load(
+    host: &PluginHost,
+    plugins_folder: &Path,
+    limits: PluginLimits,
+    plugin: &Plugin,
+) -> anyhow::Result<()> {
+    create_dir_all(plugins_folder.join(plugin.name()))
+        .await
+        .context("failed to create the plugin data directory")?;
+    let memory_bytes = limits
+        .memory_mib
+        .map(|mib| {
+            mib.checked_mul(1024 * 1024)
+                .and_then(|bytes| usize::try_from(bytes).ok())
+                .with_context(|| format!("the memory limit of {mib} MiB is too large"))
+        })
+        .transpose()?;
+    let epoch_deadline = limits
+        .timeout_ms
+        .map(|timeout| (timeout / EPOCH_TICK.as_millis() as u64).max(1));
+    host.set_limits(plugin, limits.fuel, memory_bytes, epoch_deadline)
+        .context("failed to limit plugin")?;
+    host.load_plugin(plugin)
+        .await
+        .context("failed to load plugin")
+}
+
+fn find<'a>(plugins: &'a mut [ManagedPlugin], name: &str) -> anyhow::Result<&'a mut ManagedPlugin> {
+    match plugins
+        .iter_mut()
+        .find(|plugin| plugin.plugin.name() == name)
+    {
+        Some(plugin) => Ok(plugin),
+        None => bail!("no plugin named {name}"),
+    }
+}
+
+/// A dependency of the plugin called `name` that is not enabled
+fn disabled_dependency<'a>(plugins: &'a [ManagedPlugin], name: &str) -> Option<&'a str> {
+    let plugin = plugins.iter().find(|plugin| plugin.plugin.name() == name)?;
+    plugin
+        .meta
+        .dependencies
+        .iter()
+        .find(|dependency| {
+            !plugins.iter().any(|plugin| {
+                plugin.plugin.name() == dependency.as_str() && plugin.state == PluginState::Enabled
+            })
+        })
+        .map(String::as_str)
+}
+
+/// An enabled plugin that depends on the plugin called `name`
+fn enabled_dependent<'a>(plugins: &'a [ManagedPlugin], name: &str) -> Option<&'a str> {
+    plugins
+        .iter()
+        .filter(|plugin| plugin.state == PluginState::Enabled)
+        .find(|plugin| {
+            plugin
+                .meta
+                .dependencies
+                .iter()
+                .any(|dependency| dependency == name)
+        })
+        .map(|plugin| plugin.plugin.name())
+}
+
+/// Orders `metas` so every plugin comes after its dependencies
+///
+/// Plugins whose dependencies can't be satisfied come last, together with the reason why.
+fn resolve(metas: Vec<PluginMeta>) -> Vec<(PluginMeta, Option<String>)> {
+    let names: HashSet<_> = metas.iter().map(|meta| meta.name.clone()).collect();
+    let mut ordered = Vec::with_capacity(metas.len());
+    let mut resolved = HashSet::new();
+    let mut pending = metas;
+
+    // every pass takes the plugins whose dependencies all came before, until nothing changes
+    loop {
+        let (ready, rest): (Vec<_>, Vec<_>) = pending.into_iter().partition(|meta| {
+            meta.dependencies
+                .iter()
+                .all(|dependency| resolved.contains(dependency))
+        });
+        pending = rest;
+        if ready.is_empty() {
+            break;
+        }
+        for meta in ready {
+            resolved.insert(meta.name.clone());
+            ordered.push((meta, None));
+        }
+    }
+
+    let blocked: HashSet<_> = pending.iter().map(|meta| meta.name.clone()).collect();
+    for meta in pending {
+        let problem = if let Some(missing) = meta
+            .dependencies
+            .iter()
+            .find(|dependency| !names.contains(*dependency))
+        {
+            format!("missing dependency {missing}")
+        } else {
+            let unresolved: Vec<_> = meta
+                .dependencies
+                .iter()
+                .filter(|dependency| blocked.contains(*dependency))
+                .map(String::as_str)
+                .collect();
+            format!(
+                "dependency {} is blocked or part of a cycle",
+                unresolved.join(", ")
+            )
+        };
+        ordered.push((meta, Some(problem)));
+    }
+    ordered
+}
+
+/// Loads and enables the plugins in `plugins_folder` without serving them any events, to time how long that takes
+///
+/// The plugin system isn't started, so the manager is dropped again once they are loaded.
+pub async fn load_plugins(plugins_folder: PathBuf, limits: PluginLimits) -> anyhow::Result<()> {
+    enable(plugins_folder, limits).await.map(drop)
+}
+
+/// Creates a manager enabling every plugin in `plugins_folder` under `limits`
+async fn enable(
+    plugins_folder: PathBuf,
+    limits: PluginLimits,
+) -> anyhow::Result<Arc<PluginManager>> {
+    let started = async {
+        let manager = Arc::new(PluginManager::new(plugins_folder, limits).await?);
+        manager.enable_all().await?;
+        anyhow::Ok(manager)
+    }
+    .await;
+    startup::finish();
+    started
+}
+
+/// Starts the plugin system, enabling every plugin in `plugins_folder` under `limits`
+pub async fn init(
+    plugins_folder: impl Into<PathBuf>,
+    limits: PluginLimits,
+) -> anyhow::Result<(Arc<PluginManager>, Receiver<PluginApi>)> {
+    let manager = enable(plugins_folder.into(), limits).await?;
+    let _ = MANAGER.set(manager.clone());
+
+    if limits.timeout_ms.is_some() {
+        // callbacks are interrupted once the epoch passed their deadline
+        let engine = manager.host.engine().clone();
+        let mut interval = time::interval(EPOCH_TICK);
+        tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+                engine.increment_epoch();
+            }
+        });
+    }
+
+    let refreshed = manager.clone();
+    let mut interval = time::interval(USAGE_REFRESH);
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+            // skipped while a plugin operation is running, it updates the usage itself
+            if let Ok(plugins) = refreshed.plugins.try_lock() {
+                refreshed.update(&plugins);
+            }
+        }
+    });
+
+    Ok((manager, steel_core::init_api()))
+}
+
+/// The manager of the first server that started the plugin system
+pub fn manager() -> Option<&'static Arc<PluginManager>> {
+    MANAGER.get()
+}
+
+/// Reloads plugins whose file in `plugins_folder` changed until `server_token` is cancelled
+pub fn watch(
+    manager: Arc<PluginManager>,
+    plugins_folder: &Path,
+    server_token: CancellationToken,
+) -> anyhow::Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher: RecommendedWatcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event
+                && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            {
+                let _ = tx.send(event.paths);
+            }
+        })
+        .context("failed to create the file watcher")?;
+    watcher
+        .watch(plugins_folder, RecursiveMode::NonRecursive)
+        .context("failed to watch the plugins directory")?;
+
+    tokio::spawn(async move {
+        // dropping the watcher stops it
+        let _watcher = watcher;
+        let mut changed = HashSet::new();
+        loop {
+            select! {
+                () = server_token.cancelled() => return,
+                paths = rx.recv() => match paths {
+                    Some(paths) => {
+                        changed.extend(
+                            paths
+                                .iter()
+                                .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+                                .filter_map(|path| path.file_name().map(OsString::from)),
+                        );
+                        continue;
+                    }
+                    None => return,
+                },
+                () = time::sleep(HOT_RELOAD_DELAY), if !changed.is_empty() => (),
+            }
+
+            for file_name in changed.drain() {
+                // new files are picked up on the next start, only loaded plugins are reloaded
+                let Some(info) = manager.by_file_name(&file_name) else {
+                    continue;
+                };
+                match manager.reload(&info.name).await {
+                    Ok(()) => {
+                        info!("Reloaded {} after its file changed", info.name);
+                        notifications::send(
+                            NotificationKind::PluginReloaded,
+                            Severity::Info,
+                            format!("Reloaded {}", info.name),
+                        );
+                    }
+                    Err(err) => {
+                        error!("Failed to reload {}: {err:#}", info.name);
+                        notifications::send(
+                            NotificationKind::PluginReloaded,
+                            Severity::Error,
+                            format!("Failed to reload {}: {err}", info.name),
+                        );
+                    }
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Logs `message` on behalf of `plugin`, prefixed with its name so its output can be told apart
+pub fn plugin_log(plugin: &str, level: Level, message: &str) {
+    logger::with_source(plugin, || match level {
+        Level::ERROR => error!("[{plugin}] {message}"),
+        Level::WARN => warn!("[{plugin}] {message}"),
+        Level::INFO => info!("[{plugin}] {message}"),
+        Level::DEBUG => debug!("[{plugin}] {message}"),
+        _ => trace!("[{plugin}] {message}"),
+    });
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Control {
+    Enable,
+    Disable,
+    Reload,
+    Uninstall,
+}
+
+impl Control {
+    const fn verb(self) -> &'static str {
+        match self {
+            Self::Enable => "enable",
+            Self::Disable => "disable",
+            Self::Reload => "reload",
+            Self::Uninstall => "uninstall",
+        }
+    }
+}
+
+/// Handles the `plugins` console command, returning `None` for every other command
+///
+/// Enabling, disabling and reloading runs in the background and logs its outcome once done.
+pub fn command(command: &str) -> Option<Vec<String>> {
+    let mut args = command.split_whitespace();
+    if args.next()? != "plugins" {
+        return None;
+    }
+    let Some(manager) = MANAGER.get() else {
+        return Some(vec!["The plugin system is not running".to_owned()]);
+    };
+
+    let output = match (args.next(), args.next()) {
+        (None | Some("list"), None) => {
+            let plugins = manager.list();
+            if plugins.is_empty() {
+                vec!["No plugins are loaded".to_owned()]
+            } else {
+                plugins
+                    .iter()
+                    .map(|info| match &info.problem {
+                        Some(problem) => {
+                            format!("{} {} ({}: {problem})", info.name, info.version, info.state)
+                        }
+                        None => format!("{} {} ({})", info.name, info.version, info.state),
+                    })
+                    .collect()
+            }
+        }
+        (Some("info"), Some(name)) => match manager.info(name) {
+            Some(info) => {
+                let mut lines = vec![
+                    format!("Name: {}", info.name),
+                    format!("Version: {}", info.version),
+                    format!("File: {}", info.path.display()),
+                    format!("State: {}", info.state),
+                ];
+                if !info.dependencies.is_empty() {
+                    lines.push(format!("Depends on: {}", info.dependencies.join(", ")));
+                }
+                if let Some(problem) = info.problem {
+                    lines.push(format!("Problem: {problem}"));
+                }
+                lines
+            }
+            None => vec![format!("No plugin named {name}")],
+        },
+        (Some("enable"), Some(name)) => control(manager, Control::Enable, name),
+        (Some("disable"), Some(name)) => control(manager, Control::Disable, name),
+        (Some("reload"), Some(name)) => control(manager, Control::Reload, name),
+        (Some("uninstall"), Some(name)) => control(manager, Control::Uninstall, name),
+        (Some("install"), Some(source)) => install_from(manager, source, args.next()),
+        (Some("permissions"), Some(name)) => match (args.next(), manager.info(name)) {
+            (_, None) => vec![format!("No plugin named {name}")],
+            (None, Some(_)) => match permissions::decisions(name) {
+                Ok(decisions) if decisions.is_empty() => {
+                    vec![format!("{name} was not granted or denied anything yet")]
+                }
+                Ok(decisions) => decisions,
+                Err(err) => vec![format!("{err:#}")],
+            },
+            (Some("reset"), Some(_)) => match permissions::reset(name) {
+                Ok(()) => vec![format!("{name} is asked again for every capability")],
+                Err(err) => vec![format!("{err:#}")],
+            },
+            _ => vec![USAGE.to_owned()],
+        },
+        (Some("store"), Some(name)) if manager.info(name).is_none() => {
+            vec![format!("No plugin named {name}")]
+        }
+        (Some("store"), Some(name)) => match (args.next(), args.next()) {
+            (None, _) => match storage::entries(name) {
+                Ok(entries) if entries.is_empty() => vec![format!("{name} stored nothing")],
+                Ok(entries) => entries
+                    .into_iter()
+                    .map(|(key, value)| format!("{key} = {value}"))
+                    .collect(),
+                Err(err) => vec![format!("{err:#}")],
+            },
+            (Some("set"), Some(key)) => {
+                let value = args.collect::<Vec<_>>().join(" ");
+                match storage::plugin_store_set(name, key, &value) {
+                    Ok(()) => vec![format!("Stored {key} = {value} for {name}")],
+                    Err(err) => vec![format!("{err:#}")],
+                }
+            }
+            (Some("remove"), Some(key)) => match storage::plugin_store_remove(name, key) {
+                Ok(true) => vec![format!("Removed {key} from the store of {name}")],
+                Ok(false) => vec![format!("{name} stored nothing under {key}")],
+                Err(err) => vec![format!("{err:#}")],
+            },
+            _ => vec![USAGE.to_owned()],
+        },
+        (Some("profile"), None) => profile_report(),
+        (Some("profile"), Some("reset")) => {
+            profile::reset();
+            vec!["Reset the plugin profile".to_owned()]
+        }
+        (Some("config"), Some(name)) => {
+            let setting = args
+                .next()
+                .map(|key| (key, args.collect::<Vec<_>>().join(" ")));
+            configure(manager, name, setting)
+        }
+        _ => vec![USAGE.to_owned()],
+    };
+    Some(output)
+}
+
+fn control(manager: &Arc<PluginManager>, control: Control, name: &str) -> Vec<String> {
+    if manager.info(name).is_none() {
+        return vec![format!("No plugin named {name}")];
+    }
+
+    let manager = manager.clone();
+    let plugin = name.to_owned();
+    tokio::spawn(async move {
+        let (result, done) = match control {
+            Control::Enable => (manager.enable(&plugin).await, "Enabled"),
+            Control::Disable => (manager.disable(&plugin).await, "Disabled"),
+            Control::Reload => (manager.reload(&plugin).await, "Reloaded"),
+            Control::Uninstall => (manager.uninstall(&plugin).await, "Uninstalled"),
+        };
+        match result {
+            Ok(()) => info!("{done} {plugin}"),
+            Err(err) => error!("Failed to {} {plugin}: {err:#}", control.verb()),
+        }
+    });
+
+    let pending = match control {
+        Control::Enable => "Enabling",
+        Control::Disable => "Disabling",
+        Control::Reload => "Reloading",
+        Control::Uninstall => "Uninstalling",
+    };
+    vec![format!("{pending} {name}...")]
+}
+
+/// Shows the config of the plugin called `name`, or changes one setting and reloads it to apply the change
+fn configure(
+    manager: &Arc<PluginManager>,
+    name: &str,
+    setting: Option<(&str, String)>,
+) -> Vec<String> {
+    let Some(info) = manager.info(name) else {
+        return vec![format!("No plugin named {name}")];
+    };
+
+    let Some((key, value)) = setting else {
+        return match settings::entries(name) {
+            Ok(entries) if entries.is_empty() => vec![format!("{name} has no settings")],
+            Ok(entries) => entries
+                .into_iter()
+                .map(|(key, value)| format!("{key} = {value}"))
+                .collect(),
+            Err(err) => vec![format!("{err:#}")],
+        };
+    };
+    if value.is_empty() {
+        return vec![USAGE.to_owned()];
+    }
+    if let Err(err) = settings::set(name, key, &value) {
+        return vec![format!("Failed to change {key}: {err:#}")];
+    }
+
+    let mut output = vec![format!("Set {key} of {name} to {value}")];
+    // plugins read their config when they are enabled
+    if info.state == PluginState::Enabled {
+        output.extend(control(manager, Control::Reload, name));
+    }
+    output
+}
+
+/// The time and fuel every plugin spent per event type, most expensive first
+fn profile_report() -> Vec<String> {
+    let report = profile::report();
+    if report.is_empty() {
+        return vec!["No plugin handled an event yet".to_owned()];
+    }
+    report
+        .into_iter()
+        .map(|(plugin, topic, sample)| {
+            format!(
+                "{plugin} {topic}: {} calls, {:.2?} total, {:.2?} average, {:.2?} max, {} fuel",
+                sample.calls,
+                sample.total,
+                sample.total / sample.calls.try_into().unwrap_or(u32::MAX),
+                sample.max,
+                sample.fuel,
+            )
+        })
+        .collect()
+}
+
+/// Fetches the plugin at `source` in the background and installs it once its checksum checks out
+fn install_from(manager: &Arc<PluginManager>, source: &str, sha256: Option<&str>) -> Vec<String> {
+    let mut output = vec![format!("Installing {source}...")];
+    if sha256.is_none() {
+        output.push("No checksum given, the file is installed without verifying it".to_owned());
+    }
+
+    let manager = manager.clone();
+    let source = source.to_owned();
+    let sha256 = sha256.map(str::to_owned);
+    tokio::spawn(async move {
+        let result = async {
+            let download = install::fetch(&source, sha256.as_deref()).await?;
+            let name = manager.install(&download.file_name, &download.wasm).await?;
+            anyhow::Ok((name, download.sha256))
+        };
+        match result.await {
+            Ok((name, sha256)) => info!("Installed {name} from {source} (sha256 {sha256})"),
+            Err(err) => error!("Failed to install {source}: {err:#}"),
+        }
+    });
+    output
+}
@@ -0,0 +1,59 @@
+use std::any;
+use std::collections::VecDeque;
+use std::sync::LazyLock;
+use std::time::Duration;
+use steel_utils::locks::SyncMutex;
+
+/// Number of dispatched events kept for the event monitor
+const CAPACITY: usize = 512;
+
+static EVENTS: LazyLock<SyncMutex<VecDeque<EventRecord>>> =
+    LazyLock::new(|| SyncMutex::new(VecDeque::with_capacity(CAPACITY)));
+
+/// An event that went through the plugin handlers
+#[derive(Debug, Clone)]
+pub struct EventRecord {
+    /// Name of the event type
+    pub topic: &'static str,
+    /// Plugins that had a handler for the event
+    pub handlers: Vec<String>,
+    /// Whether a handler cancelled the event
+    pub cancelled: bool,
+    /// Plugins whose handler crashed
+    pub crashed: Vec<String>,
+    /// How long all handlers took together
+    pub duration: Duration,
+}
+
+impl EventRecord {
+    /// A record of an event on `topic` that was neither cancelled nor crashed a handler
+    pub const fn new(topic: &'static str, handlers: Vec<String>, duration: Duration) -> Self {
+        Self {
+            topic,
+            handlers,
+            cancelled: false,
+            crashed: Vec::new(),
+            duration,
+        }
+    }
+}
+
+/// Name of the event type `E` without its module path
+pub fn topic<E>() -> &'static str {
+    let topic = any::type_name::<E>();
+    topic.rsplit("::").next().unwrap_or(topic)
+}
+
+/// Keeps `record` for the event monitor, dropping the oldest record once full
+pub fn record(record: EventRecord) {
+    let mut events = EVENTS.lock();
+    if events.len() == CAPACITY {
+        events.pop_front();
+    }
+    events.push_back(record);
+}
+
+/// The recently dispatched events, newest first
+pub fn recent() -> Vec<EventRecord> {
+    EVENTS.lock().iter().rev().cloned().collect()
+}
@@ -0,0 +1,133 @@
+use crate::REDRAW;
+use crate::config::Theme;
+use anyhow::Context;
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Row, Table, Wrap};
+use serde::Deserialize;
+use std::sync::LazyLock;
+use steel_utils::locks::SyncMutex;
+
+/// Panels registered by plugins in the order they were first registered in
+static PANELS: LazyLock<SyncMutex<Vec<PluginPanel>>> = LazyLock::new(|| SyncMutex::new(Vec::new()));
+
+/// What a plugin panel shows, described by the plugin and rendered by the host
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PanelContent {
+    /// Wrapped plain text
+    Text {
+        /// The text shown
+        text: String,
+    },
+    /// One item per line
+    List {
+        /// The items shown
+        items: Vec<String>,
+    },
+    /// Keys and their values in two columns
+    Table {
+        /// Pairs of a key and its value
+        rows: Vec<(String, String)>,
+    },
+}
+
+/// A panel update sent by a plugin through the `update_panel` host call, encoded as MessagePack
+#[derive(Debug, Clone, Deserialize)]
+pub struct PanelUpdate {
+    /// Identifies the panel among the panels of the plugin
+    pub id: String,
+    /// The name shown in the tab bar
+    pub title: String,
+    /// What the panel shows
+    pub content: PanelContent,
+}
+
+struct PluginPanel {
+    plugin: String,
+    id: String,
+    title: String,
+    content: PanelContent,
+}
+
+/// Creates or replaces a panel of `plugin` from the encoded `update` it sent
+pub fn update_panel(plugin: &str, update: &[u8]) -> anyhow::Result<()> {
+    let update: PanelUpdate =
+        rmp_serde::from_slice(update).context("failed to decode the panel update")?;
+
+    let mut panels = PANELS.lock();
+    match panels
+        .iter_mut()
+        .find(|panel| panel.plugin == plugin && panel.id == update.id)
+    {
+        Some(panel) => {
+            panel.title = update.title;
+            panel.content = update.content;
+        }
+        None => panels.push(PluginPanel {
+            plugin: plugin.to_owned(),
+            id: update.id,
+            title: update.title,
+            content: update.content,
+        }),
+    }
+    REDRAW.notify_one();
+    Ok(())
+}
+
+/// Removes every panel of `plugin`, done when it gets disabled
+pub fn remove(plugin: &str) {
+    PANELS.lock().retain(|panel| panel.plugin != plugin);
+    REDRAW.notify_one();
+}
+
+/// Number of registered plugin panels
+pub fn count() -> usize {
+    PANELS.lock().len()
+}
+
+/// Titles of every plugin panel in tab order
+pub fn titles() -> Vec<String> {
+    PANELS
+        .lock()
+        .iter()
+        .map(|panel| panel.title.clone())
+        .collect()
+}
+
+/// Renders the plugin panel at `index`, nothing if it was removed in the meantime
+pub fn render(index: usize, theme: &Theme, area: Rect, buf: &mut Buffer) {
+    let panels = PANELS.lock();
+    let Some(panel) = panels.get(index) else {
+        return;
+    };
+
+    match &panel.content {
+        PanelContent::Text { text } => {
+            Paragraph::new(text.as_str())
+                .wrap(Wrap { trim: false })
+                .render(area, buf);
+        }
+        PanelContent::List { items } => {
+            Text::from_iter(items.iter().map(String::as_str)).render(area, buf);
+        }
+        PanelContent::Table { rows } => {
+            let key_width = rows
+                .iter()
+                .map(|(key, _)| key.len())
+                .max()
+                .unwrap_or_default()
+                + 1;
+            let rows = rows.iter().map(|(key, value)| {
+                Row::new([
+                    Line::from(key.as_str()).fg(theme.accent),
+                    Line::from(value.as_str()),
+                ])
+            });
+            Table::new(
+                rows,
+                [Constraint::Length(key_width as u16), Constraint::Fill(1)],
+            )
+            .render(area, buf);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use super::manager;
+use crate::REDRAW;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::Duration;
+use std::{fmt, fs};
+use steel_host::{CapabilityKind, CapabilityRequest, PluginHost};
+use steel_utils::locks::SyncMutex;
+use tokio::sync::oneshot;
+use tokio::time;
+use tracing::{info, warn};
+
+/// Name of the file the decisions are persisted in, inside the plugins directory
+const PERMISSIONS_FILE: &str = "permissions.toml";
+
+/// How long a request waits for an answer before it is denied, nobody may be watching the ui
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Requests waiting for an answer, the first one is shown
+static PROMPTS: LazyLock<SyncMutex<VecDeque<Prompt>>> =
+    LazyLock::new(|| SyncMutex::new(VecDeque::new()));
+
+/// Requests that timed out, denied until the next start without asking again
+static SESSION_DENIED: LazyLock<SyncMutex<HashSet<(String, Capability)>>> =
+    LazyLock::new(|| SyncMutex::new(HashSet::new()));
+
+/// A sensitive host capability a plugin has to ask for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Reading and writing files outside of the data directory of the plugin
+    Filesystem,
+    /// Opening network connections
+    Network,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Filesystem => f.write_str("filesystem"),
+            Self::Network => f.write_str("network"),
+        }
+    }
+}
+
+impl From<CapabilityKind> for Capability {
+    fn from(kind: CapabilityKind) -> Self {
+        match kind {
+            CapabilityKind::Filesystem => Self::Filesystem,
+            CapabilityKind::Network => Self::Network,
+        }
+    }
+}
+
+/// A persisted answer to a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Decision {
+    Allow,
+    Deny,
+}
+
+/// How the operator answered a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    AllowOnce,
+    AllowAlways,
+    Deny,
+}
+
+struct Prompt {
+    plugin: String,
+    capability: Capability,
+    detail: String,
+    reply: oneshot::Sender<Answer>,
+}
+
+type Decisions = BTreeMap<String, BTreeMap<Capability, Decision>>;
+
+fn file() -> Option<PathBuf> {
+    manager().map(|manager| manager.folder.join(PERMISSIONS_FILE))
+}
+
+fn load() -> anyhow::Result<Decisions> {
+    let Some(file) = file() else {
+        return Ok(Decisions::new());
+    };
+    match fs::read_to_string(&file) {
+        Ok(content) => toml::from_str(&content).context("failed to parse the plugin permissions"),
+        Err(_) => Ok(Decisions::new()),
+    }
+}
+
+fn save(decisions: &Decisions) -> anyhow::Result<()> {
+    let file = file().context("the plugin system is not running")?;
+    let content =
+        toml::to_string_pretty(decisions).context("failed to serialize the plugin permissions")?;
+    fs::write(file, content).context("failed to write the plugin permissions")
+}
+
+fn decide(plugin: &str, capability: Capability, decision: Decision) {
+    let result = load().and_then(|mut decisions| {
+        decisions
+            .entry(plugin.to_owned())
+            .or_default()
+            .insert(capability, decision);
+        save(&decisions)
+    });
+    if let Err(err) = result {
+        warn!("Failed to remember the {capability} permission of {plugin}: {err:#}");
+    }
+}
+
+/// Whether `plugin` may use `capability`, asking the operator unless they decided before
+///
+/// `detail` tells the operator what exactly the plugin is about to do. Requests nobody answers in time are denied
+/// until the next start, but asked again after that.
+pub async fn request_capability(plugin: &str, capability: Capability, detail: &str) -> bool {
+    if SESSION_DENIED
+        .lock()
+        .contains(&(plugin.to_owned(), capability))
+    {
+        return false;
+    }
+    let decided = load()
+        .unwrap_or_else(|err| {
+            warn!("{err:#}");
+            Decisions::new()
+        })
+        .get(plugin)
+        .and_then(|decisions| decisions.get(&capability).copied());
+    if let Some(decision) = decided {
+        return decision == Decision::Allow;
+    }
+
+    let (reply, answer) = oneshot::channel();
+    PROMPTS.lock().push_back(Prompt {
+        plugin: plugin.to_owned(),
+        capability,
+        detail: detail.to_owned(),
+        reply,
+    });
+    REDRAW.notify_one();
+
+    let Some(answer) = time::timeout(PROMPT_TIMEOUT, answer)
+        .await
+        .ok()
+        .and_then(Result::ok)
+    else {
+        SESSION_DENIED
+            .lock()
+            .insert((plugin.to_owned(), capability));
+        info!("Denied {plugin} {capability} access until the next start, nobody answered");
+        return false;
+    };
+    match answer {
+        Answer::AllowOnce => true,
+        Answer::AllowAlways => {
+            decide(plugin, capability, Decision::Allow);
+            true
+        }
+        Answer::Deny => {
+            decide(plugin, capability, Decision::Deny);
+            info!("Denied {plugin} {capability} access");
+            false
+        }
+    }
+}
+
+/// Makes the filesystem and network host calls of `host` ask for their capability before doing anything
+pub(super) fn install(host: &mut PluginHost) {
+    host.set_capability_check(|request: CapabilityRequest| {
+        Box::pin(async move {
+            request_capability(&request.plugin, request.kind.into(), &request.detail).await
+        })
+    });
+}
+
+/// The plugin, capability and detail of the request waiting for an answer
+pub fn pending() -> Option<(String, Capability, String)> {
+    // requests that timed out meanwhile are no longer shown
+    let mut prompts = PROMPTS.lock();
+    prompts.retain(|prompt| !prompt.reply.is_closed());
+    prompts.front().map(|prompt| {
+        (
+            prompt.plugin.clone(),
+            prompt.capability,
+            prompt.detail.clone(),
+        )
+    })
+}
+
+/// Answers the request shown by `pending`
+pub fn answer(answer: Answer) {
+    if let Some(prompt) = PROMPTS.lock().pop_front() {
+        let _ = prompt.reply.send(answer);
+    }
+    REDRAW.notify_one();
+}
+
+/// The persisted decisions of `plugin`
+pub fn decisions(plugin: &str) -> anyhow::Result<Vec<String>> {
+    Ok(load()?
+        .remove(plugin)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(capability, decision)| match decision {
+            Decision::Allow => format!("{capability}: allowed"),
+            Decision::Deny => format!("{capability}: denied"),
+        })
+        .chain(
+            SESSION_DENIED
+                .lock()
+                .iter()
+                .filter(|(denied, _)| denied == plugin)
+                .map(|(_, capability)| format!("{capability}: denied until the next start")),
+        )
+        .collect())
+}
+
+/// Forgets the persisted decisions of `plugin`, it is asked again next time
+pub fn reset(plugin: &str) -> anyhow::Result<()> {
+    SESSION_DENIED.lock().retain(|(denied, _)| denied != plugin);
+    let mut decisions = load()?;
+    decisions.remove(plugin);
+    save(&decisions)
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+use steel_utils::locks::SyncMutex;
+
+/// Samples by plugin name and event type
+static PROFILE: LazyLock<SyncMutex<HashMap<(String, &'static str), Sample>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+/// What the handlers of one plugin for one event type cost so far
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sample {
+    /// Number of events handled
+    pub calls: u64,
+    /// Wall time spent in the handlers
+    pub total: Duration,
+    /// Wall time of the slowest call
+    pub max: Duration,
+    /// Fuel burned by the handlers, only tracked if fuel is limited
+    pub fuel: u64,
+}
+
+/// Adds a call of the handler of `plugin` for `topic` to the profile
+pub fn record(plugin: &str, topic: &'static str, elapsed: Duration, fuel: Option<u64>) {
+    let mut profile = PROFILE.lock();
+    let sample = profile.entry((plugin.to_owned(), topic)).or_default();
+    sample.calls += 1;
+    sample.total += elapsed;
+    sample.max = sample.max.max(elapsed);
+    sample.fuel += fuel.unwrap_or_default();
+}
+
+/// Every sample with its plugin and event type, most expensive first
+pub fn report() -> Vec<(String, &'static str, Sample)> {
+    let mut report: Vec<_> = PROFILE
+        .lock()
+        .iter()
+        .map(|((plugin, topic), sample)| (plugin.clone(), *topic, *sample))
+        .collect();
+    report.sort_by(|a, b| b.2.total.cmp(&a.2.total));
+    report
+}
+
+/// Wall time spent in the handlers of `plugin` over every event type
+pub fn total(plugin: &str) -> Duration {
+    PROFILE
+        .lock()
+        .iter()
+        .filter(|((name, _), _)| name == plugin)
+        .map(|(_, sample)| sample.total)
+        .sum()
+}
+
+/// Forgets every sample
+pub fn reset() {
+    PROFILE.lock().clear();
+}
@@ -0,0 +1,86 @@
+use super::PluginManager;
+use anyhow::{Context, bail};
+use steel_host::wasmtime::{FuncType, Val, ValType};
+
+/// Evaluates a REPL `line`, returning what to print
+///
+/// `<plugin>` lists the exported functions of a plugin and `<plugin> <export> [args...]` calls one.
+pub async fn eval(manager: &PluginManager, line: &str) -> Vec<String> {
+    match run(manager, line).await {
+        Ok(output) => output,
+        // the debug output includes the wasm backtrace of a trap
+        Err(err) => format!("{err:?}").lines().map(str::to_owned).collect(),
+    }
+}
+
+async fn run(manager: &PluginManager, line: &str) -> anyhow::Result<Vec<String>> {
+    let mut words = line.split_whitespace();
+    let Some(plugin) = words.next() else {
+        return Ok(Vec::new());
+    };
+    let exports = manager.exports(plugin).await?;
+
+    let Some(export) = words.next() else {
+        if exports.is_empty() {
+            return Ok(vec![format!("{plugin} exports no functions")]);
+        }
+        return Ok(exports
+            .iter()
+            .map(|(name, ty)| signature(name, ty))
+            .collect());
+    };
+
+    let (_, ty) = exports
+        .iter()
+        .find(|(name, _)| name == export)
+        .with_context(|| format!("{plugin} exports no function called {export}"))?;
+    let args: Vec<&str> = words.collect();
+    if args.len() != ty.params().len() {
+        bail!("usage: {plugin} {}", signature(export, ty));
+    }
+    let args = ty
+        .params()
+        .zip(args)
+        .map(|(ty, arg)| parse(&ty, arg))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let results = manager.call(plugin, export, &args).await?;
+    if results.is_empty() {
+        return Ok(vec!["ok".to_owned()]);
+    }
+    Ok(vec![
+        results.iter().map(format).collect::<Vec<_>>().join(", "),
+    ])
+}
+
+/// An export like `add(i32, i32) -> i32`
+fn signature(name: &str, ty: &FuncType) -> String {
+    let params: Vec<String> = ty.params().map(|ty| ty.to_string()).collect();
+    let results: Vec<String> = ty.results().map(|ty| ty.to_string()).collect();
+    if results.is_empty() {
+        format!("{name}({})", params.join(", "))
+    } else {
+        format!("{name}({}) -> {}", params.join(", "), results.join(", "))
+    }
+}
+
+fn parse(ty: &ValType, arg: &str) -> anyhow::Result<Val> {
+    let invalid = || format!("`{arg}` is not a valid {ty}");
+    Ok(match ty {
+        ValType::I32 => Val::I32(arg.parse().with_context(invalid)?),
+        ValType::I64 => Val::I64(arg.parse().with_context(invalid)?),
+        ValType::F32 => Val::F32(arg.parse::<f32>().with_context(invalid)?.to_bits()),
+        ValType::F64 => Val::F64(arg.parse::<f64>().with_context(invalid)?.to_bits()),
+        _ => bail!("arguments of type {ty} can't be typed in"),
+    })
+}
+
+fn format(val: &Val) -> String {
+    match val {
+        Val::I32(value) => value.to_string(),
+        Val::I64(value) => value.to_string(),
+        Val::F32(bits) => f32::from_bits(*bits).to_string(),
+        Val::F64(bits) => f64::from_bits(*bits).to_string(),
+        other => format!("{other:?}"),
+    }
+}
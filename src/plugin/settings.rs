@@ -0,0 +1,82 @@
+use super::manager;
+use anyhow::{Context, bail};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use toml::{Table, Value};
+
+/// Name of the config file in the data directory of every plugin
+const CONFIG_FILE: &str = "config.toml";
+
+/// The directory the plugin called `plugin` keeps its files in, created when the plugin is loaded
+pub fn plugin_data_dir(plugin: &str) -> anyhow::Result<PathBuf> {
+    let manager = manager().context("the plugin system is not running")?;
+    Ok(manager.data_dir(plugin))
+}
+
+/// The content of the config file of `plugin`, empty if it has none yet
+pub fn plugin_config(plugin: &str) -> anyhow::Result<String> {
+    match fs::read_to_string(plugin_data_dir(plugin)?.join(CONFIG_FILE)) {
+        Ok(content) => Ok(content),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(err).with_context(|| format!("failed to read the config of {plugin}")),
+    }
+}
+
+fn read(plugin: &str) -> anyhow::Result<Table> {
+    plugin_config(plugin)?
+        .parse()
+        .with_context(|| format!("failed to parse the config of {plugin}"))
+}
+
+/// Every setting in the config file of `plugin` by its dotted key
+pub fn entries(plugin: &str) -> anyhow::Result<Vec<(String, String)>> {
+    let mut entries = Vec::new();
+    flatten(&read(plugin)?, "", &mut entries);
+    Ok(entries)
+}
+
+fn flatten(table: &Table, prefix: &str, entries: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            Value::Table(table) => flatten(table, &key, entries),
+            value => entries.push((key, value.to_string())),
+        }
+    }
+}
+
+/// Sets the dotted `key` in the config file of `plugin` to `value`
+///
+/// The value is read as toml and kept as a string if it isn't valid toml.
+pub fn set(plugin: &str, key: &str, value: &str) -> anyhow::Result<()> {
+    let value = toml::from_str::<Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(value.to_owned()));
+
+    let mut config = read(plugin)?;
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let Some(last) = parts.pop() else {
+        bail!("empty key");
+    };
+    let mut table = &mut config;
+    for part in parts {
+        let entry = table
+            .entry(part)
+            .or_insert_with(|| Value::Table(Table::new()));
+        let Value::Table(inner) = entry else {
+            bail!("{part} is not a table");
+        };
+        table = inner;
+    }
+    table.insert(last.to_owned(), value);
+
+    let content = toml::to_string_pretty(&config).context("failed to serialize the config")?;
+    fs::write(plugin_data_dir(plugin)?.join(CONFIG_FILE), content)
+        .with_context(|| format!("failed to write the config of {plugin}"))
+}
@@ -0,0 +1,100 @@
+use crate::REDRAW;
+use std::fmt;
+use std::sync::{LazyLock, OnceLock};
+use std::time::{Duration, Instant};
+use steel_utils::locks::SyncMutex;
+use tokio::runtime::Handle;
+
+/// Progress of every plugin while the plugin system starts, `None` once it started
+static STARTUP: LazyLock<SyncMutex<Option<Vec<Progress>>>> = LazyLock::new(|| SyncMutex::new(None));
+
+/// The runtime wasm modules are compiled on, the current one if unset
+static COMPILE_RUNTIME: OnceLock<Handle> = OnceLock::new();
+
+/// How far a plugin got while the plugin system starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Discovered,
+    Compiled,
+    Linked,
+    Enabled,
+    Failed,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Discovered => f.write_str("discovered"),
+            Self::Compiled => f.write_str("compiled"),
+            Self::Linked => f.write_str("linked"),
+            Self::Enabled => f.write_str("enabled"),
+            Self::Failed => f.write_str("failed"),
+        }
+    }
+}
+
+/// A plugin in the startup progress
+#[derive(Debug, Clone)]
+pub struct Progress {
+    /// Name of the plugin
+    pub name: String,
+    /// The last stage the plugin reached
+    pub stage: Stage,
+    /// Time from discovering the plugin until it reached its stage
+    pub elapsed: Duration,
+    started: Instant,
+}
+
+/// Compiles plugins on `runtime`, so compiling large modules doesn't hold up the runtime of the server
+pub fn compile_plugins_on(runtime: Handle) {
+    let _ = COMPILE_RUNTIME.set(runtime);
+}
+
+/// The runtime to compile plugins on
+pub(super) fn compile_runtime() -> Handle {
+    COMPILE_RUNTIME
+        .get()
+        .cloned()
+        .unwrap_or_else(Handle::current)
+}
+
+/// Starts showing the progress of the plugins called `names`
+pub(super) fn discovered<'a>(names: impl Iterator<Item = &'a str>) {
+    let started = Instant::now();
+    *STARTUP.lock() = Some(
+        names
+            .map(|name| Progress {
+                name: name.to_owned(),
+                stage: Stage::Discovered,
+                elapsed: Duration::ZERO,
+                started,
+            })
+            .collect(),
+    );
+    REDRAW.notify_one();
+}
+
+/// Moves the plugin called `name` on to `stage`
+pub(super) fn advance(name: &str, stage: Stage) {
+    if let Some(progress) = STARTUP
+        .lock()
+        .iter_mut()
+        .flatten()
+        .find(|progress| progress.name == name)
+    {
+        progress.stage = stage;
+        progress.elapsed = progress.started.elapsed();
+    }
+    REDRAW.notify_one();
+}
+
+/// Stops showing the startup progress
+pub(super) fn finish() {
+    *STARTUP.lock() = None;
+    REDRAW.notify_one();
+}
+
+/// The progress of every plugin while the plugin system starts
+pub fn progress() -> Option<Vec<Progress>> {
+    STARTUP.lock().clone()
+}
@@ -0,0 +1,74 @@
+use super::settings::plugin_data_dir;
+use anyhow::Context;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::ErrorKind;
+use std::sync::LazyLock;
+use steel_utils::locks::SyncMutex;
+
+/// Name of the file the values are persisted in, inside the data directory of every plugin
+const STORE_FILE: &str = "store.json";
+
+/// The stores of the plugins that used theirs since the server started, by plugin name
+static STORES: LazyLock<SyncMutex<HashMap<String, BTreeMap<String, String>>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+fn load(plugin: &str) -> anyhow::Result<BTreeMap<String, String>> {
+    match fs::read_to_string(plugin_data_dir(plugin)?.join(STORE_FILE)) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse the store of {plugin}")),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err).with_context(|| format!("failed to read the store of {plugin}")),
+    }
+}
+
+/// Runs `f` on the store of `plugin`, loading it on first use
+fn with_store<T>(
+    plugin: &str,
+    f: impl FnOnce(&mut BTreeMap<String, String>) -> T,
+) -> anyhow::Result<T> {
+    let mut stores = STORES.lock();
+    if !stores.contains_key(plugin) {
+        stores.insert(plugin.to_owned(), load(plugin)?);
+    }
+    let store = stores.entry(plugin.to_owned()).or_default();
+    Ok(f(store))
+}
+
+/// Writes the store of `plugin` back to its file
+fn persist(plugin: &str, store: &BTreeMap<String, String>) -> anyhow::Result<()> {
+    let content = serde_json::to_string_pretty(store).context("failed to serialize the store")?;
+    fs::write(plugin_data_dir(plugin)?.join(STORE_FILE), content)
+        .with_context(|| format!("failed to write the store of {plugin}"))
+}
+
+/// The value `plugin` stored under `key`
+pub fn plugin_store_get(plugin: &str, key: &str) -> anyhow::Result<Option<String>> {
+    with_store(plugin, |store| store.get(key).cloned())
+}
+
+/// Stores `value` under `key` for `plugin`, persisted right away
+pub fn plugin_store_set(plugin: &str, key: &str, value: &str) -> anyhow::Result<()> {
+    with_store(plugin, |store| {
+        store.insert(key.to_owned(), value.to_owned());
+        persist(plugin, store)
+    })?
+}
+
+/// Removes what `plugin` stored under `key`, returning whether there was something
+pub fn plugin_store_remove(plugin: &str, key: &str) -> anyhow::Result<bool> {
+    with_store(plugin, |store| {
+        let removed = store.remove(key).is_some();
+        persist(plugin, store).map(|()| removed)
+    })?
+}
+
+/// Every key and value stored by `plugin`
+pub fn entries(plugin: &str) -> anyhow::Result<Vec<(String, String)>> {
+    with_store(plugin, |store| {
+        store
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    })
+}
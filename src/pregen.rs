@@ -0,0 +1,114 @@
+use crate::confirmation;
+use crate::jobs::Job;
+use crate::logger::ServerLog;
+use crate::worlds;
+use anyhow::{Context, bail};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use steel_core::server::Server;
+use steel_core::world::World;
+use steel_utils::ChunkPos;
+use tokio::runtime::Handle;
+use tokio::select;
+use tracing::info;
+
+const USAGE: &str = "usage: pregen <radius in chunks> [world]";
+
+/// Largest radius in chunks that can be pregenerated at once
+const MAX_RADIUS: i32 = 500;
+
+/// Chunks generated between two progress reports and checks for pausing or cancelling
+const BATCH: usize = 64;
+
+/// The chunk runtime chunks are pregenerated on, the one running the command if not set
+static RUNTIME: OnceLock<Handle> = OnceLock::new();
+
+/// Pregenerates chunks on `runtime`, so generating them doesn't hold up the runtime of the server
+pub fn pregenerate_on(runtime: Handle) {
+    let _ = RUNTIME.set(runtime);
+}
+
+/// Runs the `pregen` console command, returning its output
+///
+/// `pregen <radius> [world]` generates every chunk within `radius` chunks of the spawn of the world, the overworld
+/// unless another one is given, nearest first. It runs in the background on the chunk runtime and shows up in the
+/// running tab, where it can be paused, resumed and cancelled.
+pub fn command(server: &Server, args: &str) -> anyhow::Result<Vec<String>> {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let (radius, world) = match words.as_slice() {
+        [radius] => (*radius, "overworld"),
+        [radius, world] => (*radius, *world),
+        _ => bail!(USAGE),
+    };
+    let radius: i32 = radius
+        .parse()
+        .with_context(|| format!("`{radius}` is not a radius in chunks"))?;
+    if !(1..=MAX_RADIUS).contains(&radius) {
+        bail!("the radius has to be between 1 and {MAX_RADIUS} chunks");
+    }
+    let (name, world) =
+        worlds::find(server, world).with_context(|| format!("there is no world called {world}"))?;
+    let world = world.clone();
+
+    let chunks = around(world.spawn_position().chunk_pos(), radius);
+    let total = chunks.len();
+    if !confirmation::confirm(format!(
+        "This will generate up to {total} chunks of {name}, continue?"
+    )) {
+        return Ok(vec!["Cancelled the pregeneration".to_owned()]);
+    }
+
+    let job = Job::start(format!("pregen {radius} {name}")).pausable();
+    let log = ServerLog::current();
+    let output = vec![format!(
+        "Pregenerating {total} chunks of {name}, the running tab shows how far it got"
+    )];
+    // the world is gone once the server stopped, generating stops along with it
+    let server_token = server.cancel_token.clone();
+    let runtime = RUNTIME.get().cloned().unwrap_or_else(Handle::current);
+    runtime.spawn(async move {
+        select! {
+            () = server_token.cancelled() => {
+                log.scope(|| info!("Stopped pregenerating {name} since the server stopped"));
+            }
+            () = generate(world, &chunks, &job, log, &name) => (),
+        }
+    });
+    Ok(output)
+}
+
+/// Generates `chunks` of `world` in batches, reporting the progress to `job` and stopping if it is cancelled
+async fn generate(world: Arc<World>, chunks: &[ChunkPos], job: &Job, log: ServerLog, name: &str) {
+    let started = Instant::now();
+    let total = chunks.len();
+    let mut done = 0;
+    for batch in chunks.chunks(BATCH) {
+        job.wait_while_paused().await;
+        if job.is_cancelled() {
+            log.scope(|| info!("Cancelled pregenerating {name} after {done} of {total} chunks"));
+            return;
+        }
+        // chunks generated before are only loaded, which is fast
+        world.chunk_map.generate_chunks(batch).await;
+        done += batch.len();
+        job.progress(done as u64, total as u64);
+    }
+    log.scope(|| {
+        info!(
+            "Pregenerated {total} chunks of {name} in {:.1?}",
+            started.elapsed()
+        );
+    });
+}
+
+/// Every chunk within `radius` chunks of `center`, nearest first
+fn around(center: ChunkPos, radius: i32) -> Vec<ChunkPos> {
+    let mut chunks: Vec<(i32, i32)> = (-radius..=radius)
+        .flat_map(|x| (-radius..=radius).map(move |z| (x, z)))
+        .collect();
+    chunks.sort_by_key(|(x, z)| x.abs().max(z.abs()));
+    chunks
+        .into_iter()
+        .map(|(x, z)| ChunkPos::new(center.x + x, center.z + z))
+        .collect()
+}
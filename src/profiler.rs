@@ -0,0 +1,87 @@
+use anyhow::{Context, bail};
+use pprof::{ProfilerGuard, ProfilerGuardBuilder};
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use steel_utils::locks::SyncMutex;
+
+const USAGE: &str = "usage: profile <start | stop>";
+
+/// Directory flamegraphs are written into
+const PROFILE_DIR: &str = "profiles";
+
+/// Samples taken per second while profiling
+const FREQUENCY: i32 = 99;
+
+/// The running profiler and when it was started
+static PROFILER: LazyLock<SyncMutex<Option<(ProfilerGuard<'static>, Instant)>>> =
+    LazyLock::new(|| SyncMutex::new(None));
+
+/// Runs the `profile` console command, returning its output
+///
+/// `profile start` samples the stacks of every thread until `profile stop` writes them as a flamegraph.
+pub fn command(args: &str) -> anyhow::Result<Vec<String>> {
+    match args {
+        "start" => {
+            start()?;
+            Ok(vec![
+                "Started profiling, `profile stop` writes the flamegraph".to_owned(),
+            ])
+        }
+        "stop" => {
+            let path = stop()?;
+            Ok(vec![format!("Wrote the flamegraph to {}", path.display())])
+        }
+        _ => bail!(USAGE),
+    }
+}
+
+fn start() -> anyhow::Result<()> {
+    let mut profiler = PROFILER.lock();
+    if profiler.is_some() {
+        bail!("the profiler is already running");
+    }
+    let guard = ProfilerGuardBuilder::default()
+        .frequency(FREQUENCY)
+        // samples taken inside these can't be unwound safely
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .context("failed to start the profiler")?;
+    *profiler = Some((guard, Instant::now()));
+    Ok(())
+}
+
+/// Stops the profiler and writes what it sampled as a flamegraph, returning its path
+fn stop() -> anyhow::Result<PathBuf> {
+    let (guard, _) = PROFILER
+        .lock()
+        .take()
+        .context("the profiler is not running")?;
+    let report = guard
+        .report()
+        .build()
+        .context("failed to build the profile")?;
+    drop(guard);
+
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    fs::create_dir_all(PROFILE_DIR).context("failed to create the profiles directory")?;
+    let path = PathBuf::from(PROFILE_DIR).join(format!("profile-{secs}.svg"));
+    let file =
+        File::create(&path).with_context(|| format!("failed to create {}", path.display()))?;
+    report
+        .flamegraph(file)
+        .context("failed to write the flamegraph")?;
+    Ok(path)
+}
+
+/// How long the profiler has been running, if it is
+pub fn running() -> Option<Duration> {
+    PROFILER
+        .lock()
+        .as_ref()
+        .map(|(_, started)| started.elapsed())
+}
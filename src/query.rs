@@ -0,0 +1,411 @@
+use crate::config::QueryConfig;
+use crate::logger::ServerLog;
+use crate::{reload, status};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use steel_core::server::Server;
+use steel_registry::packets::CURRENT_MC_VERSION;
+use steel_utils::locks::SyncMutex;
+use tokio::net::UdpSocket;
+use tokio::select;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+const MAGIC: [u8; 2] = [0xFE, 0xFD];
+const HANDSHAKE: u8 = 9;
+const STAT: u8 = 0;
+
+/// Length of a basic stat request, a full stat request pads it with four more bytes
+const BASIC_STAT_LEN: usize = 11;
+const FULL_STAT_LEN: usize = 15;
+
+/// How long a challenge token stays valid, like in vanilla
+const CHALLENGE_LIFETIME: Duration = Duration::from_secs(30);
+
+/// Largest request a client may send
+const MAX_REQUEST: usize = 64;
+
+/// Most challenge tokens kept at once, the oldest one is dropped for a new one beyond that
+const MAX_CHALLENGES: usize = 4096;
+
+/// How often challenge tokens that ran out are forgotten
+const EXPIRE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The ports query is running on, by the log of the server it answers for
+static RUNNING: LazyLock<SyncMutex<Vec<(ServerLog, u16)>>> =
+    LazyLock::new(|| SyncMutex::new(Vec::new()));
+
+/// What query answers with
+struct Stat {
+    motd: String,
+    map: String,
+    players: Vec<String>,
+    max_players: u32,
+    port: u16,
+    plugins: Vec<String>,
+}
+
+impl Stat {
+    fn collect(server: &Server, log: ServerLog) -> Self {
+        let config = log.scope(reload::server_config);
+        let (motd, max_players, port) = config.map_or_else(
+            |_| (String::new(), 0, 0),
+            |config| (config.motd, config.max_players, config.server_port),
+        );
+        Self {
+            motd,
+            map: status::worlds(server)
+                .into_iter()
+                .next()
+                .map(|world| world.name)
+                .unwrap_or_default(),
+            players: status::players(server)
+                .into_iter()
+                .map(|player| player.name)
+                .collect(),
+            max_players,
+            port,
+            plugins: status::plugins(),
+        }
+    }
+
+    /// The answer to a basic stat request, the host port being the one field in little endian
+    fn basic(&self, session: i32) -> Vec<u8> {
+        let mut buf = response(STAT, session);
+        for value in [
+            self.motd.as_str(),
+            "SMP",
+            &self.map,
+            &self.players.len().to_string(),
+            &self.max_players.to_string(),
+        ] {
+            push_str(&mut buf, value);
+        }
+        buf.extend_from_slice(&self.port.to_le_bytes());
+        push_str(&mut buf, &Ipv4Addr::UNSPECIFIED.to_string());
+        buf
+    }
+
+    /// The answer to a full stat request, every field as a key and value followed by the player names
+    fn full(&self, session: i32) -> Vec<u8> {
+        let mut buf = response(STAT, session);
+        // padding clients expect, vanilla sends the same
+        buf.extend_from_slice(b"splitnum\0\x80\0");
+        let plugins = if self.plugins.is_empty() {
+            "Steel".to_owned()
+        } else {
+            format!("Steel: {}", self.plugins.join("; "))
+        };
+        for (key, value) in [
+            ("hostname", self.motd.as_str()),
+            ("gametype", "SMP"),
+            ("game_id", "MINECRAFT"),
+            ("version", CURRENT_MC_VERSION),
+            ("plugins", &plugins),
+            ("map", &self.map),
+            ("numplayers", &self.players.len().to_string()),
+            ("maxplayers", &self.max_players.to_string()),
+            ("hostport", &self.port.to_string()),
+            ("hostip", &Ipv4Addr::UNSPECIFIED.to_string()),
+        ] {
+            push_str(&mut buf, key);
+            push_str(&mut buf, value);
+        }
+        buf.push(0);
+        buf.extend_from_slice(b"\x01player_\0\0");
+        for player in &self.players {
+            push_str(&mut buf, player);
+        }
+        buf.push(0);
+        buf
+    }
+}
+
+/// The start of every response, its type and the session it answers
+fn response(kind: u8, session: i32) -> Vec<u8> {
+    let mut buf = vec![kind];
+    buf.extend_from_slice(&session.to_be_bytes());
+    buf
+}
+
+/// Appends `value` as a null terminated string, query predates UTF-8 so clients read it as Latin-1
+fn push_str(buf: &mut Vec<u8>, value: &str) {
+    buf.extend(
+        value
+            .chars()
+            .map(|char| u8::try_from(char).unwrap_or(b'?'))
+            .filter(|byte| *byte != 0),
+    );
+    buf.push(0);
+}
+
+/// Challenge tokens handed out in handshakes, by the address they were handed to
+#[derive(Default)]
+struct Challenges {
+    tokens: HashMap<SocketAddr, (i32, Instant)>,
+    /// Seeds the next token, clients only have to echo it back
+    next: u32,
+}
+
+impl Challenges {
+    /// Forgets the tokens that ran out
+    fn expire(&mut self, now: Instant) {
+        self.tokens
+            .retain(|_, (_, issued)| now.duration_since(*issued) < CHALLENGE_LIFETIME);
+    }
+
+    fn issue(&mut self, peer: SocketAddr) -> i32 {
+        let now = Instant::now();
+        // handshakes from spoofed addresses must not grow the map without bound
+        if self.tokens.len() >= MAX_CHALLENGES && !self.tokens.contains_key(&peer) {
+            self.expire(now);
+            let oldest = self
+                .tokens
+                .iter()
+                .min_by_key(|(_, (_, issued))| *issued)
+                .map(|(peer, _)| *peer);
+            if let Some(oldest) = oldest.filter(|_| self.tokens.len() >= MAX_CHALLENGES) {
+                self.tokens.remove(&oldest);
+            }
+        }
+        // any number works, it only proves the client can receive packets at its address
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos();
+        self.next = self.next.wrapping_mul(1_103_515_245).wrapping_add(nanos);
+        let token = (self.next >> 1) as i32;
+        self.tokens.insert(peer, (token, now));
+        token
+    }
+
+    fn is_valid(&self, peer: SocketAddr, token: i32) -> bool {
+        self.tokens
+            .get(&peer)
+            .is_some_and(|(issued, at)| *issued == token && at.elapsed() < CHALLENGE_LIFETIME)
+    }
+}
+
+/// The response to `request` from `peer`, if it is a valid query request
+fn answer(
+    request: &[u8],
+    peer: SocketAddr,
+    challenges: &mut Challenges,
+    stat: impl FnOnce() -> Stat,
+) -> Option<Vec<u8>> {
+    let (magic, rest) = request.split_first_chunk::<2>()?;
+    let (&kind, rest) = rest.split_first()?;
+    let (session, rest) = rest.split_first_chunk::<4>()?;
+    if *magic != MAGIC {
+        return None;
+    }
+    // clients may only use the lower four bits of every byte
+    let session = i32::from_be_bytes(*session) & 0x0F0F_0F0F;
+
+    match (kind, request.len()) {
+        (HANDSHAKE, _) => {
+            let mut buf = response(HANDSHAKE, session);
+            push_str(&mut buf, &challenges.issue(peer).to_string());
+            Some(buf)
+        }
+        (STAT, BASIC_STAT_LEN | FULL_STAT_LEN) => {
+            let token = i32::from_be_bytes(*rest.first_chunk::<4>()?);
+            if !challenges.is_valid(peer, token) {
+                return None;
+            }
+            let stat = stat();
+            Some(if request.len() == FULL_STAT_LEN {
+                stat.full(session)
+            } else {
+                stat.basic(session)
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Answers the query protocol if it is enabled, so server lists and monitoring like Gamedig can read the players
+/// and plugins, until `token` is cancelled
+pub fn spawn(server: Arc<Server>, config: QueryConfig, token: CancellationToken) {
+    if !config.enabled {
+        return;
+    }
+    let log = ServerLog::current();
+
+    tokio::spawn(async move {
+        let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, config.port));
+        let socket = match UdpSocket::bind(addr).await {
+            Ok(socket) => socket,
+            Err(err) => {
+                warn!("Failed to bind query to {addr}: {err}");
+                return;
+            }
+        };
+        info!("Query running on {addr}");
+        RUNNING.lock().push((log, config.port));
+
+        let mut challenges = Challenges::default();
+        let mut expire = time::interval(EXPIRE_INTERVAL);
+        let mut buf = [0; MAX_REQUEST];
+        loop {
+            let (len, peer) = select! {
+                () = token.cancelled() => break,
+                _ = expire.tick() => {
+                    challenges.expire(Instant::now());
+                    continue;
+                }
+                received = socket.recv_from(&mut buf) => match received {
+                    Ok(received) => received,
+                    Err(err) => {
+                        debug!("Failed to receive a query request: {err}");
+                        continue;
+                    }
+                },
+            };
+            let Some(response) = answer(&buf[..len], peer, &mut challenges, || {
+                Stat::collect(&server, log)
+            }) else {
+                continue;
+            };
+            if let Err(err) = socket.send_to(&response, peer).await {
+                debug!("Failed to answer the query request from {peer}: {err}");
+            }
+        }
+
+        RUNNING.lock().retain(|(running, _)| *running != log);
+    });
+}
+
+/// The port query is running on for the server logging into `log`, if it is
+pub fn port(log: ServerLog) -> Option<u16> {
+    RUNNING
+        .lock()
+        .iter()
+        .find(|(running, _)| *running == log)
+        .map(|(_, port)| *port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Challenges, MAX_CHALLENGES, Stat, answer};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::str;
+    use steel_registry::packets::CURRENT_MC_VERSION;
+
+    const SESSION: [u8; 4] = [0x01, 0x02, 0x03, 0x04];
+
+    fn peer(port: u16) -> SocketAddr {
+        SocketAddr::from((Ipv4Addr::LOCALHOST, port))
+    }
+
+    fn stat() -> Stat {
+        Stat {
+            motd: "A Steel server".to_owned(),
+            map: "world".to_owned(),
+            players: vec!["Alex".to_owned(), "Steve".to_owned()],
+            max_players: 20,
+            port: 25565,
+            plugins: vec!["chat".to_owned()],
+        }
+    }
+
+    /// Does the handshake for `peer`, returning the challenge token it was given
+    fn handshake(challenges: &mut Challenges, peer: SocketAddr) -> i32 {
+        let request = [&[0xFE, 0xFD, 9][..], &SESSION].concat();
+        let response = answer(&request, peer, challenges, stat).expect("a handshake is answered");
+        assert_eq!(response[..5], [9, 0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(response.last(), Some(&0));
+        str::from_utf8(&response[5..response.len() - 1])
+            .expect("the token is ASCII")
+            .parse()
+            .expect("the token is a number")
+    }
+
+    fn stat_request(token: i32, full: bool) -> Vec<u8> {
+        let mut request = [&[0xFE, 0xFD, 0][..], &SESSION, &token.to_be_bytes()].concat();
+        if full {
+            request.extend_from_slice(&[0; 4]);
+        }
+        request
+    }
+
+    #[test]
+    fn basic_stat() {
+        let mut challenges = Challenges::default();
+        let token = handshake(&mut challenges, peer(1));
+        let response = answer(&stat_request(token, false), peer(1), &mut challenges, stat)
+            .expect("a basic stat request is answered");
+
+        let mut expected = vec![0, 0x01, 0x02, 0x03, 0x04];
+        expected.extend_from_slice(b"A Steel server\0SMP\0world\02\020\0");
+        expected.extend_from_slice(&[0xDD, 0x63]);
+        expected.extend_from_slice(b"0.0.0.0\0");
+        assert_eq!(response, expected);
+    }
+
+    #[test]
+    fn full_stat() {
+        let mut challenges = Challenges::default();
+        let token = handshake(&mut challenges, peer(1));
+        let response = answer(&stat_request(token, true), peer(1), &mut challenges, stat)
+            .expect("a full stat request is answered");
+
+        let mut expected = vec![0, 0x01, 0x02, 0x03, 0x04];
+        expected.extend_from_slice(b"splitnum\0\x80\0");
+        for (key, value) in [
+            ("hostname", "A Steel server"),
+            ("gametype", "SMP"),
+            ("game_id", "MINECRAFT"),
+            ("version", CURRENT_MC_VERSION),
+            ("plugins", "Steel: chat"),
+            ("map", "world"),
+            ("numplayers", "2"),
+            ("maxplayers", "20"),
+            ("hostport", "25565"),
+            ("hostip", "0.0.0.0"),
+        ] {
+            expected.extend_from_slice(format!("{key}\0{value}\0").as_bytes());
+        }
+        expected.push(0);
+        expected.extend_from_slice(b"\x01player_\0\0Alex\0Steve\0\0");
+        assert_eq!(response, expected);
+    }
+
+    #[test]
+    fn session_keeps_the_lower_four_bits() {
+        let mut challenges = Challenges::default();
+        let request = [0xFE, 0xFD, 9, 0xFF, 0xFF, 0xFF, 0xFF];
+        let response = answer(&request, peer(1), &mut challenges, stat).expect("answered");
+        assert_eq!(response[..5], [9, 0x0F, 0x0F, 0x0F, 0x0F]);
+    }
+
+    #[test]
+    fn rejects_wrong_tokens_and_peers() {
+        let mut challenges = Challenges::default();
+        let token = handshake(&mut challenges, peer(1));
+        let wrong = token.wrapping_add(1);
+        assert!(answer(&stat_request(wrong, false), peer(1), &mut challenges, stat).is_none());
+        assert!(answer(&stat_request(token, false), peer(2), &mut challenges, stat).is_none());
+        // neither a request of another length nor one without the magic is answered
+        let mut odd = stat_request(token, false);
+        odd.push(0);
+        assert!(answer(&odd, peer(1), &mut challenges, stat).is_none());
+        let mut request = stat_request(token, false);
+        request[0] = 0;
+        assert!(answer(&request, peer(1), &mut challenges, stat).is_none());
+    }
+
+    #[test]
+    fn challenges_are_capped() {
+        let mut challenges = Challenges::default();
+        for port in 0..=MAX_CHALLENGES as u16 {
+            challenges.issue(peer(port));
+        }
+        // one of the oldest made room for the newest
+        assert_eq!(challenges.tokens.len(), MAX_CHALLENGES);
+        assert!(challenges.tokens.contains_key(&peer(MAX_CHALLENGES as u16)));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::command::{self, CommandOrigin};
+use crate::config::RconConfig;
+use anyhow::{Context, bail};
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use steel_core::server::Server;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+const SERVERDATA_RESPONSE_VALUE: i32 = 0;
+const SERVERDATA_EXECCOMMAND: i32 = 2;
+const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+const SERVERDATA_AUTH: i32 = 3;
+
+/// Largest packet a client may send, matching the vanilla server
+const MAX_CLIENT_PACKET: usize = 1460;
+/// Largest body of a single response packet, longer responses are split
+const MAX_RESPONSE_BODY: usize = 4096;
+
+struct Packet {
+    id: i32,
+    kind: i32,
+    body: String,
+}
+
+impl Packet {
+    async fn read(stream: &mut TcpStream) -> anyhow::Result<Option<Self>> {
+        let length = match stream.read_i32_le().await {
+            Ok(length) => length as usize,
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        if !(10..=MAX_CLIENT_PACKET).contains(&length) {
+            bail!("invalid packet length {length}");
+        }
+
+        let mut buf = vec![0; length];
+        stream.read_exact(&mut buf).await?;
+
+        let id = i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]);
+        let kind = i32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]);
+        let body = String::from_utf8_lossy(&buf[8..length - 2]).into_owned();
+        Ok(Some(Self { id, kind, body }))
+    }
+
+    async fn write(stream: &mut TcpStream, id: i32, kind: i32, body: &str) -> anyhow::Result<()> {
+        let mut buf = Vec::with_capacity(body.len() + 14);
+        buf.extend_from_slice(&(body.len() as i32 + 10).to_le_bytes());
+        buf.extend_from_slice(&id.to_le_bytes());
+        buf.extend_from_slice(&kind.to_le_bytes());
+        buf.extend_from_slice(body.as_bytes());
+        buf.extend_from_slice(&[0, 0]);
+        stream.write_all(&buf).await?;
+        Ok(())
+    }
+}
+
+/// Starts the RCON server if it is enabled, it stops once `token` is cancelled
+pub fn spawn(server: Arc<Server>, config: RconConfig, token: CancellationToken) {
+    if !config.enabled {
+        return;
+    }
+    if config.password.is_empty() {
+        warn!("RCON is enabled but has no password set, refusing to start");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, config.port));
+        let listener = match TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                warn!("Failed to bind RCON to {addr}: {err}");
+                return;
+            }
+        };
+        info!("RCON running on {addr}");
+
+        let password: Arc<str> = config.password.into();
+        loop {
+            let (stream, peer) = select! {
+                () = token.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        warn!("Failed to accept RCON connection: {err}");
+                        continue;
+                    }
+                },
+            };
+
+            let server = server.clone();
+            let password = password.clone();
+            let token = token.clone();
+            tokio::spawn(async move {
+                select! {
+                    () = token.cancelled() => (),
+                    result = handle_client(stream, peer, &server, &password, &token) => {
+                        if let Err(err) = result {
+                            warn!("RCON connection from {peer} closed: {err:#}");
+                        }
+                    }
+                }
+            });
+        }
+    });
+}
+
+async fn handle_client(
+    mut stream: TcpStream,
+    peer: SocketAddr,
+    server: &Arc<Server>,
+    password: &str,
+    token: &CancellationToken,
+) -> anyhow::Result<()> {
+    let mut authenticated = false;
+    let client = peer.to_string();
+
+    while let Some(packet) = Packet::read(&mut stream)
+        .await
+        .context("failed to read packet")?
+    {
+        match packet.kind {
+            SERVERDATA_AUTH if packet.body == password => {
+                authenticated = true;
+                Packet::write(&mut stream, packet.id, SERVERDATA_AUTH_RESPONSE, "").await?;
+            }
+            SERVERDATA_AUTH => {
+                authenticated = false;
+                Packet::write(&mut stream, -1, SERVERDATA_AUTH_RESPONSE, "").await?;
+            }
+            SERVERDATA_EXECCOMMAND if authenticated && !token.is_cancelled() => {
+                let output =
+                    command::dispatch(server, CommandOrigin::Rcon, Some(&client), packet.body)
+                        .join("\n");
+                write_response(&mut stream, packet.id, &output).await?;
+            }
+            SERVERDATA_EXECCOMMAND => {
+                Packet::write(&mut stream, -1, SERVERDATA_AUTH_RESPONSE, "").await?;
+            }
+            kind => {
+                let body = format!("Unknown request {kind:x}");
+                Packet::write(&mut stream, packet.id, SERVERDATA_RESPONSE_VALUE, &body).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn write_response(stream: &mut TcpStream, id: i32, output: &str) -> anyhow::Result<()> {
+    let mut rest = output;
+    loop {
+        let mut end = rest.len().min(MAX_RESPONSE_BODY);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        Packet::write(stream, id, SERVERDATA_RESPONSE_VALUE, chunk).await?;
+
+        if tail.is_empty() {
+            return Ok(());
+        }
+        rest = tail;
+    }
+}
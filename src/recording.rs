@@ -0,0 +1,105 @@
+use crate::command::CommandOrigin;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use steel_utils::locks::SyncMutex;
+
+static RECORDER: OnceLock<SyncMutex<Recorder>> = OnceLock::new();
+
+struct Recorder {
+    file: BufWriter<File>,
+    path: PathBuf,
+    start: Instant,
+}
+
+/// Something that happened in a recorded session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Record {
+    /// Text written to the log, including its ansi colors
+    Log { text: String },
+    /// A command executed from any interface
+    Command {
+        origin: CommandOrigin,
+        command: String,
+    },
+}
+
+/// A record together with the time it happened at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// Milliseconds since the recording started
+    pub at_ms: u64,
+    pub record: Record,
+}
+
+impl Entry {
+    pub const fn at(&self) -> Duration {
+        Duration::from_millis(self.at_ms)
+    }
+}
+
+/// Starts recording the session into `path` as json lines, replacing the file if it exists
+pub fn start_recording(path: &Path) -> io::Result<()> {
+    let recorder = Recorder {
+        file: BufWriter::new(File::create(path)?),
+        path: path.to_owned(),
+        start: Instant::now(),
+    };
+    RECORDER
+        .set(SyncMutex::new(recorder))
+        .map_err(|_| io::Error::other("already recording"))
+}
+
+fn record(record: Record) {
+    let Some(recorder) = RECORDER.get() else {
+        return;
+    };
+    let mut recorder = recorder.lock();
+    let entry = Entry {
+        at_ms: recorder.start.elapsed().as_millis() as u64,
+        record,
+    };
+
+    // a broken recording must not take the console down with it
+    if let Ok(line) = serde_json::to_string(&entry) {
+        let _ = writeln!(recorder.file, "{line}");
+        let _ = recorder.file.flush();
+    }
+}
+
+/// The file the session is recorded into, if it is
+pub fn path() -> Option<PathBuf> {
+    RECORDER.get().map(|recorder| recorder.lock().path.clone())
+}
+
+/// Records text written to the log
+pub fn log(text: &str) {
+    record(Record::Log {
+        text: text.to_owned(),
+    });
+}
+
+/// Records a command executed from `origin`
+pub fn command(origin: CommandOrigin, command: &str) {
+    record(Record::Command {
+        origin,
+        command: command.to_owned(),
+    });
+}
+
+/// Reads every entry of the recording at `path`
+pub fn load(path: &Path) -> anyhow::Result<Vec<Entry>> {
+    let file = File::open(path).context("failed to open recording")?;
+    BufReader::new(file)
+        .lines()
+        .map(|line| {
+            let line = line.context("failed to read recording")?;
+            serde_json::from_str(&line).context("invalid recording entry")
+        })
+        .collect()
+}
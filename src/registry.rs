@@ -0,0 +1,88 @@
+use std::sync::LazyLock;
+use steel_registry::REGISTRY;
+
+/// Every entry of the registries that can be browsed, built once since they don't change while the server runs
+static ENTRIES: LazyLock<Vec<Entry>> = LazyLock::new(collect);
+
+/// A registry that can be browsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Registry {
+    Items,
+    Blocks,
+    Biomes,
+    Recipes,
+}
+
+impl Registry {
+    pub const ALL: [Self; 4] = [Self::Items, Self::Blocks, Self::Biomes, Self::Recipes];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Items => "items",
+            Self::Blocks => "blocks",
+            Self::Biomes => "biomes",
+            Self::Recipes => "recipes",
+        }
+    }
+}
+
+/// An entry of a registry with what is known about it
+pub struct Entry {
+    pub registry: Registry,
+    pub key: String,
+    pub id: usize,
+    /// Properties of the entry, like the states of a block or the result of a recipe
+    pub details: Vec<String>,
+}
+
+fn collect() -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for (id, item) in REGISTRY.items.iter() {
+        entries.push(Entry {
+            registry: Registry::Items,
+            key: item.key.to_string(),
+            id,
+            details: vec![format!("Max stack size {}", item.max_stack_size())],
+        });
+    }
+    for (id, block) in REGISTRY.blocks.iter() {
+        entries.push(Entry {
+            registry: Registry::Blocks,
+            key: block.key.to_string(),
+            id,
+            details: block
+                .default_state()
+                .properties()
+                .into_iter()
+                .map(|(name, value)| format!("{name} = {value} by default"))
+                .collect(),
+        });
+    }
+    for (id, biome) in REGISTRY.biomes.iter() {
+        entries.push(Entry {
+            registry: Registry::Biomes,
+            key: biome.key.to_string(),
+            id,
+            details: vec![format!("Temperature {}", biome.temperature)],
+        });
+    }
+    for (id, recipe) in REGISTRY.recipes.iter() {
+        entries.push(Entry {
+            registry: Registry::Recipes,
+            key: recipe.key.to_string(),
+            id,
+            details: vec![format!("Result {}", recipe.result())],
+        });
+    }
+    entries
+}
+
+/// The entries of `registry`, or of every registry, whose key contains `search`
+pub fn search(registry: Option<Registry>, search: &str) -> Vec<&'static Entry> {
+    let search = search.trim().to_lowercase();
+    ENTRIES
+        .iter()
+        .filter(|entry| registry.is_none_or(|registry| entry.registry == registry))
+        .filter(|entry| entry.key.contains(&search))
+        .collect()
+}
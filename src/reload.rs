@@ -0,0 +1,186 @@
+use crate::config::TuiConfig;
+use crate::logger::ServerLog;
+use crate::triggers::LogTriggers;
+use anyhow::{Context, anyhow};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, OnceLock};
+use steel::config::{LogConfig, SteelConfig, load_or_create};
+use steel_core::server::Server;
+use steel_utils::locks::SyncMutex;
+use tokio::sync::broadcast;
+use toml::{Table, Value};
+use toml_edit::{DocumentMut, Item};
+use tracing::info;
+
+static RELOADED: LazyLock<broadcast::Sender<Arc<TuiConfig>>> =
+    LazyLock::new(|| broadcast::channel(4).0);
+
+/// Server configs `reload config` re-reads, with the log of the server they belong to
+static SERVER_CONFIGS: LazyLock<SyncMutex<Vec<(ServerLog, PathBuf, SteelConfig)>>> =
+    LazyLock::new(|| SyncMutex::new(Vec::new()));
+
+/// Rebuilds the log output with new log settings
+static LOG_RELOAD: OnceLock<Box<dyn Fn(Option<&LogConfig>) + Send + Sync>> = OnceLock::new();
+
+/// Fields of the server config that are applied without a restart
+const HOT_RELOADABLE: [&str; 3] = ["view_distance", "motd", "log"];
+
+/// Reloads the tui config at `path`, applying the log triggers and ui settings right away.
+///
+/// Services that are already running, like RCON or the admin api, keep their settings until
+/// the next restart.
+pub fn reload_config(path: &Path) -> anyhow::Result<()> {
+    let config = TuiConfig::load_or_create(path)?;
+    LogTriggers::load(&config.triggers)?;
+
+    // nobody is listening if this fails, which is fine
+    let _ = RELOADED.send(Arc::new(config));
+    info!("Reloaded {}", path.display());
+    Ok(())
+}
+
+/// Subscribes to every config reloaded from now on
+pub fn subscribe() -> broadcast::Receiver<Arc<TuiConfig>> {
+    RELOADED.subscribe()
+}
+
+/// Lets `reload config` re-read the server config at `path`, which `config` was loaded from
+///
+/// `log` is the log of the server, which is the one `reload config` is run in. A server started again in the same log
+/// replaces the config it was tracked with before.
+pub fn track_server_config(log: ServerLog, path: PathBuf, config: SteelConfig) {
+    let mut configs = SERVER_CONFIGS.lock();
+    match configs
+        .iter_mut()
+        .find(|(config_log, _, _)| *config_log == log)
+    {
+        Some(tracked) => *tracked = (log, path, config),
+        None => configs.push((log, path, config)),
+    }
+}
+
+/// Calls `reload` with the new log settings whenever they changed, `None` if they were removed
+pub fn on_log_reload(reload: impl Fn(Option<&LogConfig>) + Send + Sync + 'static) {
+    let _ = LOG_RELOAD.set(Box::new(reload));
+}
+
+/// Runs `f` on the path and the config of the server the current thread logs into
+fn with_server_config<T>(
+    f: impl FnOnce(&Path, &mut SteelConfig) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let log = ServerLog::current();
+    let mut configs = SERVER_CONFIGS.lock();
+    let (_, path, config) = configs
+        .iter_mut()
+        .find(|(config_log, _, _)| *config_log == log)
+        .context("the config file of this server is not known")?;
+    f(path, config)
+}
+
+/// The config of the server the current thread logs into, as it was last loaded or changed
+pub fn server_config() -> anyhow::Result<SteelConfig> {
+    with_server_config(|_, config| Ok(config.clone()))
+}
+
+/// Sets `key` in the config file of the server the current thread logs into, keeping the rest of the file
+///
+/// The comments, order and formatting of the file stay as they are, only the value of `key` is replaced.
+pub fn set_server_config(key: &str, value: Value) -> anyhow::Result<()> {
+    with_server_config(|path, config| {
+        let mut updated =
+            Table::try_from(&*config).context("failed to serialize the server config")?;
+        updated.insert(key.to_owned(), value.clone());
+        let updated: SteelConfig = updated
+            .try_into()
+            .with_context(|| format!("invalid value for {key}"))?;
+
+        let content = fs::read_to_string(path).context("failed to read the server config")?;
+        let mut file: DocumentMut = content
+            .parse()
+            .context("failed to parse the server config")?;
+        let mut edited: toml_edit::Value = value
+            .to_string()
+            .parse()
+            .with_context(|| format!("invalid value for {key}"))?;
+        match file.get_mut(key).and_then(Item::as_value_mut) {
+            // keeps a comment after the old value
+            Some(old) => {
+                *edited.decor_mut() = old.decor().clone();
+                *old = edited;
+            }
+            None => file[key] = Item::Value(edited),
+        }
+        fs::write(path, file.to_string()).context("failed to write the server config")?;
+
+        // a reload must not report the change again
+        *config = updated;
+        Ok(())
+    })
+}
+
+/// Re-reads the config of `server` from disk and applies the fields that don't need a restart
+///
+/// Returns a report of what was applied and what only takes effect after a restart.
+pub fn reload_server_config(server: &Server) -> anyhow::Result<Vec<String>> {
+    with_server_config(|path, current| reload_into(server, path, current))
+}
+
+fn reload_into(
+    server: &Server,
+    path: &Path,
+    current: &mut SteelConfig,
+) -> anyhow::Result<Vec<String>> {
+    let config =
+        load_or_create(path).map_err(|err| anyhow!("failed to load configuration: {err}"))?;
+
+    let old = Table::try_from(&*current).context("failed to serialize the current config")?;
+    let new = Table::try_from(&config).context("failed to serialize the new config")?;
+    let changed: Vec<&str> = old
+        .keys()
+        .chain(new.keys())
+        .map(String::as_str)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .collect();
+    if changed.is_empty() {
+        return Ok(vec![format!("{} is unchanged", path.display())]);
+    }
+
+    let (applied, restart): (Vec<&str>, Vec<&str>) = changed
+        .into_iter()
+        .partition(|key| HOT_RELOADABLE.contains(key));
+    for key in &applied {
+        match *key {
+            "view_distance" => {
+                server.set_view_distance(config.view_distance);
+                current.view_distance = config.view_distance;
+            }
+            "motd" => {
+                server.set_motd(config.motd.clone());
+                current.motd.clone_from(&config.motd);
+            }
+            "log" => {
+                if let Some(reload) = LOG_RELOAD.get() {
+                    reload(config.log.as_ref());
+                }
+                current.log.clone_from(&config.log);
+            }
+            _ => (),
+        }
+    }
+
+    let mut report = vec![format!("Reloaded {}", path.display())];
+    if !applied.is_empty() {
+        report.push(format!("Applied: {}", applied.join(", ")));
+    }
+    if !restart.is_empty() {
+        report.push(format!(
+            "Changed but only applied after a restart: {}",
+            restart.join(", ")
+        ));
+    }
+    Ok(report)
+}
@@ -0,0 +1,168 @@
+use crate::AppEvent;
+use crate::config::{Theme, UiConfig};
+use crate::keymap::{Action, Keymap};
+use crate::logger::{LOGGER, ServerLog};
+use crate::panels::console::Console;
+use crate::recording::{self, Entry, Record};
+use crate::terminal;
+use ansi_to_tui::IntoText;
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, MouseEventKind};
+use ratatui::prelude::*;
+use std::path::Path;
+use std::time::Duration;
+use tokio::select;
+use tokio::time::{self, Instant};
+use tokio_util::sync::CancellationToken;
+
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 64.0;
+
+struct Replay {
+    entries: Vec<Entry>,
+    next: usize,
+    clock: Duration,
+    speed: f32,
+    paused: bool,
+    console: Console,
+    keymap: Keymap,
+    theme: Theme,
+    quit: bool,
+}
+
+impl Replay {
+    fn length(&self) -> Duration {
+        self.entries.last().map_or(Duration::ZERO, Entry::at)
+    }
+
+    /// Pushes every entry that is due at the current clock into the log
+    fn play_due(&mut self) {
+        while let Some(entry) = self.entries.get(self.next)
+            && entry.at() <= self.clock
+        {
+            let text = match &entry.record {
+                Record::Log { text } => {
+                    text.into_text().unwrap_or_else(|_| Text::raw(text.clone()))
+                }
+                Record::Command { origin, command } => Text::raw(format!("{origin}> {command}")),
+            };
+            LOGGER.lock().push(text);
+            self.next += 1;
+        }
+    }
+
+    /// Real time until the next entry is due, `None` while paused or once everything played
+    fn until_next(&self) -> Option<Duration> {
+        if self.paused {
+            return None;
+        }
+        let entry = self.entries.get(self.next)?;
+        Some(entry.at().saturating_sub(self.clock).div_f32(self.speed))
+    }
+
+    fn handle_key(&mut self, event: KeyEvent) {
+        if !event.is_press() {
+            return;
+        }
+
+        match (self.keymap.action(event), event.code) {
+            (Some(Action::StopServer), _) | (_, KeyCode::Char('q') | KeyCode::Esc) => {
+                self.quit = true;
+            }
+            (Some(Action::ScrollUp), _) => self.console.scroll_up(),
+            (Some(Action::ScrollDown), _) => self.console.scroll_down(),
+            (Some(Action::PageUp), _) => self.console.page_up(),
+            (Some(Action::PageDown), _) => self.console.page_down(),
+            (Some(Action::ScrollTop), _) => self.console.scroll_top(),
+            (Some(Action::ScrollBottom), _) => self.console.scroll_bottom(),
+            (_, KeyCode::Char(' ')) => self.paused = !self.paused,
+            (_, KeyCode::Char('+' | '=')) => self.speed = (self.speed * 2.0).min(MAX_SPEED),
+            (_, KeyCode::Char('-')) => self.speed = (self.speed / 2.0).max(MIN_SPEED),
+            _ => (),
+        }
+    }
+}
+
+impl Widget for &mut Replay {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [log_area, status_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+
+        self.console.render(log_area, buf);
+
+        let state = if self.paused {
+            "paused"
+        } else if self.next >= self.entries.len() {
+            "finished"
+        } else {
+            "playing"
+        };
+        Line::from(vec![
+            format!(
+                "{state} {} / {} at {}x ",
+                format_time(self.clock.min(self.length())),
+                format_time(self.length()),
+                self.speed
+            )
+            .fg(self.theme.accent),
+            "space pause, +/- speed, q quit".fg(self.theme.muted),
+        ])
+        .render(status_area, buf);
+    }
+}
+
+fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+/// Plays back the session recorded in `path` at `speed` times the original pace
+pub async fn replay(path: &Path, speed: f32, ui: UiConfig) -> anyhow::Result<()> {
+    let entries = recording::load(path)?;
+    LOGGER.lock().set_max_lines(ui.scrollback);
+
+    let mut replay = Replay {
+        entries,
+        next: 0,
+        clock: Duration::ZERO,
+        speed: speed.clamp(MIN_SPEED, MAX_SPEED),
+        paused: false,
+        console: Console::new(ServerLog::PRIMARY),
+        keymap: Keymap::new(&ui.keybinds),
+        theme: ui.theme,
+        quit: false,
+    };
+
+    let token = CancellationToken::new();
+    let mut events = terminal::events(&token);
+    let mut terminal = terminal::init(true, true, &token)?;
+    let mut last = Instant::now();
+
+    while !replay.quit {
+        let now = Instant::now();
+        if !replay.paused {
+            replay.clock += (now - last).mul_f32(replay.speed);
+        }
+        last = now;
+
+        replay.play_due();
+        terminal.draw(|frame| frame.render_widget(&mut replay, frame.area()))?;
+
+        let until_next = replay.until_next();
+        select! {
+            event = events.recv() => match event {
+                Some(AppEvent::UiEvent(Event::Key(event))) => replay.handle_key(event),
+                Some(AppEvent::UiEvent(Event::Mouse(event))) => match event.kind {
+                    MouseEventKind::ScrollUp => replay.console.scroll_up(),
+                    MouseEventKind::ScrollDown => replay.console.scroll_down(),
+                    _ => (),
+                },
+                Some(AppEvent::UiEvent(_)) => (),
+                None => break,
+            },
+            () = time::sleep(until_next.unwrap_or_default()), if until_next.is_some() => (),
+        }
+    }
+
+    token.cancel();
+    terminal::restore(&mut terminal, true)
+}
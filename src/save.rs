@@ -0,0 +1,334 @@
+use crate::REDRAW;
+use crate::config::AutosaveConfig;
+use crate::jobs::Job;
+use crate::logger::ServerLog;
+use crate::notifications::{self, NotificationKind, Severity};
+use crate::session;
+use anyhow::{anyhow, bail};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use steel_core::player::Player;
+use steel_core::server::Server;
+use steel_utils::locks::SyncMutex;
+use tokio::select;
+use tokio::time::{self, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// How long the outcome of a save stays in the status bar
+const STATUS_DURATION: Duration = Duration::from_secs(5);
+
+/// Chunks written between two progress reports
+const CHUNK_BATCH: usize = 256;
+
+/// Players written between two progress reports
+const PLAYER_BATCH: usize = 16;
+
+/// The saves of every server, by its log
+static SAVES: LazyLock<SyncMutex<HashMap<ServerLog, Arc<Saves>>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+/// The saves of one server, shared by the apps showing it and the tasks saving it
+#[derive(Default)]
+pub struct Saves {
+    /// Whether a save is running, another one of the same server is refused meanwhile
+    saving: AtomicBool,
+    /// What the status bar shows about saving, with when it expires unless a save is still running
+    status: SyncMutex<Option<(String, Option<Instant>)>>,
+}
+
+impl Saves {
+    fn is_saving(&self) -> bool {
+        self.saving.load(Ordering::Acquire)
+    }
+
+    fn set_status(&self, message: impl Into<String>, duration: Option<Duration>) {
+        *self.status.lock() = Some((
+            message.into(),
+            duration.map(|duration| Instant::now() + duration),
+        ));
+        REDRAW.notify_one();
+    }
+
+    /// What the status bar shows about saving, if anything
+    pub fn status(&self) -> Option<String> {
+        let mut status = self.status.lock();
+        if let Some((_, Some(expiry))) = &*status
+            && *expiry <= Instant::now()
+        {
+            *status = None;
+        }
+        status.as_ref().map(|(message, _)| message.clone())
+    }
+}
+
+/// The saves of the server logging into `log`
+pub fn saves(log: ServerLog) -> Arc<Saves> {
+    SAVES.lock().entry(log).or_default().clone()
+}
+
+/// How far a save got
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Progress {
+    pub chunks: usize,
+    pub total_chunks: usize,
+    pub players: usize,
+    pub total_players: usize,
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} chunks, {}/{} players",
+            self.chunks, self.total_chunks, self.players, self.total_players
+        )
+    }
+}
+
+/// Why a save was started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reason {
+    Autosave,
+    Requested,
+}
+
+impl Reason {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Autosave => "Autosave",
+            Self::Requested => "Save",
+        }
+    }
+}
+
+/// Marks a server as no longer saving once the save is done, even if it failed
+struct SaveGuard<'a>(&'a Saves);
+
+impl<'a> SaveGuard<'a> {
+    fn acquire(saves: &'a Saves) -> anyhow::Result<Self> {
+        if saves.saving.swap(true, Ordering::AcqRel) {
+            bail!("a save is already running");
+        }
+        Ok(Self(saves))
+    }
+}
+
+impl Drop for SaveGuard<'_> {
+    fn drop(&mut self) {
+        self.0.saving.store(false, Ordering::Release);
+    }
+}
+
+/// Every player online in any world
+fn online_players(server: &Server) -> Vec<Arc<Player>> {
+    let mut players = Vec::new();
+    for world in server.worlds.values() {
+        world.players.iter_players(|_, player| {
+            players.push(player.clone());
+            true
+        });
+    }
+    players
+}
+
+/// Saves `players` in batches, reporting the progress after each one
+async fn save_players(
+    server: &Server,
+    players: &[Arc<Player>],
+    progress: &mut Progress,
+    report: &mut impl FnMut(Progress),
+) -> anyhow::Result<()> {
+    for batch in players.chunks(PLAYER_BATCH) {
+        progress.players += server
+            .player_data_storage
+            .save_all(batch)
+            .await
+            .map_err(|err| anyhow!("{err}"))?;
+        report(*progress);
+    }
+    Ok(())
+}
+
+/// Saves every dirty chunk and every online player without stopping the server
+///
+/// Stops between batches once `job` is cancelled from the running tab.
+async fn save(
+    server: &Server,
+    saves: &Saves,
+    job: &Job,
+    mut report: impl FnMut(Progress),
+) -> anyhow::Result<Progress> {
+    let _guard = SaveGuard::acquire(saves)?;
+
+    let dirty: Vec<_> = server
+        .worlds
+        .values()
+        .map(|world| (world, world.chunk_map.dirty_chunks()))
+        .collect();
+    let players = online_players(server);
+    let mut progress = Progress {
+        total_chunks: dirty.iter().map(|(_, chunks)| chunks.len()).sum(),
+        total_players: players.len(),
+        ..Progress::default()
+    };
+    report(progress);
+
+    for (world, chunks) in &dirty {
+        for batch in chunks.chunks(CHUNK_BATCH) {
+            if job.is_cancelled() {
+                bail!("cancelled after {progress}");
+            }
+            let saved = world.chunk_map.save_chunks(batch).await;
+            session::chunks_saved(saved);
+            progress.chunks += saved;
+            report(progress);
+        }
+    }
+    save_players(server, &players, &mut progress, &mut report).await?;
+    Ok(progress)
+}
+
+/// Saves the server and reports the outcome into `log` and the status bar
+async fn save_reporting(server: &Server, log: ServerLog, reason: Reason) {
+    let name = reason.name();
+    let saves = saves(log);
+    saves.set_status(format!("{name} running..."), None);
+    if reason == Reason::Requested {
+        log.scope(|| info!("Saving the server..."));
+    }
+
+    let job = log.scope(|| Job::start(name));
+    let started = Instant::now();
+    let result = save(server, &saves, &job, |progress| {
+        saves.set_status(format!("{name} running: {progress}"), None);
+        job.progress(
+            (progress.chunks + progress.players) as u64,
+            (progress.total_chunks + progress.total_players) as u64,
+        );
+        // autosaves only report into the status bar, so they don't flood the log
+        if reason == Reason::Requested {
+            log.scope(|| info!("Saving {progress}"));
+        }
+    })
+    .await;
+    drop(job);
+
+    match result {
+        Ok(saved) => {
+            log.scope(|| {
+                info!(
+                    "{name} done: {} chunks and {} players in {:.1?}",
+                    saved.chunks,
+                    saved.players,
+                    started.elapsed()
+                );
+            });
+            saves.set_status(
+                format!(
+                    "{name} done: {} chunks, {} players",
+                    saved.chunks, saved.players
+                ),
+                Some(STATUS_DURATION),
+            );
+            // autosaves only notify when they fail, a toast every few minutes would be noise
+            if reason == Reason::Requested {
+                notifications::send(
+                    NotificationKind::SaveFinished,
+                    Severity::Info,
+                    format!(
+                        "{name} done: {} chunks and {} players",
+                        saved.chunks, saved.players
+                    ),
+                );
+            }
+        }
+        Err(err) => {
+            log.scope(|| error!("{name} failed: {err:#}"));
+            saves.set_status(format!("{name} failed"), Some(STATUS_DURATION));
+            notifications::send(
+                NotificationKind::SaveFinished,
+                Severity::Error,
+                format!("{name} failed: {err:#}"),
+            );
+        }
+    }
+
+    // clears the outcome from the status bar once it expired
+    time::sleep(STATUS_DURATION).await;
+    REDRAW.notify_one();
+}
+
+/// Saves the server in the background, streaming the progress into `log`
+///
+/// This is what the save keybind and the `save-all` console command run.
+pub fn start(server: Arc<Server>, log: ServerLog) {
+    if saves(log).is_saving() {
+        log.scope(|| warn!("A save is already running"));
+        return;
+    }
+    tokio::spawn(async move { save_reporting(&server, log, Reason::Requested).await });
+}
+
+/// Saves the server every `config.interval_secs` until `server_token` is cancelled
+pub fn spawn(server: Arc<Server>, config: AutosaveConfig, server_token: CancellationToken) {
+    if !config.enabled {
+        return;
+    }
+    let log = ServerLog::current();
+    let saves = saves(log);
+    let period = Duration::from_secs(config.interval_secs.max(1));
+    let mut interval = time::interval_at(time::Instant::now() + period, period);
+    // a slow save must not be followed by another one right away
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    tokio::spawn(async move {
+        loop {
+            select! {
+                () = server_token.cancelled() => return,
+                _ = interval.tick() => (),
+            }
+            // a save requested by hand just ran or is still running
+            if saves.is_saving() {
+                continue;
+            }
+            save_reporting(&server, log, Reason::Autosave).await;
+        }
+    });
+}
+
+/// Saves everything once the server stopped, unloading the chunks
+pub async fn shutdown(server: &Server) {
+    for world in server.worlds.values() {
+        world.chunk_map.task_tracker.close();
+        world.chunk_map.task_tracker.wait().await;
+    }
+
+    // a save started by hand or by the autosave has to finish first
+    let saves = saves(ServerLog::current());
+    while saves.is_saving() {
+        time::sleep(Duration::from_millis(50)).await;
+    }
+
+    info!("Saving world data...");
+    let mut total_saved = 0;
+    for world in server.worlds.values() {
+        world.cleanup(&mut total_saved).await;
+    }
+    session::chunks_saved(total_saved);
+    info!("Saved {total_saved} chunks");
+
+    info!("Saving player data...");
+    let players = online_players(server);
+    let mut progress = Progress {
+        total_players: players.len(),
+        ..Progress::default()
+    };
+    match save_players(server, &players, &mut progress, &mut |_| ()).await {
+        Ok(()) => info!("Saved {} players", progress.players),
+        Err(err) => error!("Failed to save player data: {err:#}"),
+    }
+}
@@ -0,0 +1,295 @@
+use anyhow::{Context, bail};
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use steel::SteelServer;
+use steel_utils::locks::SyncMutex;
+use tokio::time;
+
+const USAGE: &str = "usage: block <ip> [minutes] | unblock <ip> | block list";
+
+/// How far back connection attempts are counted
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Connection attempts from one address within `THROTTLE_WINDOW` before it is throttled
+const THROTTLE_ATTEMPTS: usize = 8;
+
+const THROTTLE_WINDOW: Duration = Duration::from_secs(10);
+
+/// How long a throttled address is refused
+const THROTTLE_DURATION: Duration = Duration::from_secs(60);
+
+/// How long `block` blocks an address unless told otherwise
+const DEFAULT_BLOCK_MINUTES: u64 = 60;
+
+/// How often attempts older than the window are forgotten
+const EXPIRE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What is known about the connections from one address
+#[derive(Default)]
+struct Peer {
+    /// When it tried to connect within the window, oldest first
+    attempts: VecDeque<Instant>,
+    rejected: u64,
+    throttled_until: Option<Instant>,
+    blocked_until: Option<Instant>,
+}
+
+impl Peer {
+    fn is_idle(&self, now: Instant) -> bool {
+        self.attempts.is_empty()
+            && self.throttled_until.is_none_or(|until| until <= now)
+            && self.blocked_until.is_none_or(|until| until <= now)
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    peers: HashMap<IpAddr, Peer>,
+    /// Every connection attempt within the window, oldest first
+    attempts: VecDeque<Instant>,
+    total_attempts: u64,
+    total_rejected: u64,
+    total_refused: u64,
+}
+
+impl Counters {
+    /// Forgets the attempts older than the window and the addresses nothing is known about anymore
+    fn expire(&mut self, now: Instant) {
+        let expired = |attempts: &mut VecDeque<Instant>| {
+            while attempts
+                .front()
+                .is_some_and(|attempt| now.duration_since(*attempt) > WINDOW)
+            {
+                attempts.pop_front();
+            }
+        };
+        expired(&mut self.attempts);
+        for peer in self.peers.values_mut() {
+            expired(&mut peer.attempts);
+        }
+        self.peers.retain(|_, peer| !peer.is_idle(now));
+    }
+}
+
+static COUNTERS: LazyLock<SyncMutex<Counters>> =
+    LazyLock::new(|| SyncMutex::new(Counters::default()));
+
+/// Whether the counters are expired on a timer already, they are shared by every server
+static EXPIRING: AtomicBool = AtomicBool::new(false);
+
+/// How an address is treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerState {
+    Allowed,
+    /// Refused for connecting too often, for the remaining time
+    Throttled(Duration),
+    /// Blocked by hand, for the remaining time
+    Blocked(Duration),
+}
+
+/// An address that connected within the window or is refused
+#[derive(Debug, Clone)]
+pub struct PeerInfo {
+    pub ip: IpAddr,
+    /// Connection attempts within the last minute
+    pub attempts: usize,
+    /// Handshakes the network layer rejected
+    pub rejected: u64,
+    pub state: PeerState,
+}
+
+/// The connection counters at one point in time
+#[derive(Debug, Clone)]
+pub struct Stats {
+    /// Connection attempts per second, averaged over the last minute
+    pub rate: f64,
+    pub total_attempts: u64,
+    /// Handshakes the network layer rejected, like ones with an unknown protocol
+    pub total_rejected: u64,
+    /// Connections refused for being throttled or blocked
+    pub total_refused: u64,
+    /// Refused addresses first, then the ones connecting the most
+    pub peers: Vec<PeerInfo>,
+}
+
+/// Counts a connection attempt from `ip`, returning whether the network layer should accept it
+///
+/// Addresses connecting more than 8 times in 10 seconds are refused for a minute, blocked ones until their block
+/// runs out.
+pub fn connection_attempt(ip: IpAddr) -> bool {
+    let now = Instant::now();
+    let mut counters = COUNTERS.lock();
+    counters.attempts.push_back(now);
+    counters.total_attempts += 1;
+
+    let peer = counters.peers.entry(ip).or_default();
+    peer.attempts.push_back(now);
+    let recent = peer
+        .attempts
+        .iter()
+        .rev()
+        .take_while(|attempt| now.duration_since(**attempt) <= THROTTLE_WINDOW)
+        .count();
+    if recent > THROTTLE_ATTEMPTS {
+        peer.throttled_until = Some(now + THROTTLE_DURATION);
+    }
+    let refused = [peer.blocked_until, peer.throttled_until]
+        .into_iter()
+        .flatten()
+        .any(|until| until > now);
+    if refused {
+        counters.total_refused += 1;
+    }
+    !refused
+}
+
+/// Counts a handshake from `ip` the network layer rejected
+pub fn handshake_rejected(ip: IpAddr) {
+    let mut counters = COUNTERS.lock();
+    counters.total_rejected += 1;
+    counters.peers.entry(ip).or_default().rejected += 1;
+}
+
+/// Refuses every connection from `ip` for `duration`
+pub fn block(ip: IpAddr, duration: Duration) {
+    COUNTERS.lock().peers.entry(ip).or_default().blocked_until = Some(Instant::now() + duration);
+}
+
+/// Lifts the block and throttling of `ip`, returning whether it was refused
+pub fn unblock(ip: IpAddr) -> bool {
+    let now = Instant::now();
+    let mut counters = COUNTERS.lock();
+    let Some(peer) = counters.peers.get_mut(&ip) else {
+        return false;
+    };
+    let refused = [peer.blocked_until.take(), peer.throttled_until.take()]
+        .into_iter()
+        .flatten()
+        .any(|until| until > now);
+    peer.attempts.clear();
+    refused
+}
+
+/// The connection counters right now
+pub fn stats() -> Stats {
+    let now = Instant::now();
+    let counters = COUNTERS.lock();
+
+    let mut peers: Vec<PeerInfo> = counters
+        .peers
+        .iter()
+        .map(|(ip, peer)| {
+            let remaining = |until: Option<Instant>| {
+                until.filter(|until| *until > now).map(|until| until - now)
+            };
+            let state = match (
+                remaining(peer.blocked_until),
+                remaining(peer.throttled_until),
+            ) {
+                (Some(left), _) => PeerState::Blocked(left),
+                (None, Some(left)) => PeerState::Throttled(left),
+                (None, None) => PeerState::Allowed,
+            };
+            PeerInfo {
+                ip: *ip,
+                attempts: peer.attempts.len(),
+                rejected: peer.rejected,
+                state,
+            }
+        })
+        .collect();
+    peers.sort_by_key(|peer| {
+        (
+            peer.state == PeerState::Allowed,
+            Reverse(peer.attempts),
+            peer.ip,
+        )
+    });
+
+    Stats {
+        rate: counters.attempts.len() as f64 / WINDOW.as_secs_f64(),
+        total_attempts: counters.total_attempts,
+        total_rejected: counters.total_rejected,
+        total_refused: counters.total_refused,
+        peers,
+    }
+}
+
+/// Counts the connections `steel_server` accepts and the handshakes it rejects, refusing throttled and blocked
+/// addresses before their handshake
+pub fn install(steel_server: &mut SteelServer) {
+    steel_server.set_connection_filter(|addr: SocketAddr| connection_attempt(addr.ip()));
+    steel_server.on_handshake_rejected(|addr: SocketAddr| handshake_rejected(addr.ip()));
+
+    if !EXPIRING.swap(true, Ordering::Relaxed) {
+        let mut interval = time::interval(EXPIRE_INTERVAL);
+        tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+                COUNTERS.lock().expire(Instant::now());
+            }
+        });
+    }
+}
+
+/// Runs the `block` and `unblock` console commands, returning their output
+///
+/// `block <ip> [minutes]` refuses connections from an address for an hour unless told otherwise, `unblock <ip>`
+/// lets it connect again and `block list` lists the refused addresses.
+pub fn command(name: &str, args: &str) -> anyhow::Result<Vec<String>> {
+    let mut args = args.split_whitespace();
+    match (name, args.next(), args.next(), args.next()) {
+        ("block", Some("list"), None, None) => {
+            let refused: Vec<String> = stats()
+                .peers
+                .into_iter()
+                .filter_map(|peer| match peer.state {
+                    PeerState::Allowed => None,
+                    PeerState::Throttled(left) => {
+                        Some(format!("{} throttled for {}s", peer.ip, left.as_secs()))
+                    }
+                    PeerState::Blocked(left) => Some(format!(
+                        "{} blocked for {}m",
+                        peer.ip,
+                        left.as_secs().div_ceil(60)
+                    )),
+                })
+                .collect();
+            if refused.is_empty() {
+                Ok(vec!["No addresses are refused".to_owned()])
+            } else {
+                Ok(refused)
+            }
+        }
+        ("block", Some(ip), minutes, None) => {
+            let ip: IpAddr = ip
+                .parse()
+                .with_context(|| format!("invalid address `{ip}`"))?;
+            let minutes = match minutes {
+                Some(minutes) => minutes
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|minutes| *minutes > 0)
+                    .with_context(|| format!("invalid number of minutes `{minutes}`"))?,
+                None => DEFAULT_BLOCK_MINUTES,
+            };
+            block(ip, Duration::from_secs(minutes * 60));
+            Ok(vec![format!("Blocked {ip} for {minutes} minutes")])
+        }
+        ("unblock", Some(ip), None, None) => {
+            let ip: IpAddr = ip
+                .parse()
+                .with_context(|| format!("invalid address `{ip}`"))?;
+            if unblock(ip) {
+                Ok(vec![format!("Unblocked {ip}")])
+            } else {
+                Ok(vec![format!("{ip} was not blocked or throttled")])
+            }
+        }
+        _ => bail!(USAGE),
+    }
+}
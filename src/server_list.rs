@@ -0,0 +1,71 @@
+use crate::reload;
+use anyhow::{Context, bail};
+use std::fs;
+use steel_core::server::Server;
+use toml::Value;
+
+const USAGE: &str = "usage: status [motd <text> | max-players <count> | icon <png>]";
+
+/// Start of every PNG file
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Width and height the server list shows icons at
+const ICON_SIZE: u32 = 64;
+
+/// Runs the `status` console command, returning its output
+///
+/// `status` shows what the server list shows about the server, `status motd <text>`,
+/// `status max-players <count>` and `status icon <png>` change it right away and in the server config.
+pub fn command(server: &Server, args: &str) -> anyhow::Result<Vec<String>> {
+    let (field, value) = args.split_once(' ').unwrap_or((args, ""));
+    let value = value.trim();
+    match field {
+        "" => {
+            let config = reload::server_config()?;
+            return Ok(vec![
+                format!("MOTD: {}", config.motd),
+                format!("Max players: {}", config.max_players),
+                format!("Icon: {}", config.favicon),
+            ]);
+        }
+        "motd" if !value.is_empty() => {
+            reload::set_server_config("motd", Value::from(value))?;
+            server.set_motd(value.to_owned());
+        }
+        "max-players" if !value.is_empty() => {
+            let max_players: u32 = value
+                .parse()
+                .with_context(|| format!("`{value}` is not a player count"))?;
+            reload::set_server_config("max_players", Value::from(max_players))?;
+            server.set_max_players(max_players);
+        }
+        "icon" if !value.is_empty() => {
+            let icon = fs::read(value).with_context(|| format!("failed to read {value}"))?;
+            check_icon(&icon)?;
+            reload::set_server_config("favicon", Value::from(value))?;
+            server.set_favicon(icon);
+        }
+        _ => bail!(USAGE),
+    }
+    Ok(vec![format!("Updated the {field} of the server list")])
+}
+
+/// Checks that `icon` is a PNG the server list can show
+fn check_icon(icon: &[u8]) -> anyhow::Result<()> {
+    if !icon.starts_with(PNG_SIGNATURE) {
+        bail!("the icon has to be a PNG");
+    }
+    // the header chunk follows the signature, its data starts with the width and height
+    let size = |offset: usize| {
+        icon.get(offset..offset + 4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u32::from_be_bytes)
+    };
+    match (size(16), size(20)) {
+        (Some(ICON_SIZE), Some(ICON_SIZE)) => Ok(()),
+        (Some(width), Some(height)) => {
+            bail!("the icon is {width}x{height}, it has to be {ICON_SIZE}x{ICON_SIZE}")
+        }
+        _ => bail!("the icon is not a valid PNG"),
+    }
+}
@@ -0,0 +1,126 @@
+use crate::cli::Cli;
+use crate::{Exit, Frontend, run};
+use anyhow::Context;
+use clap::Parser;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::{env, io};
+use tokio_util::sync::CancellationToken;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use windows_service::service::{
+    ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+    ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+};
+use windows_service::service_control_handler::{
+    self, ServiceControlHandlerResult, ServiceStatusHandle,
+};
+use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+use windows_service::{define_windows_service, service_dispatcher};
+
+const SERVICE_NAME: &str = "steel";
+
+define_windows_service!(ffi_service_main, service_main);
+
+/// Hands the process over to the service control manager, which then calls `service_main`
+pub fn start() {
+    if let Err(err) = service_dispatcher::start(SERVICE_NAME, ffi_service_main) {
+        eprintln!("Failed to start the service dispatcher: {err}");
+    }
+}
+
+/// Registers this executable as an automatically started service
+pub fn install() -> anyhow::Result<()> {
+    let manager =
+        ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CREATE_SERVICE)
+            .context("failed to connect to the service manager")?;
+    let info = ServiceInfo {
+        name: OsString::from(SERVICE_NAME),
+        display_name: OsString::from("Steel Minecraft Server"),
+        service_type: ServiceType::OWN_PROCESS,
+        start_type: ServiceStartType::AutoStart,
+        error_control: ServiceErrorControl::Normal,
+        executable_path: env::current_exe().context("failed to locate the executable")?,
+        launch_arguments: vec![OsString::from("--service")],
+        dependencies: Vec::new(),
+        account_name: None,
+        account_password: None,
+    };
+    manager
+        .create_service(&info, ServiceAccess::QUERY_STATUS)
+        .context("failed to create the service")?;
+    println!("Installed the {SERVICE_NAME} service");
+    Ok(())
+}
+
+/// Opens `logs/service.log`, where the console output goes while running as a service
+pub fn log_writer() -> io::Result<BoxMakeWriter> {
+    fs::create_dir_all("logs")?;
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open("logs/service.log")?;
+    Ok(BoxMakeWriter::new(Mutex::new(file)))
+}
+
+fn service_main(_arguments: Vec<OsString>) {
+    // services start in the system directory, the server expects its files next to the executable
+    if let Some(dir) = env::current_exe().ok().as_deref().and_then(Path::parent) {
+        let _ = env::set_current_dir(dir);
+    }
+
+    let token = CancellationToken::new();
+    let handler_token = token.clone();
+    let status_handle =
+        match service_control_handler::register(SERVICE_NAME, move |control| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                handler_token.cancel();
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        }) {
+            Ok(status_handle) => status_handle,
+            Err(err) => {
+                eprintln!("Failed to register the service control handler: {err}");
+                return;
+            }
+        };
+
+    set_status(
+        &status_handle,
+        ServiceState::Running,
+        ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN,
+        Exit::Stopped,
+    );
+    let exit = run(Cli::parse(), token, Frontend::Service);
+    set_status(
+        &status_handle,
+        ServiceState::Stopped,
+        ServiceControlAccept::empty(),
+        exit,
+    );
+}
+
+fn set_status(
+    handle: &ServiceStatusHandle,
+    state: ServiceState,
+    accept: ServiceControlAccept,
+    exit: Exit,
+) {
+    let exit_code = match exit {
+        Exit::Stopped => ServiceExitCode::Win32(0),
+        exit => ServiceExitCode::ServiceSpecific(exit as u32),
+    };
+    let _ = handle.set_service_status(ServiceStatus {
+        service_type: ServiceType::OWN_PROCESS,
+        current_state: state,
+        controls_accepted: accept,
+        exit_code,
+        checkpoint: 0,
+        wait_hint: Duration::default(),
+        process_id: None,
+    });
+}
@@ -0,0 +1,60 @@
+use crate::logger::LogCounter;
+use crate::{recording, status};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use steel_core::server::Server;
+use tokio::select;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+/// How often the player count is checked for a new peak
+const PEAK_INTERVAL: Duration = Duration::from_secs(5);
+
+static STARTED: OnceLock<Instant> = OnceLock::new();
+static PEAK_PLAYERS: AtomicUsize = AtomicUsize::new(0);
+static CHUNKS_SAVED: AtomicUsize = AtomicUsize::new(0);
+
+/// Starts the session clock and tracks the peak player count of `server` until `server_token` is cancelled
+pub fn spawn(server: Arc<Server>, server_token: CancellationToken) {
+    STARTED.get_or_init(Instant::now);
+    let mut interval = time::interval(PEAK_INTERVAL);
+    tokio::spawn(async move {
+        loop {
+            select! {
+                () = server_token.cancelled() => return,
+                _ = interval.tick() => (),
+            }
+            PEAK_PLAYERS.fetch_max(status::players(&server).len(), Ordering::Relaxed);
+        }
+    });
+}
+
+/// Counts chunks written to disk by a save
+pub fn chunks_saved(chunks: usize) {
+    CHUNKS_SAVED.fetch_add(chunks, Ordering::Relaxed);
+}
+
+/// What happened during this session, printed once the terminal ui has exited
+pub fn summary() -> Vec<String> {
+    let uptime = STARTED
+        .get()
+        .map_or(0, |started| started.elapsed().as_secs());
+    let (errors, warnings) = LogCounter::counts();
+    let log = recording::path().map_or_else(
+        || "not recorded, start with --record <file> to keep one".to_owned(),
+        |path| path.display().to_string(),
+    );
+    vec![
+        format!(
+            "Uptime: {}h {:02}m {:02}s",
+            uptime / 3600,
+            uptime / 60 % 60,
+            uptime % 60
+        ),
+        format!("Peak players: {}", PEAK_PLAYERS.load(Ordering::Relaxed)),
+        format!("Chunks saved: {}", CHUNKS_SAVED.load(Ordering::Relaxed)),
+        format!("Errors logged: {errors}, warnings: {warnings}"),
+        format!("Session log: {log}"),
+    ]
+}
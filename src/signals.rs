@@ -0,0 +1,126 @@
+use crate::logger::ServerLog;
+use crate::reload;
+use std::collections::HashMap;
+use std::future;
+use std::path::Path;
+use std::sync::{Arc, LazyLock};
+use steel_core::server::Server;
+use steel_utils::locks::SyncMutex;
+#[cfg(unix)]
+use tokio::select;
+use tokio::signal;
+#[cfg(unix)]
+use tokio::signal::unix::{self, SignalKind};
+use tracing::{error, info};
+
+/// The server running in each log, which a reload applies the config of that log to
+static SERVERS: LazyLock<SyncMutex<HashMap<ServerLog, Arc<Server>>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+/// What a signal sent to the process asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Ctrl+C or SIGTERM, stopping the servers gracefully so their worlds and players are saved
+    Stop,
+    /// SIGHUP, reloading the configs
+    Reload,
+}
+
+/// The signals the process listens for, received in the main `select!` next to the running servers
+pub struct Signals {
+    /// SIGTERM and SIGHUP, missing if they couldn't be listened for
+    #[cfg(unix)]
+    unix: Option<(unix::Signal, unix::Signal)>,
+}
+
+impl Signals {
+    /// Starts listening for signals, logging if that failed
+    pub fn listen() -> Self {
+        #[cfg(unix)]
+        let unix = match (
+            unix::signal(SignalKind::terminate()),
+            unix::signal(SignalKind::hangup()),
+        ) {
+            (Ok(terminate), Ok(hangup)) => Some((terminate, hangup)),
+            (Err(err), _) | (_, Err(err)) => {
+                error!("Failed to listen for signals: {err}");
+                None
+            }
+        };
+        Self {
+            #[cfg(unix)]
+            unix,
+        }
+    }
+
+    /// Waits for the next signal
+    pub async fn recv(&mut self) -> Signal {
+        #[cfg(unix)]
+        if let Some((terminate, hangup)) = &mut self.unix {
+            return select! {
+                Ok(()) = signal::ctrl_c() => {
+                    info!("Received Ctrl+C, stopping the server");
+                    Signal::Stop
+                }
+                _ = terminate.recv() => {
+                    info!("Received SIGTERM, stopping the server");
+                    Signal::Stop
+                }
+                _ = hangup.recv() => {
+                    info!("Received SIGHUP, reloading the configs");
+                    Signal::Reload
+                }
+            };
+        }
+
+        match signal::ctrl_c().await {
+            Ok(()) => {
+                info!("Received Ctrl+C, stopping the server");
+                Signal::Stop
+            }
+            Err(err) => {
+                error!("Failed to listen for Ctrl+C: {err}");
+                future::pending().await
+            }
+        }
+    }
+}
+
+/// Makes `server` the one a reload applies the config of `log` to, in place of one that stopped in it
+pub(crate) fn running(log: ServerLog, server: Arc<Server>) {
+    SERVERS.lock().insert(log, server);
+}
+
+/// Stops reloading the config of `log` into `server`, unless another server started in it meanwhile
+pub(crate) fn stopped(log: ServerLog, server: &Arc<Server>) {
+    let mut servers = SERVERS.lock();
+    if servers
+        .get(&log)
+        .is_some_and(|running| Arc::ptr_eq(running, server))
+    {
+        servers.remove(&log);
+    }
+}
+
+/// Reloads the tui config at `tui_config` and the config of each running server, logged into its own log
+pub fn reload_configs(tui_config: &Path) {
+    if let Err(err) = reload::reload_config(tui_config) {
+        error!("Failed to reload {}: {err:#}", tui_config.display());
+    }
+    // the servers are looked up now, a server started again from its menu replaced the one that stopped
+    let servers: Vec<_> = SERVERS
+        .lock()
+        .iter()
+        .map(|(log, server)| (*log, server.clone()))
+        .collect();
+    for (log, server) in &servers {
+        log.scope(|| match reload::reload_server_config(server) {
+            Ok(report) => {
+                for line in report {
+                    info!("{line}");
+                }
+            }
+            Err(err) => error!("Failed to reload the server config: {err:#}"),
+        });
+    }
+}
@@ -0,0 +1,283 @@
+use ratatui::crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use std::str;
+
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+enum Parsed {
+    Event(Event, usize),
+    Skip(usize),
+    Incomplete,
+}
+
+/// Decodes the raw bytes sent by a remote terminal into crossterm events
+#[derive(Default)]
+pub struct InputParser {
+    pending: Vec<u8>,
+}
+
+impl InputParser {
+    /// Feeds `bytes` into the parser, returning every event that is complete
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Event> {
+        self.pending.extend_from_slice(bytes);
+
+        let mut events = Vec::new();
+        let mut offset = 0;
+        while offset < self.pending.len() {
+            match parse(&self.pending[offset..]) {
+                Parsed::Event(event, length) => {
+                    events.push(event);
+                    offset += length;
+                }
+                Parsed::Skip(length) => offset += length,
+                Parsed::Incomplete => break,
+            }
+        }
+        self.pending.drain(..offset);
+        events
+    }
+
+    /// Whether the last byte fed is an escape that may still start a sequence
+    pub fn escape_pending(&self) -> bool {
+        self.pending == [0x1b]
+    }
+
+    /// Turns an escape no sequence followed into the Esc key it was pressed as
+    pub fn flush_escape(&mut self) -> Option<Event> {
+        if !self.escape_pending() {
+            return None;
+        }
+        self.pending.clear();
+        Some(Event::Key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)))
+    }
+}
+
+const fn key(code: KeyCode, modifiers: KeyModifiers, length: usize) -> Parsed {
+    Parsed::Event(Event::Key(KeyEvent::new(code, modifiers)), length)
+}
+
+fn parse(bytes: &[u8]) -> Parsed {
+    match bytes[0] {
+        0x1b => parse_escape(bytes),
+        b'\r' | b'\n' => key(KeyCode::Enter, KeyModifiers::NONE, 1),
+        b'\t' => key(KeyCode::Tab, KeyModifiers::NONE, 1),
+        0x7f | 0x08 => key(KeyCode::Backspace, KeyModifiers::NONE, 1),
+        0x00 => Parsed::Skip(1),
+        byte @ 0x01..=0x1a => key(
+            KeyCode::Char(char::from(byte - 1 + b'a')),
+            KeyModifiers::CONTROL,
+            1,
+        ),
+        byte => {
+            let length = match byte {
+                0x00..=0x7f => 1,
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => return Parsed::Skip(1),
+            };
+            let Some(encoded) = bytes.get(..length) else {
+                return Parsed::Incomplete;
+            };
+            match str::from_utf8(encoded).ok().and_then(|s| s.chars().next()) {
+                Some(char) => key(KeyCode::Char(char), KeyModifiers::NONE, length),
+                None => Parsed::Skip(1),
+            }
+        }
+    }
+}
+
+fn parse_escape(bytes: &[u8]) -> Parsed {
+    match bytes.get(1) {
+        // the rest of the sequence may come in the next packet, `flush_escape` sends the Esc if it doesn't
+        None => Parsed::Incomplete,
+        Some(b'[') => parse_csi(bytes),
+        Some(b'O') => match bytes.get(2) {
+            None => Parsed::Incomplete,
+            Some(&byte) => match cursor_key(byte) {
+                Some(code) => key(code, KeyModifiers::NONE, 3),
+                None => Parsed::Skip(3),
+            },
+        },
+        // terminals send Alt+key as an escape before the key
+        Some(&byte @ 0x20..=0x7e) => key(KeyCode::Char(char::from(byte)), KeyModifiers::ALT, 2),
+        Some(_) => key(KeyCode::Esc, KeyModifiers::NONE, 1),
+    }
+}
+
+const fn cursor_key(byte: u8) -> Option<KeyCode> {
+    match byte {
+        b'A' => Some(KeyCode::Up),
+        b'B' => Some(KeyCode::Down),
+        b'C' => Some(KeyCode::Right),
+        b'D' => Some(KeyCode::Left),
+        b'H' => Some(KeyCode::Home),
+        b'F' => Some(KeyCode::End),
+        _ => None,
+    }
+}
+
+fn parse_csi(bytes: &[u8]) -> Parsed {
+    let Some(end) = bytes[2..]
+        .iter()
+        .position(|byte| (0x40..=0x7e).contains(byte))
+    else {
+        return Parsed::Incomplete;
+    };
+    let end = end + 2;
+    let length = end + 1;
+
+    let params = str::from_utf8(&bytes[2..end]).unwrap_or_default();
+    if let Some(params) = params.strip_prefix('<') {
+        return parse_sgr_mouse(params, bytes[end], length);
+    }
+
+    let mut numbers = params.split(';').map(|n| n.parse::<u16>().unwrap_or(1));
+    let first = numbers.next().unwrap_or(1);
+    let modifiers = numbers.next().map_or(KeyModifiers::NONE, modifiers);
+
+    let code = match bytes[end] {
+        b'~' => match first {
+            1 | 7 => KeyCode::Home,
+            2 => KeyCode::Insert,
+            3 => KeyCode::Delete,
+            4 | 8 => KeyCode::End,
+            5 => KeyCode::PageUp,
+            6 => KeyCode::PageDown,
+            200 => return parse_paste(&bytes[length..], length),
+            _ => return Parsed::Skip(length),
+        },
+        byte => match cursor_key(byte) {
+            Some(code) => code,
+            None => return Parsed::Skip(length),
+        },
+    };
+    key(code, modifiers, length)
+}
+
+fn parse_paste(bytes: &[u8], start: usize) -> Parsed {
+    let Some(end) = bytes
+        .windows(PASTE_END.len())
+        .position(|window| window == PASTE_END)
+    else {
+        return Parsed::Incomplete;
+    };
+    let paste = String::from_utf8_lossy(&bytes[..end]).into_owned();
+    Parsed::Event(Event::Paste(paste), start + end + PASTE_END.len())
+}
+
+fn parse_sgr_mouse(params: &str, last: u8, length: usize) -> Parsed {
+    let mut numbers = params.split(';').filter_map(|n| n.parse::<u16>().ok());
+    let (Some(button), Some(column), Some(row)) = (numbers.next(), numbers.next(), numbers.next())
+    else {
+        return Parsed::Skip(length);
+    };
+
+    let mut modifiers = KeyModifiers::NONE;
+    if button & 4 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if button & 8 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if button & 16 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+
+    let mouse_button = match button & 3 {
+        0 => MouseButton::Left,
+        1 => MouseButton::Middle,
+        _ => MouseButton::Right,
+    };
+    let kind = match (button & 64 != 0, button & 32 != 0, last) {
+        (true, _, _) if button & 1 == 0 => MouseEventKind::ScrollUp,
+        (true, _, _) => MouseEventKind::ScrollDown,
+        (false, true, _) if button & 3 == 3 => MouseEventKind::Moved,
+        (false, true, _) => MouseEventKind::Drag(mouse_button),
+        (false, false, b'M') => MouseEventKind::Down(mouse_button),
+        (false, false, _) => MouseEventKind::Up(mouse_button),
+    };
+
+    let event = MouseEvent {
+        kind,
+        column: column.saturating_sub(1),
+        row: row.saturating_sub(1),
+        modifiers,
+    };
+    Parsed::Event(Event::Mouse(event), length)
+}
+
+fn modifiers(value: u16) -> KeyModifiers {
+    let bits = value.saturating_sub(1);
+    let mut modifiers = KeyModifiers::NONE;
+    if bits & 1 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if bits & 2 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if bits & 4 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+    modifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InputParser;
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn alt_char() {
+        let mut parser = InputParser::default();
+        assert_eq!(
+            parser.feed(b"\x1bpa"),
+            [
+                key(KeyCode::Char('p'), KeyModifiers::ALT),
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn csi_split_across_reads() {
+        let mut parser = InputParser::default();
+        assert_eq!(parser.feed(b"\x1b"), []);
+        assert!(parser.escape_pending());
+        assert_eq!(parser.feed(b"[1;5"), []);
+        assert_eq!(parser.feed(b"A"), [key(KeyCode::Up, KeyModifiers::CONTROL)]);
+        assert_eq!(parser.flush_escape(), None);
+    }
+
+    #[test]
+    fn lone_escape_is_flushed() {
+        let mut parser = InputParser::default();
+        assert_eq!(parser.feed(b"\x1b"), []);
+        assert_eq!(
+            parser.flush_escape(),
+            Some(key(KeyCode::Esc, KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parser.feed(b"a"),
+            [key(KeyCode::Char('a'), KeyModifiers::NONE)]
+        );
+    }
+
+    #[test]
+    fn bracketed_paste() {
+        let mut parser = InputParser::default();
+        assert_eq!(parser.feed(b"\x1b[200~hello\x1b"), []);
+        assert_eq!(
+            parser.feed(b"[Aworld\x1b[201~x"),
+            [
+                Event::Paste("hello\x1b[Aworld".to_owned()),
+                key(KeyCode::Char('x'), KeyModifiers::NONE),
+            ]
+        );
+    }
+}
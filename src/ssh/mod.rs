@@ -0,0 +1,344 @@
+use crate::config::{SshConfig, UiConfig};
+use crate::ssh::input::InputParser;
+use crate::{AppEvent, EVENT_BUFFER, SteelApp, terminal};
+use anyhow::Context;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::ExecutableCommand;
+use ratatui::crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event,
+};
+use ratatui::crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::Rect;
+use ratatui::{Terminal, TerminalOptions, Viewport};
+use russh::keys::ssh_key::LineEnding;
+use russh::keys::ssh_key::rand_core::OsRng;
+use russh::keys::{Algorithm, PrivateKey, PublicKey, load_secret_key};
+use russh::server::{Auth, Config, Handler, Msg, Server as _, Session};
+use russh::{Channel, ChannelId, CryptoVec, Pty};
+use std::io::{self, Write};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{fs, mem};
+use steel_core::server::Server;
+use steel_utils::locks::SyncMutex;
+use tokio::select;
+use tokio::sync::mpsc;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+mod input;
+
+/// How long an escape waits for the rest of a sequence before it counts as the Esc key
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Starts the SSH console if it is enabled, it stops once `server_token` is cancelled
+pub fn spawn(
+    server: Arc<Server>,
+    config: SshConfig,
+    ui: UiConfig,
+    server_token: CancellationToken,
+) {
+    if !config.enabled {
+        return;
+    }
+
+    let (host_key, authorized_keys) = match load_host_key(&config.host_key)
+        .and_then(|host_key| Ok((host_key, load_authorized_keys(&config.authorized_keys)?)))
+    {
+        Ok(keys) => keys,
+        Err(err) => {
+            warn!("Failed to start the SSH console: {err:#}");
+            return;
+        }
+    };
+    if authorized_keys.is_empty() {
+        warn!(
+            "The SSH console is enabled but {} contains no keys, refusing to start",
+            config.authorized_keys.display()
+        );
+        return;
+    }
+
+    let ssh_config = Arc::new(Config {
+        keys: vec![host_key],
+        auth_rejection_time: Duration::from_secs(1),
+        ..Default::default()
+    });
+    let mut ssh_server = SshServer {
+        server,
+        ui,
+        server_token: server_token.clone(),
+        authorized_keys: Arc::new(authorized_keys),
+    };
+
+    tokio::spawn(async move {
+        let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, config.port));
+        info!("SSH console running on {addr}");
+        select! {
+            () = server_token.cancelled() => (),
+            result = ssh_server.run_on_address(ssh_config, addr) => {
+                if let Err(err) = result {
+                    warn!("SSH console stopped: {err}");
+                }
+            }
+        }
+    });
+}
+
+fn load_host_key(path: &Path) -> anyhow::Result<PrivateKey> {
+    if path.exists() {
+        return load_secret_key(path, None).context("failed to load SSH host key");
+    }
+
+    let key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519)
+        .context("failed to generate SSH host key")?;
+    key.write_openssh_file(path, LineEnding::LF)
+        .context("failed to write SSH host key")?;
+    Ok(key)
+}
+
+fn load_authorized_keys(path: &Path) -> anyhow::Result<Vec<PublicKey>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    fs::read_to_string(path)
+        .context("failed to read SSH authorized keys")?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| PublicKey::from_openssh(line).context("invalid SSH authorized key"))
+        .collect()
+}
+
+#[derive(Clone)]
+struct SshServer {
+    server: Arc<Server>,
+    ui: UiConfig,
+    server_token: CancellationToken,
+    authorized_keys: Arc<Vec<PublicKey>>,
+}
+
+impl russh::server::Server for SshServer {
+    type Handler = SshSession;
+
+    fn new_client(&mut self, peer_addr: Option<SocketAddr>) -> Self::Handler {
+        SshSession {
+            shared: self.clone(),
+            peer: peer_addr,
+            user: None,
+            area: Rect::new(0, 0, 80, 24),
+            parser: Arc::new(SyncMutex::new(InputParser::default())),
+            events: None,
+        }
+    }
+}
+
+struct SshSession {
+    shared: SshServer,
+    peer: Option<SocketAddr>,
+    /// The user that logged in, once the key was accepted
+    user: Option<String>,
+    area: Rect,
+    /// Shared with the task sending a lone escape as the Esc key once no sequence followed it
+    parser: Arc<SyncMutex<InputParser>>,
+    events: Option<mpsc::Sender<AppEvent>>,
+}
+
+impl SshSession {
+    /// Who is connected, as written into the audit log
+    fn client(&self) -> String {
+        let user = self.user.as_deref().unwrap_or("unknown");
+        match self.peer {
+            Some(peer) => format!("{user}@{peer}"),
+            None => user.to_owned(),
+        }
+    }
+
+    async fn send(&self, event: Event) {
+        if let Some(events) = &self.events {
+            // the console has exited if this fails, the channel gets closed by its writer
+            terminal::forward(events, event).await;
+        }
+    }
+}
+
+impl Handler for SshSession {
+    type Error = anyhow::Error;
+
+    async fn auth_publickey(&mut self, user: &str, key: &PublicKey) -> Result<Auth, Self::Error> {
+        let authorized = self
+            .shared
+            .authorized_keys
+            .iter()
+            .any(|authorized| authorized.key_data() == key.key_data());
+
+        if authorized {
+            info!("{user} logged into the SSH console");
+            self.user = Some(user.to_owned());
+            Ok(Auth::Accept)
+        } else {
+            Ok(Auth::Reject {
+                proceed_with_methods: None,
+                partial_success: false,
+            })
+        }
+    }
+
+    async fn channel_open_session(
+        &mut self,
+        _channel: Channel<Msg>,
+        _session: &mut Session,
+    ) -> Result<bool, Self::Error> {
+        Ok(self.events.is_none())
+    }
+
+    async fn pty_request(
+        &mut self,
+        _channel: ChannelId,
+        _term: &str,
+        col_width: u32,
+        row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        _modes: &[(Pty, u32)],
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.area = Rect::new(0, 0, col_width as u16, row_height as u16);
+        Ok(())
+    }
+
+    async fn shell_request(
+        &mut self,
+        channel: ChannelId,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let handle = session.handle();
+        let (output_tx, mut output_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+        tokio::spawn(async move {
+            while let Some(bytes) = output_rx.recv().await {
+                if handle.data(channel, CryptoVec::from(bytes)).await.is_err() {
+                    return;
+                }
+            }
+            let _ = handle.close(channel).await;
+        });
+
+        let (event_tx, event_rx) = mpsc::channel(EVENT_BUFFER);
+        self.events = Some(event_tx);
+
+        let shared = &self.shared;
+        let app = SteelApp::builder(shared.server.clone())
+            .ui(shared.ui.clone())
+            .token(shared.server_token.child_token())
+            .server_token(shared.server_token.clone())
+            .remote(self.client())
+            .events(event_rx)
+            .build()?;
+        let writer = ChannelWriter {
+            buf: Vec::new(),
+            output: output_tx,
+        };
+        let area = self.area;
+        tokio::spawn(async move {
+            if let Err(err) = run_console(app, writer, area).await {
+                warn!("SSH console session failed: {err:#}");
+            }
+        });
+        Ok(())
+    }
+
+    async fn data(
+        &mut self,
+        _channel: ChannelId,
+        data: &[u8],
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let (events, escape_pending) = {
+            let mut parser = self.parser.lock();
+            (parser.feed(data), parser.escape_pending())
+        };
+        for event in events {
+            self.send(event).await;
+        }
+        if escape_pending && let Some(sender) = self.events.clone() {
+            let parser = self.parser.clone();
+            tokio::spawn(async move {
+                time::sleep(ESCAPE_TIMEOUT).await;
+                let flushed = parser.lock().flush_escape();
+                if let Some(event) = flushed {
+                    terminal::forward(&sender, event).await;
+                }
+            });
+        }
+        Ok(())
+    }
+
+    async fn window_change_request(
+        &mut self,
+        _channel: ChannelId,
+        col_width: u32,
+        row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.area = Rect::new(0, 0, col_width as u16, row_height as u16);
+        self.send(Event::Resize(self.area.width, self.area.height))
+            .await;
+        Ok(())
+    }
+}
+
+/// Buffers terminal output and forwards it to the SSH channel on flush
+struct ChannelWriter {
+    buf: Vec<u8>,
+    output: mpsc::UnboundedSender<Vec<u8>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        self.output
+            .send(mem::take(&mut self.buf))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+}
+
+async fn run_console(mut app: SteelApp, writer: ChannelWriter, area: Rect) -> anyhow::Result<()> {
+    let mut backend = CrosstermBackend::new(writer);
+    backend
+        .execute(EnterAlternateScreen)?
+        .execute(EnableMouseCapture)?
+        .execute(EnableBracketedPaste)?;
+
+    let mut terminal = Terminal::with_options(
+        backend,
+        TerminalOptions {
+            viewport: Viewport::Fixed(area),
+        },
+    )?;
+    terminal.clear()?;
+
+    app.session.open();
+    let result = app.run_on(&mut terminal).await;
+    app.session.close();
+    result?;
+
+    terminal
+        .backend_mut()
+        .execute(DisableBracketedPaste)?
+        .execute(DisableMouseCapture)?
+        .execute(LeaveAlternateScreen)?;
+    Ok(())
+}
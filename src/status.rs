@@ -0,0 +1,184 @@
+use serde::Serialize;
+use std::sync::LazyLock;
+use std::time::Duration;
+use steel_core::server::Server;
+use steel_utils::locks::SyncMutex;
+use tokio::time;
+use uuid::Uuid;
+
+/// How often the tick count is checked while waiting for the server to finish starting
+const STARTED_POLL: Duration = Duration::from_millis(50);
+
+/// Names of the plugins enabled by the plugin host
+pub(crate) static PLUGINS: LazyLock<SyncMutex<Vec<String>>> =
+    LazyLock::new(|| SyncMutex::new(Vec::new()));
+
+/// A player connected to the server
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerInfo {
+    pub name: String,
+    pub uuid: Uuid,
+    pub client: Client,
+    /// The brand the client announced, like `vanilla` or `fabric`, missing until it sent it
+    pub brand: Option<String>,
+}
+
+/// The edition of Minecraft a player plays with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Client {
+    Java,
+    /// Connected through a GeyserMC proxy with Floodgate
+    Bedrock,
+}
+
+impl Client {
+    /// The edition a player whose client announced `brand` plays with
+    ///
+    /// GeyserMC connects Bedrock players with its own name as the brand, every other brand is a Java client.
+    pub fn of(brand: Option<&str>) -> Self {
+        if brand.is_some_and(|brand| brand.to_ascii_lowercase().starts_with("geyser")) {
+            Self::Bedrock
+        } else {
+            Self::Java
+        }
+    }
+
+    /// The name of the edition, like `Java`
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Java => "Java",
+            Self::Bedrock => "Bedrock",
+        }
+    }
+}
+
+/// Tick timings of the server
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TickInfo {
+    pub tps: f32,
+    pub mspt: f32,
+}
+
+/// A world loaded by the server
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldInfo {
+    pub name: String,
+    pub loaded_chunks: usize,
+}
+
+/// Memory usage of the server process
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MemoryInfo {
+    pub resident_bytes: Option<u64>,
+}
+
+/// What the panels show about a server, read from the running server or made up by the demo
+pub trait ServerStatus: Send + Sync {
+    /// Every player connected to any world
+    fn players(&self) -> Vec<PlayerInfo>;
+
+    /// Every loaded world
+    fn worlds(&self) -> Vec<WorldInfo>;
+
+    /// The current tick rate and the average time spent per tick
+    fn ticks(&self) -> TickInfo;
+}
+
+impl ServerStatus for Server {
+    fn players(&self) -> Vec<PlayerInfo> {
+        players(self)
+    }
+
+    fn worlds(&self) -> Vec<WorldInfo> {
+        worlds(self)
+    }
+
+    fn ticks(&self) -> TickInfo {
+        ticks(self)
+    }
+}
+
+/// Returns every player connected to any world
+pub fn players(server: &Server) -> Vec<PlayerInfo> {
+    let mut players = Vec::new();
+    for world in server.worlds.values() {
+        world.players.iter_players(|_, player| {
+            let brand = player.client_brand();
+            players.push(PlayerInfo {
+                name: player.gameprofile.name.clone(),
+                uuid: player.gameprofile.id,
+                client: Client::of(brand.as_deref()),
+                brand,
+            });
+            true
+        });
+    }
+    players
+}
+
+/// Returns every loaded world
+pub fn worlds(server: &Server) -> Vec<WorldInfo> {
+    server
+        .worlds
+        .iter()
+        .map(|(name, world)| WorldInfo {
+            name: name.to_string(),
+            loaded_chunks: world.chunk_map.loaded_chunk_count(),
+        })
+        .collect()
+}
+
+/// Returns how many ticks the server has completed since it started
+pub fn tick_count(server: &Server) -> u64 {
+    server.tick_count()
+}
+
+/// Waits until `server` completes a tick, which it does once it finished starting
+///
+/// Its world is loaded and its port bound by then, a server that fails to bind never gets here.
+pub async fn started(server: &Server) {
+    let ticks = tick_count(server);
+    while tick_count(server) == ticks {
+        time::sleep(STARTED_POLL).await;
+    }
+}
+
+/// Returns the current tick rate and the average time spent per tick
+pub fn ticks(server: &Server) -> TickInfo {
+    let tick_rate = server.tick_rate_manager.read();
+    let mspt = tick_rate.average_tick_time().as_secs_f32() * 1000.0;
+    let tps = if mspt > 0.0 {
+        (1000.0 / mspt).min(tick_rate.tickrate)
+    } else {
+        tick_rate.tickrate
+    };
+    TickInfo { tps, mspt }
+}
+
+/// Returns the memory used by this process, where the platform reports it
+pub fn memory() -> MemoryInfo {
+    MemoryInfo {
+        resident_bytes: resident_bytes(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn resident_bytes() -> Option<u64> {
+    use std::fs;
+
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+const fn resident_bytes() -> Option<u64> {
+    None
+}
+
+/// Returns the names of all enabled plugins
+pub fn plugins() -> Vec<String> {
+    PLUGINS.lock().clone()
+}
@@ -0,0 +1,32 @@
+use crate::command::{self, CommandOrigin};
+use std::io::{self, BufRead};
+use std::sync::Arc;
+use std::thread;
+use steel_core::server::Server;
+use tokio::runtime::Handle;
+use tracing::warn;
+
+/// Runs every line read from stdin as a console command of `server`, for when there is no terminal ui
+///
+/// This lets `echo stop | steel-tui` and wrappers that write into the stdin of the server control it. Reading stops
+/// once stdin is closed. Has to be called from within the runtime, commands like `save-all` spawn tasks on it.
+pub fn read_commands(server: Arc<Server>) {
+    let runtime = Handle::current();
+    let spawned = thread::Builder::new()
+        .name("stdin-console".to_owned())
+        .spawn(move || {
+            let _runtime = runtime.enter();
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                let line = line.trim();
+                if !line.is_empty() {
+                    command::dispatch(&server, CommandOrigin::Console, None, line.to_owned());
+                }
+            }
+        });
+    if let Err(err) = spawned {
+        warn!("Failed to read commands from stdin: {err}");
+    }
+}
@@ -0,0 +1,49 @@
+use crate::status;
+use sd_notify::NotifyState;
+use std::sync::Arc;
+use std::time::Duration;
+use steel_core::server::Server;
+use tokio::select;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+fn notify(state: &[NotifyState]) {
+    if let Err(err) = sd_notify::notify(false, state) {
+        debug!("Failed to notify systemd: {err}");
+    }
+}
+
+/// Tells systemd the server has started and keeps its watchdog fed if the unit enables one.
+///
+/// The watchdog is only fed while `server` completes ticks, so systemd restarts a server whose tick loop hangs even
+/// though the runtime around it still runs. Feeding stops once `server_token` is cancelled.
+pub fn ready(server: Arc<Server>, server_token: CancellationToken) {
+    notify(&[NotifyState::Ready]);
+
+    let mut usec = 0;
+    if !sd_notify::watchdog_enabled(false, &mut usec) {
+        return;
+    }
+
+    let mut interval = time::interval(Duration::from_micros(usec) / 2);
+    tokio::spawn(async move {
+        let mut ticks = status::tick_count(&server);
+        loop {
+            select! {
+                () = server_token.cancelled() => return,
+                _ = interval.tick() => (),
+            }
+            let count = status::tick_count(&server);
+            if count != ticks {
+                ticks = count;
+                notify(&[NotifyState::Watchdog]);
+            }
+        }
+    });
+}
+
+/// Tells systemd the process is shutting down, which stops the whole unit
+pub fn stopping() {
+    notify(&[NotifyState::Stopping]);
+}
@@ -0,0 +1,213 @@
+use crate::{AppEvent, EVENT_BUFFER};
+use anyhow::Context;
+use futures::StreamExt;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::ExecutableCommand;
+use ratatui::crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event,
+    EventStream, KeyboardEnhancementFlags, MouseEventKind, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
+};
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement,
+};
+use ratatui::{DefaultTerminal, Terminal};
+use std::io::{Write, stdout};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Once};
+use steel_utils::locks::SyncMutex;
+use tokio::select;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio_util::sync::CancellationToken;
+use tracing::error;
+
+/// Token of the app currently drawing on the terminal, cancelled if anything panics
+static ACTIVE: LazyLock<SyncMutex<Option<CancellationToken>>> =
+    LazyLock::new(|| SyncMutex::new(None));
+
+/// Whether the keyboard enhancements of the kitty protocol were turned on, which have to be turned off again
+static KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+
+/// Turns on the kitty keyboard protocol where the terminal supports it
+///
+/// It reports chords legacy terminals can't tell apart, like Ctrl+Enter and Shift+Enter, and key releases. Terminals
+/// without it keep sending the legacy key codes.
+fn enhance_keyboard(out: &mut impl Write) {
+    if !supports_keyboard_enhancement().unwrap_or(false) {
+        return;
+    }
+    let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+        | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+        | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS;
+    if out.execute(PushKeyboardEnhancementFlags(flags)).is_ok() {
+        KEYBOARD_ENHANCED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Turns the kitty keyboard protocol off again, if `enhance_keyboard` turned it on
+fn restore_keyboard(out: &mut impl Write) {
+    if KEYBOARD_ENHANCED.swap(false, Ordering::Relaxed) {
+        let _ = out.execute(PopKeyboardEnhancementFlags);
+    }
+}
+
+/// Restores the terminal before the panic is printed and exits the app that was drawing on it
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        // the previous hook is the one of ratatui, which leaves raw mode and the alternate screen
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let active = ACTIVE.lock().take();
+            if active.is_some() {
+                // the hook of ratatui only knows about terminals it set up itself
+                let _ = disable_raw_mode();
+                restore_keyboard(&mut stdout());
+                let _ = stdout().execute(DisableBracketedPaste);
+                let _ = stdout().execute(DisableMouseCapture);
+            }
+            previous(info);
+            if let Some(token) = active {
+                token.cancel();
+            }
+        }));
+    });
+}
+
+/// Spawns a task forwarding the events of the terminal until `token` is cancelled
+///
+/// The channel is closed if reading from the terminal fails, which exits the app.
+pub fn events(token: &CancellationToken) -> mpsc::Receiver<AppEvent> {
+    let (tx, rx) = mpsc::channel(EVENT_BUFFER);
+    let event_token = token.child_token();
+    tokio::spawn(async move {
+        let mut stream = EventStream::new();
+        loop {
+            let event = select! {
+                () = event_token.cancelled() => return,
+                event = stream.next() => event,
+            };
+            match event {
+                Some(Ok(event)) => {
+                    if !forward(&tx, event).await {
+                        return;
+                    }
+                }
+                Some(Err(err)) => {
+                    error!("Failed to read terminal events: {err}");
+                    return;
+                }
+                None => return,
+            }
+        }
+    });
+    rx
+}
+
+/// Queues `event` for the app, waiting while the queue is full instead of losing input
+///
+/// Mouse movement is dropped while the app lags behind, it is stale by the time the app catches up.
+/// Returns `false` once the app has exited.
+pub async fn forward(tx: &mpsc::Sender<AppEvent>, event: Event) -> bool {
+    match tx.try_send(AppEvent::UiEvent(event)) {
+        Ok(()) => true,
+        Err(TrySendError::Full(AppEvent::UiEvent(Event::Mouse(event))))
+            if matches!(event.kind, MouseEventKind::Moved | MouseEventKind::Drag(_)) =>
+        {
+            true
+        }
+        Err(TrySendError::Full(event)) => tx.send(event).await.is_ok(),
+        Err(TrySendError::Closed(_)) => false,
+    }
+}
+
+/// Puts the terminal into raw mode with bracketed paste and optionally mouse capture enabled.
+///
+/// Without `alternate_screen` the app draws over the normal screen, so its last frame stays in the scrollback of the
+/// terminal after it exits. `token` is cancelled if anything panics before `restore` is called.
+pub fn init(
+    mouse_capture: bool,
+    alternate_screen: bool,
+    token: &CancellationToken,
+) -> anyhow::Result<DefaultTerminal> {
+    let mut terminal = if alternate_screen {
+        ratatui::try_init()?
+    } else {
+        enable_raw_mode().context("failed to enable raw mode")?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        terminal.clear().context("failed to clear the terminal")?;
+        terminal
+    };
+    install_panic_hook();
+    *ACTIVE.lock() = Some(token.clone());
+    if mouse_capture {
+        terminal
+            .backend_mut()
+            .execute(EnableMouseCapture)
+            .context("failed to enable mouse capture")?;
+    }
+    terminal
+        .backend_mut()
+        .execute(EnableBracketedPaste)
+        .context("failed to enable bracketed paste")?;
+    enhance_keyboard(terminal.backend_mut().writer_mut());
+    Ok(terminal)
+}
+
+/// Undoes everything `init` did to the terminal
+pub fn restore(terminal: &mut DefaultTerminal, mouse_capture: bool) -> anyhow::Result<()> {
+    *ACTIVE.lock() = None;
+    restore_keyboard(terminal.backend_mut().writer_mut());
+    terminal
+        .backend_mut()
+        .execute(DisableBracketedPaste)
+        .context("failed to disable bracketed paste")?;
+    if mouse_capture {
+        terminal
+            .backend_mut()
+            .execute(DisableMouseCapture)
+            .context("failed to disable mouse capture")?;
+    }
+    ratatui::try_restore().context("failed to restore terminal")?;
+    Ok(())
+}
+
+/// Hands the terminal back to the shell and stops the process until it is continued with SIGCONT,
+/// then takes the terminal over again and redraws it from scratch
+#[cfg(unix)]
+pub fn suspend(
+    terminal: &mut DefaultTerminal,
+    mouse_capture: bool,
+    alternate_screen: bool,
+    token: &CancellationToken,
+) -> anyhow::Result<()> {
+    use ratatui::crossterm::terminal::EnterAlternateScreen;
+    use signal_hook::consts::SIGTSTP;
+    use signal_hook::low_level::raise;
+
+    restore(terminal, mouse_capture)?;
+    raise(SIGTSTP).context("failed to suspend")?;
+
+    enable_raw_mode().context("failed to enable raw mode")?;
+    if alternate_screen {
+        terminal
+            .backend_mut()
+            .execute(EnterAlternateScreen)
+            .context("failed to enter the alternate screen")?;
+    }
+    if mouse_capture {
+        terminal
+            .backend_mut()
+            .execute(EnableMouseCapture)
+            .context("failed to enable mouse capture")?;
+    }
+    terminal
+        .backend_mut()
+        .execute(EnableBracketedPaste)
+        .context("failed to enable bracketed paste")?;
+    enhance_keyboard(terminal.backend_mut().writer_mut());
+    *ACTIVE.lock() = Some(token.clone());
+    terminal.clear().context("failed to clear the terminal")?;
+    Ok(())
+}
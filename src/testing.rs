@@ -0,0 +1,152 @@
+use crate::config::UiConfig;
+use crate::logger::TuiLoggerWriter;
+use crate::{AppEvent, SteelApp};
+use ratatui::backend::TestBackend;
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::{Terminal, TerminalOptions, Viewport};
+use std::io::Write;
+use std::sync::Arc;
+use steel_core::server::Server;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Drives a `SteelApp` on a `TestBackend` with synthetic input instead of a real terminal.
+///
+/// The log is process wide, so log lines pushed by one harness show up in every other one.
+pub struct TestHarness {
+    app: SteelApp,
+    terminal: Terminal<TestBackend>,
+    token: CancellationToken,
+    server_token: CancellationToken,
+    // keeps the event channel of the app open
+    _events: mpsc::Sender<AppEvent>,
+}
+
+impl TestHarness {
+    /// Creates a harness rendering onto a `width` by `height` buffer
+    pub fn new(server: Arc<Server>, ui: UiConfig, width: u16, height: u16) -> anyhow::Result<Self> {
+        let token = CancellationToken::new();
+        let server_token = token.child_token();
+        let (events, event_rx) = mpsc::channel(1);
+        let app = SteelApp::builder(server)
+            .ui(ui)
+            .token(token.clone())
+            .server_token(server_token.clone())
+            .events(event_rx)
+            .build()?;
+        let Ok(terminal) = Terminal::with_options(
+            TestBackend::new(width, height),
+            TerminalOptions {
+                viewport: Viewport::Fixed(Rect::new(0, 0, width, height)),
+            },
+        );
+
+        Ok(Self {
+            app,
+            terminal,
+            token,
+            server_token,
+            _events: events,
+        })
+    }
+
+    /// Handles `event` the same way the event loop does
+    pub fn send(&mut self, event: Event) -> &mut Self {
+        match event {
+            Event::Resize(width, height) => {
+                let area = Rect::new(0, 0, width, height);
+                self.terminal.backend_mut().resize(width, height);
+                let Ok(()) = self.terminal.resize(area);
+            }
+            event => self.app.handle_event(event),
+        }
+        self
+    }
+
+    /// Presses `code` without any modifiers
+    pub fn press(&mut self, code: KeyCode) -> &mut Self {
+        self.send(Event::Key(KeyEvent::from(code)))
+    }
+
+    /// Presses `code` while holding `modifiers`
+    pub fn press_with(&mut self, code: KeyCode, modifiers: KeyModifiers) -> &mut Self {
+        self.send(Event::Key(KeyEvent::new(code, modifiers)))
+    }
+
+    /// Types every character of `text` into the input line
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        for c in text.chars() {
+            self.press(KeyCode::Char(c));
+        }
+        self
+    }
+
+    /// Writes `line` into the log as if it was logged, ansi colors included
+    pub fn log(&mut self, line: &str) -> &mut Self {
+        // writing into the log never fails
+        let _ = writeln!(TuiLoggerWriter, "{line}");
+        self
+    }
+
+    /// Renders the app and returns the resulting buffer
+    pub fn render(&mut self) -> &Buffer {
+        let Ok(_) = self.terminal.draw(|frame| self.app.render_frame(frame));
+        self.terminal.backend().buffer()
+    }
+
+    /// Renders the app and returns the buffer as plain text, one line per row
+    pub fn snapshot(&mut self) -> String {
+        rows(self.render())
+    }
+
+    /// Whether the app asked the primary server to stop
+    #[must_use]
+    pub fn server_stopped(&self) -> bool {
+        self.server_token.is_cancelled()
+    }
+
+    /// Whether the app asked to exit
+    #[must_use]
+    pub fn exited(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+/// The symbols of `buffer` as plain text, one line per row
+fn rows(buffer: &Buffer) -> String {
+    let width = usize::from(buffer.area.width).max(1);
+    buffer
+        .content
+        .chunks(width)
+        .map(|row| row.iter().map(Cell::symbol).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rows;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    #[test]
+    fn rows_are_joined_by_newlines() {
+        let buffer = Buffer::with_lines(["ab", "cd"]);
+        assert_eq!(rows(&buffer), "ab\ncd");
+    }
+
+    #[test]
+    fn rows_keep_trailing_spaces() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 2));
+        buffer.set_string(0, 1, "x", Style::new());
+        assert_eq!(rows(&buffer), "    \nx   ");
+    }
+
+    #[test]
+    fn empty_buffer_has_no_rows() {
+        assert_eq!(rows(&Buffer::empty(Rect::ZERO)), "");
+    }
+}
@@ -0,0 +1,206 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use serde_json::{Map, Value};
+
+/// Commands whose arguments end with a JSON text component worth previewing
+const COMMANDS: [&str; 2] = ["tellraw", "title"];
+
+/// The colors of chat formatting by name, as the vanilla client shows them
+const COLORS: [(&str, Color); 16] = [
+    ("black", Color::Rgb(0x00, 0x00, 0x00)),
+    ("dark_blue", Color::Rgb(0x00, 0x00, 0xAA)),
+    ("dark_green", Color::Rgb(0x00, 0xAA, 0x00)),
+    ("dark_aqua", Color::Rgb(0x00, 0xAA, 0xAA)),
+    ("dark_red", Color::Rgb(0xAA, 0x00, 0x00)),
+    ("dark_purple", Color::Rgb(0xAA, 0x00, 0xAA)),
+    ("gold", Color::Rgb(0xFF, 0xAA, 0x00)),
+    ("gray", Color::Rgb(0xAA, 0xAA, 0xAA)),
+    ("dark_gray", Color::Rgb(0x55, 0x55, 0x55)),
+    ("blue", Color::Rgb(0x55, 0x55, 0xFF)),
+    ("green", Color::Rgb(0x55, 0xFF, 0x55)),
+    ("aqua", Color::Rgb(0x55, 0xFF, 0xFF)),
+    ("red", Color::Rgb(0xFF, 0x55, 0x55)),
+    ("light_purple", Color::Rgb(0xFF, 0x55, 0xFF)),
+    ("yellow", Color::Rgb(0xFF, 0xFF, 0x55)),
+    ("white", Color::Rgb(0xFF, 0xFF, 0xFF)),
+];
+
+/// The formatting flags of a component with the modifier each one shows as
+const FLAGS: [(&str, Modifier); 5] = [
+    ("bold", Modifier::BOLD),
+    ("italic", Modifier::ITALIC),
+    ("underlined", Modifier::UNDERLINED),
+    ("strikethrough", Modifier::CROSSED_OUT),
+    ("obfuscated", Modifier::RAPID_BLINK),
+];
+
+/// The JSON text component at the end of `command`, if it is a command sending one
+pub fn component(command: &str) -> Option<&str> {
+    let (name, _) = command.split_once(' ')?;
+    if !COMMANDS.contains(&name) {
+        return None;
+    }
+    // the component is the first argument opening like JSON, selectors only have brackets after the `@`
+    let start = command
+        .match_indices(' ')
+        .map(|(at, _)| at + 1)
+        .find(|&at| command[at..].starts_with(['{', '[', '"']))?;
+    Some(&command[start..])
+}
+
+/// Renders the JSON text component `json` like the client shows it, followed by its click and hover events
+pub fn preview(json: &str) -> Result<Text<'static>, String> {
+    let component: Value =
+        serde_json::from_str(json).map_err(|err| format!("Invalid JSON: {err}"))?;
+    let mut spans = Vec::new();
+    let mut events = Vec::new();
+    walk(&component, Style::new(), &mut spans, &mut events)?;
+
+    let mut text = Text::from(Line::from(spans));
+    for (index, event) in events.into_iter().enumerate() {
+        text.push_line(
+            Line::from(format!("[{}] {event}", index + 1)).style(Style::new().fg(Color::DarkGray)),
+        );
+    }
+    Ok(text)
+}
+
+fn walk(
+    component: &Value,
+    inherited: Style,
+    spans: &mut Vec<Span<'static>>,
+    events: &mut Vec<String>,
+) -> Result<(), String> {
+    match component {
+        Value::String(text) => spans.push(Span::styled(text.clone(), inherited)),
+        Value::Array(components) => {
+            // the first component is the parent of the ones after it
+            let Some((first, rest)) = components.split_first() else {
+                return Ok(());
+            };
+            let style = match first {
+                Value::Object(object) => style(object, inherited)?,
+                _ => inherited,
+            };
+            walk(first, inherited, spans, events)?;
+            for component in rest {
+                walk(component, style, spans, events)?;
+            }
+        }
+        Value::Object(object) => {
+            let style = style(object, inherited)?;
+            spans.push(Span::styled(content(object)?, style));
+            if let Some(marker) = annotate(object, events) {
+                spans.push(Span::styled(marker, Style::new().fg(Color::DarkGray)));
+            }
+            if let Some(extra) = object.get("extra") {
+                let Value::Array(extra) = extra else {
+                    return Err("`extra` has to be a list of components".to_owned());
+                };
+                for component in extra {
+                    walk(component, style, spans, events)?;
+                }
+            }
+        }
+        Value::Bool(_) | Value::Number(_) => {
+            spans.push(Span::styled(component.to_string(), inherited))
+        }
+        Value::Null => return Err("a component can't be null".to_owned()),
+    }
+    Ok(())
+}
+
+/// The style of `object` on top of the one it inherits
+fn style(object: &Map<String, Value>, inherited: Style) -> Result<Style, String> {
+    let mut style = inherited;
+    if let Some(color) = object.get("color") {
+        let Value::String(color) = color else {
+            return Err("`color` has to be a string".to_owned());
+        };
+        style = style.fg(parse_color(color)?);
+    }
+    for (flag, modifier) in FLAGS {
+        match object.get(flag) {
+            Some(Value::Bool(true)) => style = style.add_modifier(modifier),
+            Some(Value::Bool(false)) => style = style.remove_modifier(modifier),
+            Some(_) => return Err(format!("`{flag}` has to be true or false")),
+            None => (),
+        }
+    }
+    Ok(style)
+}
+
+fn parse_color(color: &str) -> Result<Color, String> {
+    if let Some(hex) = color.strip_prefix('#') {
+        let rgb = u32::from_str_radix(hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 6)
+            .ok_or_else(|| format!("`{color}` is not a hex color"))?;
+        let [_, r, g, b] = rgb.to_be_bytes();
+        return Ok(Color::Rgb(r, g, b));
+    }
+    COLORS
+        .iter()
+        .find(|(name, _)| *name == color)
+        .map(|(_, color)| *color)
+        .ok_or_else(|| format!("`{color}` is not a color"))
+}
+
+/// What `object` shows, with placeholders for what only the client or the server fills in
+fn content(object: &Map<String, Value>) -> Result<String, String> {
+    let field = |name: &str| object.get(name).and_then(Value::as_str);
+    if object.contains_key("text") {
+        return field("text")
+            .map(str::to_owned)
+            .ok_or_else(|| "`text` has to be a string".to_owned());
+    }
+    if let Some(key) = field("translate") {
+        return Ok(format!("‹{key}›"));
+    }
+    if let Some(selector) = field("selector") {
+        return Ok(format!("‹{selector}›"));
+    }
+    if let Some(keybind) = field("keybind") {
+        return Ok(format!("‹{keybind}›"));
+    }
+    if let Some(score) = object.get("score") {
+        let name = score.get("name").and_then(Value::as_str).unwrap_or("?");
+        let objective = score
+            .get("objective")
+            .and_then(Value::as_str)
+            .unwrap_or("?");
+        return Ok(format!("‹{name} {objective}›"));
+    }
+    if object.contains_key("nbt") {
+        return Ok("‹nbt›".to_owned());
+    }
+    Err("a component needs `text`, `translate`, `selector`, `keybind`, `score` or `nbt`".to_owned())
+}
+
+/// Adds the click and hover events of `object` to `events`, returning the marker pointing at them
+fn annotate(object: &Map<String, Value>, events: &mut Vec<String>) -> Option<String> {
+    let first = events.len();
+    for (field, kind) in [
+        ("clickEvent", "click"),
+        ("click_event", "click"),
+        ("hoverEvent", "hover"),
+        ("hover_event", "hover"),
+    ] {
+        let Some(event) = object.get(field) else {
+            continue;
+        };
+        let action = event.get("action").and_then(Value::as_str).unwrap_or("?");
+        let value = ["value", "url", "command", "contents", "page"]
+            .into_iter()
+            .find_map(|key| event.get(key))
+            .map_or_else(String::new, |value| match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            });
+        events.push(format!("{kind} {action}: {value}"));
+    }
+    let markers: Vec<String> = (first..events.len())
+        .map(|index| (index + 1).to_string())
+        .collect();
+    (!markers.is_empty()).then(|| format!("[{}]", markers.join(",")))
+}
@@ -0,0 +1,42 @@
+use console_subscriber::{ConsoleLayer, Server};
+use std::env;
+use std::net::SocketAddr;
+use std::sync::OnceLock;
+use tracing::Metadata;
+
+/// Targets of the instrumentation tokio emits for `tokio-console`
+const TARGETS: [&str; 2] = ["tokio", "runtime"];
+
+/// Where `tokio-console` can attach, once the layer was built
+static ADDRESS: OnceLock<SocketAddr> = OnceLock::new();
+
+/// Builds the layer `tokio-console` attaches to, listening on `TOKIO_CONSOLE_BIND` or `127.0.0.1:6669`
+///
+/// Its server runs on a thread of its own, so it keeps answering while the runtimes of the server are starved.
+#[must_use]
+pub fn console_layer() -> ConsoleLayer {
+    let address = env::var("TOKIO_CONSOLE_BIND")
+        .ok()
+        .and_then(|address| address.parse().ok())
+        .unwrap_or(SocketAddr::new(Server::DEFAULT_IP, Server::DEFAULT_PORT));
+    let _ = ADDRESS.set(address);
+    ConsoleLayer::builder()
+        .with_default_env()
+        .server_addr(address)
+        .spawn()
+}
+
+/// Whether `metadata` belongs to the instrumentation only `tokio-console` reads, which would flood the log
+#[must_use]
+pub fn is_console_instrumentation(metadata: &Metadata<'_>) -> bool {
+    metadata
+        .target()
+        .split("::")
+        .next()
+        .is_some_and(|root| TARGETS.contains(&root))
+}
+
+/// Where `tokio-console` can attach, if it is enabled
+pub fn address() -> Option<SocketAddr> {
+    ADDRESS.get().copied()
+}
@@ -0,0 +1,168 @@
+use crate::command::{self, CommandOrigin};
+use crate::config::TriggerConfig;
+use crate::logger::ServerLog;
+use crate::notifications::{self, NotificationKind, Severity};
+use anyhow::Context as _;
+use ratatui::style::{Color, Style};
+use ratatui::text::Text;
+use regex::Regex;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+use steel_core::server::Server;
+use steel_utils::locks::SyncMutex;
+use tokio::select;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+
+/// The queue of the commands fired by triggers of every running server, by its log
+static COMMANDS: LazyLock<SyncMutex<HashMap<ServerLog, mpsc::UnboundedSender<String>>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+static TRIGGERS: RwLock<Vec<Trigger>> = RwLock::new(Vec::new());
+
+thread_local! {
+    static HIGHLIGHT: Cell<Option<Color>> = const { Cell::new(None) };
+    static RUNNING: Cell<bool> = const { Cell::new(false) };
+}
+
+struct Trigger {
+    pattern: Regex,
+    command: Option<String>,
+    notify: Option<String>,
+    highlight: Option<Color>,
+}
+
+/// A tracing layer that matches logged messages against the configured triggers and fires their actions
+#[derive(Debug, Clone, Copy)]
+pub struct LogTriggers;
+
+impl LogTriggers {
+    /// Compiles the patterns of `triggers` and replaces the active triggers with them
+    pub fn load(triggers: &[TriggerConfig]) -> anyhow::Result<()> {
+        let triggers = triggers
+            .iter()
+            .map(|trigger| {
+                Ok(Trigger {
+                    pattern: Regex::new(&trigger.pattern).with_context(|| {
+                        format!("invalid trigger pattern `{}`", trigger.pattern)
+                    })?,
+                    command: trigger.command.clone(),
+                    notify: trigger.notify.clone(),
+                    highlight: trigger.highlight,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        *TRIGGERS.write().unwrap_or_else(PoisonError::into_inner) = triggers;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{value:?}");
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            value.clone_into(&mut self.0);
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogTriggers {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        // output of a command run by a trigger must not fire triggers again
+        if RUNNING.get() {
+            return;
+        }
+        let triggers = TRIGGERS.read().unwrap_or_else(PoisonError::into_inner);
+        if triggers.is_empty() {
+            return;
+        }
+
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+
+        for trigger in triggers.iter().filter(|t| t.pattern.is_match(&message.0)) {
+            // the command runs on the server whose log the message went into
+            if let Some(command) = &trigger.command
+                && let Some(commands) = COMMANDS.lock().get(&ServerLog::current())
+            {
+                let _ = commands.send(command.clone());
+            }
+            if let Some(notify) = &trigger.notify {
+                let severity = match *event.metadata().level() {
+                    Level::ERROR => Severity::Error,
+                    Level::WARN => Severity::Warning,
+                    _ => Severity::Info,
+                };
+                notifications::send(NotificationKind::Trigger, severity, notify.clone());
+            }
+            if trigger.highlight.is_some() {
+                HIGHLIGHT.set(trigger.highlight);
+            }
+        }
+    }
+}
+
+/// Applies the highlight of a trigger that matched the event currently being written on this thread
+pub fn highlight(text: &mut Text<'_>) {
+    if let Some(color) = HIGHLIGHT.take() {
+        for line in &mut text.lines {
+            line.style = line.style.patch(Style::new().bg(color));
+        }
+    }
+}
+
+/// Runs `f` with the event it logs on this thread highlighted in `color`, like a matching trigger does
+pub fn highlighted<R>(color: Color, f: impl FnOnce() -> R) -> R {
+    HIGHLIGHT.set(Some(color));
+    let result = f();
+    // nothing was written if the event was filtered out
+    HIGHLIGHT.set(None);
+    result
+}
+
+/// Runs the commands queued by triggers matching the log of the current thread on `server`, until `server_token` is
+/// cancelled
+///
+/// A server started again in the same log replaces the queue of the one that stopped.
+pub fn spawn(server: Arc<Server>, server_token: CancellationToken) {
+    let log = ServerLog::current();
+    let (sender, mut commands) = mpsc::unbounded_channel();
+    COMMANDS.lock().insert(log, sender.clone());
+
+    tokio::spawn(async move {
+        loop {
+            let command = select! {
+                () = server_token.cancelled() => break,
+                command = commands.recv() => match command {
+                    Some(command) => command,
+                    None => break,
+                },
+            };
+
+            RUNNING.set(true);
+            log.scope(|| command::dispatch(&server, CommandOrigin::Trigger, None, command));
+            RUNNING.set(false);
+        }
+        let mut queues = COMMANDS.lock();
+        if queues
+            .get(&log)
+            .is_some_and(|queue| queue.same_channel(&sender))
+        {
+            queues.remove(&log);
+        }
+    });
+}
@@ -0,0 +1,102 @@
+use crate::config::WatchdogConfig;
+use crate::notifications::{self, NotificationKind, Severity};
+use crate::{dump, status, triggers};
+use ratatui::style::Color;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use steel_core::server::Server;
+use tokio::select;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// How bad the tick times are, each level alerting once when it is reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Normal,
+    Slow,
+    Critical,
+    Stalled,
+}
+
+/// Watches the tick times of `server` until `server_token` is cancelled, alerting with a toast and a
+/// highlighted log line whenever they get worse
+pub fn spawn(server: Arc<Server>, config: WatchdogConfig, server_token: CancellationToken) {
+    if !config.enabled {
+        return;
+    }
+    let stall = Duration::from_secs(config.stall_secs);
+    let mut interval = time::interval(Duration::from_secs(1));
+
+    tokio::spawn(async move {
+        let mut level = Level::Normal;
+        // the rolling tick time freezes while the tick thread hangs, a tick that never completes doesn't count
+        let mut ticks = status::tick_count(&server);
+        let mut last_tick = Instant::now();
+
+        loop {
+            select! {
+                () = server_token.cancelled() => return,
+                _ = interval.tick() => (),
+            }
+
+            let count = status::tick_count(&server);
+            if count != ticks {
+                ticks = count;
+                last_tick = Instant::now();
+            }
+            let mspt = status::ticks(&server).mspt;
+            let next = if last_tick.elapsed() >= stall {
+                Level::Stalled
+            } else if mspt >= config.critical_mspt {
+                Level::Critical
+            } else if mspt >= config.slow_mspt {
+                Level::Slow
+            } else {
+                Level::Normal
+            };
+
+            if next > level {
+                alert(next, mspt, last_tick.elapsed());
+                if next == Level::Stalled && config.dump_on_stall {
+                    match dump::write(&server).await {
+                        Ok(path) => warn!("Wrote a diagnostic dump to {}", path.display()),
+                        Err(err) => error!("Failed to write a diagnostic dump: {err:#}"),
+                    }
+                }
+            } else if next == Level::Normal && level != Level::Normal {
+                info!("Tick times are back to normal at {mspt:.1} ms");
+            }
+            level = next;
+        }
+    });
+}
+
+fn alert(level: Level, mspt: f32, since_tick: Duration) {
+    let (severity, color, message) = match level {
+        Level::Normal => return,
+        Level::Slow => (
+            Severity::Warning,
+            Color::Yellow,
+            format!("Ticks are slow, taking {mspt:.1} ms"),
+        ),
+        Level::Critical => (
+            Severity::Error,
+            Color::Red,
+            format!("Ticks are critically slow, taking {mspt:.1} ms"),
+        ),
+        Level::Stalled => (
+            Severity::Error,
+            Color::Red,
+            format!(
+                "The server is stalling, no tick has completed for {}s",
+                since_tick.as_secs()
+            ),
+        ),
+    };
+    triggers::highlighted(color, || match severity {
+        Severity::Error => error!("{message}"),
+        _ => warn!("{message}"),
+    });
+    notifications::send(NotificationKind::SlowTicks, severity, message);
+}
@@ -0,0 +1,113 @@
+use crate::config::{WebhookConfig, WebhookFormat};
+use crate::logger::ServerLog;
+use crate::notifications::{self, Notification, NotificationKind, Severity};
+use reqwest::Client;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
+use steel_utils::locks::SyncMutex;
+use tokio::sync::oneshot;
+use tracing::warn;
+
+/// The webhooks of every running server that has some, by its log
+static SERVERS: LazyLock<SyncMutex<HashMap<ServerLog, Webhooks>>> =
+    LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+/// Whether the task posting to the webhooks of every server is running
+static SPAWNED: AtomicBool = AtomicBool::new(false);
+
+struct Webhooks {
+    configs: Arc<Vec<WebhookConfig>>,
+    /// Told once the server stopped notification went out
+    stopped: oneshot::Sender<()>,
+}
+
+#[derive(Serialize)]
+struct DiscordMessage<'a> {
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct SlackMessage<'a> {
+    text: &'a str,
+}
+
+impl WebhookConfig {
+    fn wants(&self, kind: NotificationKind) -> bool {
+        notifications::wanted(&self.events, kind)
+    }
+
+    async fn post(&self, client: &Client, text: &str) -> reqwest::Result<()> {
+        let request = client.post(&self.url);
+        let request = match self.format {
+            WebhookFormat::Discord => request.json(&DiscordMessage { content: text }),
+            WebhookFormat::Slack => request.json(&SlackMessage { text }),
+        };
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Posts the notifications about the server logging into `log` to `webhooks`, until it stops
+///
+/// The returned receiver completes once the server stopped notification was delivered, so awaiting it makes sure it
+/// went out before the process exits.
+pub fn configure(log: ServerLog, webhooks: Vec<WebhookConfig>) -> Option<oneshot::Receiver<()>> {
+    if webhooks.is_empty() {
+        return None;
+    }
+    let (stopped, delivered) = oneshot::channel();
+    SERVERS.lock().insert(
+        log,
+        Webhooks {
+            configs: Arc::new(webhooks),
+            stopped,
+        },
+    );
+    Some(delivered)
+}
+
+/// Posts every notification to the webhooks of its server interested in it, for every server at once
+pub fn spawn() {
+    if SPAWNED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    let mut bus = notifications::subscribe();
+    let client = Client::new();
+    tokio::spawn(async move {
+        while let Some(notification) = notifications::next(&mut bus, "Webhooks").await {
+            let Some(webhooks) = SERVERS
+                .lock()
+                .get(&notification.server)
+                .map(|server| server.configs.clone())
+            else {
+                continue;
+            };
+            let text = format_text(&notification);
+            for webhook in webhooks.iter().filter(|w| w.wants(notification.kind)) {
+                if let Err(err) = webhook.post(&client, &text).await {
+                    // the url contains the secret of the webhook
+                    warn!("Failed to post to webhook: {}", err.without_url());
+                }
+            }
+
+            // the next start of the server configures its webhooks again
+            if notification.kind == NotificationKind::ServerStopped
+                && let Some(server) = SERVERS.lock().remove(&notification.server)
+            {
+                let _ = server.stopped.send(());
+            }
+        }
+    });
+}
+
+fn format_text(notification: &Notification) -> String {
+    let icon = match notification.severity {
+        Severity::Info => "ℹ️",
+        Severity::Warning => "⚠️",
+        Severity::Error => "🛑",
+    };
+    format!("{icon} {}", notification.text())
+}
@@ -0,0 +1,285 @@
+use crate::config::Theme;
+use crate::notifications::{Notification, Severity};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph};
+
+/// A bordered message centered over the area it is rendered in
+pub struct Popup<'a> {
+    message: Line<'a>,
+    theme: Theme,
+}
+
+impl<'a> Popup<'a> {
+    pub fn new(message: impl Into<Line<'a>>, theme: Theme) -> Self {
+        Self {
+            message: message.into(),
+            theme,
+        }
+    }
+}
+
+impl Widget for Popup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = self.message.width() as u16 + 4;
+        let area = area.centered(Constraint::Length(width), Constraint::Length(3));
+
+        Clear.render(area, buf);
+        Paragraph::new(self.message)
+            .centered()
+            .block(Block::bordered().border_style(self.theme.accent))
+            .render(area, buf);
+    }
+}
+
+/// Bordered lines centered over the area they are rendered in, shown while something starts up
+#[cfg(feature = "plugin")]
+pub struct Splash<'a> {
+    title: &'a str,
+    lines: Vec<Line<'a>>,
+    theme: Theme,
+}
+
+#[cfg(feature = "plugin")]
+impl<'a> Splash<'a> {
+    pub const fn new(title: &'a str, lines: Vec<Line<'a>>, theme: Theme) -> Self {
+        Self {
+            title,
+            lines,
+            theme,
+        }
+    }
+}
+
+#[cfg(feature = "plugin")]
+impl Widget for Splash<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = self
+            .lines
+            .iter()
+            .map(Line::width)
+            .max()
+            .unwrap_or_default()
+            .max(self.title.len()) as u16
+            + 4;
+        let area = area.centered(
+            Constraint::Length(width),
+            Constraint::Length(self.lines.len() as u16 + 2),
+        );
+
+        Clear.render(area, buf);
+        Paragraph::new(self.lines)
+            .block(
+                Block::bordered()
+                    .title(self.title)
+                    .border_style(self.theme.accent),
+            )
+            .render(area, buf);
+    }
+}
+
+/// A notification shown in the top right corner of the area it is rendered in
+pub struct Toast<'a> {
+    notification: &'a Notification,
+    theme: Theme,
+}
+
+impl<'a> Toast<'a> {
+    /// Height of the toast including its border
+    pub const HEIGHT: u16 = 3;
+
+    pub const fn new(notification: &'a Notification, theme: Theme) -> Self {
+        Self {
+            notification,
+            theme,
+        }
+    }
+}
+
+impl Widget for Toast<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let color = match self.notification.severity {
+            Severity::Info => self.theme.accent,
+            Severity::Warning => Color::Yellow,
+            Severity::Error => Color::Red,
+        };
+        let width = (self.notification.message.width() as u16 + 4).min(area.width / 2);
+        let area = Rect::new(
+            area.right().saturating_sub(width),
+            area.y,
+            width,
+            Self::HEIGHT.min(area.height),
+        );
+
+        Clear.render(area, buf);
+        Paragraph::new(self.notification.message.as_str())
+            .block(Block::bordered().border_style(color))
+            .render(area, buf);
+    }
+}
+
+/// An entry of a [`Tree`], its children are hidden until it is expanded
+#[derive(Debug, Clone, Default)]
+pub struct TreeNode {
+    label: Line<'static>,
+    children: Vec<TreeNode>,
+    expanded: bool,
+}
+
+impl TreeNode {
+    pub fn leaf(label: impl Into<Line<'static>>) -> Self {
+        Self {
+            label: label.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn branch(label: impl Into<Line<'static>>, children: Vec<Self>) -> Self {
+        Self {
+            label: label.into(),
+            children,
+            expanded: false,
+        }
+    }
+
+    /// Shows the children of this node right away
+    #[must_use]
+    pub const fn expanded(mut self) -> Self {
+        self.expanded = true;
+        self
+    }
+
+    /// The label of this node and its children indented below it, whether they are shown or not
+    pub fn lines(&self, depth: usize) -> Vec<String> {
+        let mut lines = vec![format!("{}{}", "  ".repeat(depth), self.label)];
+        for child in &self.children {
+            lines.extend(child.lines(depth + 1));
+        }
+        lines
+    }
+}
+
+/// Collapsible nodes shown in a bordered modal over the area they are rendered in
+pub struct Tree {
+    title: String,
+    nodes: Vec<TreeNode>,
+    state: ListState,
+    theme: Theme,
+}
+
+impl Tree {
+    pub fn new(title: impl Into<String>, nodes: Vec<TreeNode>, theme: Theme) -> Self {
+        Self {
+            title: title.into(),
+            nodes,
+            state: ListState::default().with_selected(Some(0)),
+            theme,
+        }
+    }
+
+    /// Every node that is shown, with the path of child indices leading to it
+    fn visible(&self) -> Vec<(Vec<usize>, &TreeNode)> {
+        fn walk<'a>(
+            nodes: &'a [TreeNode],
+            path: &mut Vec<usize>,
+            visible: &mut Vec<(Vec<usize>, &'a TreeNode)>,
+        ) {
+            for (index, node) in nodes.iter().enumerate() {
+                path.push(index);
+                visible.push((path.clone(), node));
+                if node.expanded {
+                    walk(&node.children, path, visible);
+                }
+                path.pop();
+            }
+        }
+        let mut visible = Vec::new();
+        walk(&self.nodes, &mut Vec::new(), &mut visible);
+        visible
+    }
+
+    fn node(&mut self, path: &[usize]) -> Option<&mut TreeNode> {
+        let (first, rest) = path.split_first()?;
+        let mut node = self.nodes.get_mut(*first)?;
+        for index in rest {
+            node = node.children.get_mut(*index)?;
+        }
+        Some(node)
+    }
+
+    /// Handles a key pressed while the tree is shown, returning whether it should stay open
+    ///
+    /// Up and Down move the selection, Right or Enter expand the selected node, Left collapses it or moves to its
+    /// parent and Esc closes the tree.
+    pub fn handle_key(&mut self, event: KeyEvent) -> bool {
+        let visible: Vec<Vec<usize>> = self.visible().into_iter().map(|(path, _)| path).collect();
+        let selected = self
+            .state
+            .selected()
+            .unwrap_or_default()
+            .min(visible.len().saturating_sub(1));
+        let Some(path) = visible.get(selected).cloned() else {
+            return event.code != KeyCode::Esc;
+        };
+        match event.code {
+            KeyCode::Esc | KeyCode::Char('q') => return false,
+            KeyCode::Up => self.state.select_previous(),
+            KeyCode::Down if selected + 1 < visible.len() => self.state.select_next(),
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(node) = self.node(&path) {
+                    node.expanded = event.code == KeyCode::Right || !node.expanded;
+                }
+            }
+            KeyCode::Left => match self.node(&path) {
+                Some(node) if node.expanded => node.expanded = false,
+                _ if path.len() > 1 => {
+                    let parent = &path[..path.len() - 1];
+                    let index = visible
+                        .iter()
+                        .position(|visible| visible.as_slice() == parent);
+                    self.state.select(index);
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+        true
+    }
+}
+
+impl Widget for &mut Tree {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.centered(Constraint::Percentage(80), Constraint::Percentage(80));
+        let items: Vec<ListItem> = self
+            .visible()
+            .into_iter()
+            .map(|(path, node)| {
+                let depth = path.len() - 1;
+                let marker = match (node.children.is_empty(), node.expanded) {
+                    (true, _) => "  ",
+                    (false, true) => "▾ ",
+                    (false, false) => "▸ ",
+                };
+                let mut line = node.label.clone();
+                line.spans
+                    .insert(0, Span::raw(format!("{}{marker}", "  ".repeat(depth))));
+                ListItem::new(line)
+            })
+            .collect();
+
+        Clear.render(area, buf);
+        StatefulWidget::render(
+            List::new(items)
+                .highlight_style(Style::new().reversed())
+                .block(
+                    Block::bordered()
+                        .title(self.title.as_str())
+                        .title_bottom("←/→ collapse/expand, Esc closes".fg(self.theme.muted))
+                        .border_style(self.theme.accent),
+                ),
+            area,
+            buf,
+            &mut self.state,
+        );
+    }
+}
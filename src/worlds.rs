@@ -0,0 +1,153 @@
+use anyhow::{Context, bail};
+use std::sync::Arc;
+use steel_core::server::Server;
+use steel_core::world::{GameRuleValue, World};
+use steel_utils::types::Difficulty;
+
+const USAGE: &str = "usage: world <name> <time <day | noon | night | midnight | ticks> | weather <clear | rain | thunder> | difficulty <peaceful | easy | normal | hard> | gamerule <rule> <value>>";
+
+/// Ticks in one day of a world
+const DAY_TICKS: i64 = 24000;
+
+/// The weather in a world
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weather {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+impl Weather {
+    const ALL: [Self; 3] = [Self::Clear, Self::Rain, Self::Thunder];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Clear => "clear",
+            Self::Rain => "rain",
+            Self::Thunder => "thunder",
+        }
+    }
+
+    /// The weather currently in `world`
+    pub fn of(world: &World) -> Self {
+        match (world.is_raining(), world.is_thundering()) {
+            (_, true) => Self::Thunder,
+            (true, false) => Self::Rain,
+            (false, false) => Self::Clear,
+        }
+    }
+}
+
+/// The gamerules of `world` with their values, by name
+pub fn game_rules(world: &World) -> Vec<(String, GameRuleValue)> {
+    let mut rules: Vec<_> = world
+        .game_rules()
+        .iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+    rules.sort_by(|(a, _), (b, _)| a.cmp(b));
+    rules
+}
+
+/// Sets the gamerule `rule` of `world` to `value`, parsed by the type of the rule
+fn set_game_rule(world: &World, rule: &str, value: &str) -> anyhow::Result<()> {
+    let (_, current) = game_rules(world)
+        .into_iter()
+        .find(|(name, _)| name == rule)
+        .with_context(|| format!("there is no gamerule called {rule}"))?;
+    let value = match current {
+        GameRuleValue::Bool(_) => GameRuleValue::Bool(
+            value
+                .parse()
+                .with_context(|| format!("{rule} is either true or false"))?,
+        ),
+        GameRuleValue::Int(_) => GameRuleValue::Int(
+            value
+                .parse()
+                .with_context(|| format!("{rule} is a whole number"))?,
+        ),
+    };
+    world.set_game_rule(rule, value);
+    Ok(())
+}
+
+/// The world of `server` whose name is or ends with `name`, like `overworld` for `minecraft:overworld`
+pub fn find<'a>(server: &'a Server, name: &str) -> Option<(String, &'a Arc<World>)> {
+    server
+        .worlds
+        .iter()
+        .map(|(key, world)| (key.to_string(), world))
+        .find(|(key, _)| key == name || key.ends_with(&format!(":{name}")))
+}
+
+/// Runs the `world` console command, returning its output
+///
+/// `world` lists the worlds, `world <name> time`, `weather` and `difficulty` change one of them.
+/// `world <name> gamerule <rule> <value>` changes one of its gamerules.
+pub fn command(server: &Server, args: &str) -> anyhow::Result<Vec<String>> {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let (name, field, value) = match words.as_slice() {
+        [] => {
+            return Ok(server
+                .worlds
+                .iter()
+                .map(|(name, world)| {
+                    format!(
+                        "{name}: day time {}, {}, {}",
+                        world.day_time() % DAY_TICKS,
+                        Weather::of(world).name(),
+                        world.difficulty()
+                    )
+                })
+                .collect());
+        }
+        [name, field, value] => (*name, *field, *value),
+        [name, "gamerule", rule, value] => {
+            let (name, world) =
+                find(server, name).with_context(|| format!("there is no world called {name}"))?;
+            set_game_rule(world, rule, value)?;
+            return Ok(vec![format!(
+                "Set the gamerule {rule} of {name} to {value}"
+            )]);
+        }
+        _ => bail!(USAGE),
+    };
+    let (name, world) =
+        find(server, name).with_context(|| format!("there is no world called {name}"))?;
+
+    match field {
+        "time" => {
+            let time = match value {
+                "day" => 1000,
+                "noon" => 6000,
+                "night" => 13000,
+                "midnight" => 18000,
+                ticks => ticks
+                    .parse::<i64>()
+                    .with_context(|| format!("`{ticks}` is not a time of day"))?,
+            };
+            // keeps the days that passed, only the time within the current one changes
+            let day = world.day_time().div_euclid(DAY_TICKS);
+            world.set_day_time(day * DAY_TICKS + time.rem_euclid(DAY_TICKS));
+        }
+        "weather" => {
+            let weather = Weather::ALL
+                .into_iter()
+                .find(|weather| weather.name() == value)
+                .context(USAGE)?;
+            world.set_weather(weather != Weather::Clear, weather == Weather::Thunder);
+        }
+        "difficulty" => {
+            let difficulty = match value {
+                "peaceful" => Difficulty::Peaceful,
+                "easy" => Difficulty::Easy,
+                "normal" => Difficulty::Normal,
+                "hard" => Difficulty::Hard,
+                _ => bail!(USAGE),
+            };
+            world.set_difficulty(difficulty);
+        }
+        _ => bail!(USAGE),
+    }
+    Ok(vec![format!("Set the {field} of {name} to {value}")])
+}
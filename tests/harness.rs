@@ -0,0 +1,129 @@
+//! Drives the terminal ui against a real server through the `TestHarness`
+
+use ratatui::crossterm::event::{Event, KeyCode, KeyModifiers};
+use std::sync::{Arc, LazyLock, OnceLock};
+use std::time::Duration;
+use steel::SteelServer;
+use steel::config::load_or_create;
+use steel_core::server::Server;
+use steel_registry::REGISTRY;
+use steel_tui::{Action, KeyChord, SteelApp, TestHarness, UiConfig};
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Runs the server shared by every test, the app spawns its tasks onto it
+static RUNTIME: LazyLock<Runtime> =
+    LazyLock::new(|| Runtime::new().expect("failed to build the runtime"));
+
+/// A server with a fresh world in a temporary directory, loaded once since that is slow
+fn server() -> Arc<Server> {
+    static SERVER: OnceLock<Arc<Server>> = OnceLock::new();
+    SERVER
+        .get_or_init(|| {
+            LazyLock::force(&REGISTRY);
+            let dir = tempfile::tempdir()
+                .expect("failed to create a temporary directory")
+                .keep();
+            let mut config = load_or_create(&dir.join("steel_config.json5"))
+                .unwrap_or_else(|err| panic!("failed to create the config: {err}"));
+            config.world_path = dir.join("world");
+            config.server_port = 0;
+            let chunk_runtime = Arc::new(Runtime::new().expect("failed to build the runtime"));
+            RUNTIME
+                .block_on(SteelServer::new(
+                    chunk_runtime,
+                    CancellationToken::new(),
+                    config,
+                ))
+                .unwrap_or_else(|err| panic!("failed to load the world: {err:?}"))
+                .server
+                .clone()
+        })
+        .clone()
+}
+
+fn harness(ui: UiConfig) -> TestHarness {
+    TestHarness::new(server(), ui, 100, 30).expect("failed to build the app")
+}
+
+#[test]
+fn typed_text_shows_in_the_input_line() {
+    let _runtime = RUNTIME.enter();
+    let mut harness = harness(UiConfig::default());
+    harness.type_text("say hello");
+    assert!(harness.snapshot().contains("say hello"));
+}
+
+#[test]
+fn logged_lines_show_in_the_console() {
+    let _runtime = RUNTIME.enter();
+    let mut harness = harness(UiConfig::default());
+    harness.log("a line only this test logs");
+    assert!(harness.snapshot().contains("a line only this test logs"));
+}
+
+#[test]
+fn stopping_asks_first() {
+    let _runtime = RUNTIME.enter();
+    let mut harness = harness(UiConfig::default());
+    harness.press_with(KeyCode::Char('c'), KeyModifiers::CONTROL);
+    assert!(harness.snapshot().contains("Stop the server?"));
+    assert!(!harness.server_stopped());
+
+    harness.press(KeyCode::Char('n'));
+    assert!(!harness.snapshot().contains("Stop the server?"));
+    assert!(!harness.server_stopped());
+
+    harness
+        .press_with(KeyCode::Char('c'), KeyModifiers::CONTROL)
+        .press(KeyCode::Char('y'));
+    assert!(harness.server_stopped());
+    assert!(!harness.exited());
+}
+
+#[test]
+fn stopping_without_confirmation() {
+    let _runtime = RUNTIME.enter();
+    let mut harness = harness(UiConfig {
+        confirm_stop: false,
+        ..UiConfig::default()
+    });
+    harness.press_with(KeyCode::Char('c'), KeyModifiers::CONTROL);
+    assert!(harness.server_stopped());
+}
+
+#[test]
+fn keybinds_are_overridden() {
+    let _runtime = RUNTIME.enter();
+    let chord: KeyChord = "ctrl+q".parse().expect("the chord is valid");
+    let mut ui = UiConfig {
+        confirm_stop: false,
+        ..UiConfig::default()
+    };
+    ui.keybinds.insert(chord, Action::StopServer);
+    let mut harness = harness(ui);
+    harness.press_with(KeyCode::Char('q'), KeyModifiers::CONTROL);
+    assert!(harness.server_stopped());
+}
+
+#[test]
+fn resizing_redraws_at_the_new_size() {
+    let _runtime = RUNTIME.enter();
+    let mut harness = harness(UiConfig::default());
+    harness.send(Event::Resize(60, 12));
+    let snapshot = harness.snapshot();
+    assert_eq!(snapshot.lines().count(), 12);
+    assert!(snapshot.lines().all(|line| line.chars().count() == 60));
+}
+
+#[test]
+fn zero_tick_rate_is_rejected() {
+    let _runtime = RUNTIME.enter();
+    let (_events, event_rx) = mpsc::channel(1);
+    let app = SteelApp::builder(server())
+        .tick_rate(Duration::ZERO)
+        .events(event_rx)
+        .build();
+    assert!(app.is_err());
+}